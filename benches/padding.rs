@@ -0,0 +1,55 @@
+use bus_queue::flavors::arc_swap::Slot;
+use bus_queue::{CachePadded, SwapSlot};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const READERS: usize = 4;
+
+/// Every reader repeatedly loads its own slot while the publisher keeps storing
+/// into the slot next to it, which is where false sharing shows up.
+fn neighbour_loads<S: SwapSlot<u64> + Send + Sync + 'static>(iterations: u64) {
+    let slots: Arc<Vec<S>> = Arc::new((0..=READERS).map(|_| S::none()).collect());
+    slots.iter().for_each(|slot| slot.store(0));
+    let running = Arc::new(AtomicBool::new(true));
+
+    let writer = {
+        let slots = slots.clone();
+        let running = running.clone();
+        thread::spawn(move || {
+            let mut i = 0;
+            while running.load(Ordering::Relaxed) {
+                slots[READERS].store(i);
+                i += 1;
+            }
+        })
+    };
+    let readers: Vec<_> = (0..READERS)
+        .map(|r| {
+            let slots = slots.clone();
+            thread::spawn(move || {
+                for _ in 0..iterations {
+                    criterion::black_box(slots[r].load());
+                }
+            })
+        })
+        .collect();
+    readers.into_iter().for_each(|r| r.join().unwrap());
+    running.store(false, Ordering::Relaxed);
+    writer.join().unwrap();
+}
+
+fn padding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbour_loads");
+    group.bench_function("unpadded", |b| {
+        b.iter(|| neighbour_loads::<Slot<u64>>(10_000))
+    });
+    group.bench_function("padded", |b| {
+        b.iter(|| neighbour_loads::<CachePadded<Slot<u64>>>(10_000))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, padding);
+criterion_main!(benches);
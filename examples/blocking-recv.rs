@@ -0,0 +1,27 @@
+use bus_queue::flavors::arc_swap::bounded;
+use std::thread;
+
+fn main() {
+    let (tx, rx) = bounded(10);
+    let consumers: Vec<_> = (0..2)
+        .map(|_| {
+            let rx = rx.clone();
+            // Parks until an item arrives, ending once the publisher is gone.
+            thread::spawn(move || {
+                let mut received = Vec::new();
+                while let Ok(x) = rx.recv() {
+                    received.push(*x);
+                }
+                received
+            })
+        })
+        .collect();
+    drop(rx);
+
+    (0..5).for_each(|x| tx.broadcast(x).unwrap());
+    drop(tx);
+
+    for consumer in consumers {
+        assert_eq!(consumer.join().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::position::{Position, RestoreError};
+use crate::ring_buffer::TryRecvError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::ops::Range;
+
+/// Receiver tracking which items were processed, for at-least-once processing over
+/// the lossy bus.
+///
+/// Items are acked explicitly, and on error the receiver rewinds to the oldest
+/// unacked item to receive it again, as long as the bus still retains it.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use bus_queue::AckingSubscriber;
+///
+/// let (tx, rx) = bounded(10);
+/// let mut rx = AckingSubscriber::new(rx);
+/// (0..3).for_each(|x| tx.broadcast(x).unwrap());
+///
+/// let (seq, _) = rx.try_recv().unwrap();
+/// rx.ack(seq);
+/// assert_eq!(*rx.try_recv().unwrap().1, 1);
+/// // Processing item 1 failed, receive it again.
+/// rx.rewind().unwrap();
+/// assert_eq!(*rx.try_recv().unwrap().1, 1);
+/// ```
+pub struct AckingSubscriber<T, S: SwapSlot<T>> {
+    subscriber: Subscriber<T, S>,
+    /// Sequence of the oldest unacked item
+    acked: usize,
+}
+
+impl<T, S: SwapSlot<T>> AckingSubscriber<T, S> {
+    /// Wraps the receiver, the items it already received counting as acked.
+    pub fn new(subscriber: Subscriber<T, S>) -> Self {
+        Self {
+            acked: subscriber.ri.get(),
+            subscriber,
+        }
+    }
+
+    /// Receives the next item like `Subscriber::try_recv`, along with the sequence to ack it with.
+    pub fn try_recv(&mut self) -> Result<(usize, S::Shared), TryRecvError> {
+        let item = self.subscriber.try_recv()?;
+        Ok((self.subscriber.ri.get().wrapping_sub(1), item))
+    }
+
+    /// Acks every item up to and including the one at `seq`. Acking an item
+    /// already acked, or not received yet, is ignored.
+    pub fn ack(&mut self, seq: usize) {
+        if seq >= self.acked && seq < self.subscriber.ri.get() {
+            self.acked = seq + 1;
+        }
+    }
+
+    /// Returns the sequences received but not acked yet. It also covers the items
+    /// missed because the receiver was lapped.
+    pub fn unacked(&self) -> Range<usize> {
+        self.acked..self.subscriber.ri.get()
+    }
+
+    /// Moves the receiver back to the oldest unacked item.
+    ///
+    /// Fails if some of the unacked items are no longer retained, moving to the
+    /// oldest retained item instead.
+    pub fn rewind(&mut self) -> Result<(), RestoreError> {
+        let checkpoint = self.checkpoint();
+        let restored = self.subscriber.restore(checkpoint);
+        if restored.is_err() {
+            self.acked = self.subscriber.ri.get();
+        }
+        restored
+    }
+
+    /// Returns the position of the oldest unacked item, to resume from after a restart
+    /// with `Subscriber::restore`.
+    pub fn checkpoint(&self) -> Position {
+        Position {
+            bus_id: self.subscriber.bus_id(),
+            sequence: self.acked as u64,
+        }
+    }
+
+    /// Returns the underlying receiver, positioned after the last received item.
+    pub fn into_inner(self) -> Subscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for AckingSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AckingSubscriber")
+            .field("subscriber", &self.subscriber)
+            .field("acked", &self.acked)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AckingSubscriber;
+    use crate::flavors::arc_swap::bounded;
+    use crate::position::RestoreError;
+
+    #[test]
+    fn unacked_items_are_received_again() {
+        let (publisher, subscriber) = bounded(10);
+        let mut subscriber = AckingSubscriber::new(subscriber);
+        (0..4).for_each(|x| publisher.broadcast(x).unwrap());
+        let received: Vec<_> = (0..3).map(|_| subscriber.try_recv().unwrap().0).collect();
+        assert_eq!(received, vec![0, 1, 2]);
+        subscriber.ack(1);
+        // Acking backwards is ignored.
+        subscriber.ack(0);
+        assert_eq!(subscriber.unacked(), 2..3);
+        assert_eq!(subscriber.checkpoint().sequence, 2);
+
+        subscriber.rewind().unwrap();
+        assert_eq!(*subscriber.try_recv().unwrap().1, 2);
+        assert_eq!(*subscriber.try_recv().unwrap().1, 3);
+    }
+
+    #[test]
+    fn rewind_past_retained_items() {
+        let (publisher, subscriber) = bounded(2);
+        let mut subscriber = AckingSubscriber::new(subscriber);
+        publisher.broadcast(0).unwrap();
+        assert_eq!(*subscriber.try_recv().unwrap().1, 0);
+        (1..4).for_each(|x| publisher.broadcast(x).unwrap());
+        assert_eq!(subscriber.rewind(), Err(RestoreError::NotRetained));
+        assert_eq!(subscriber.unacked(), 2..2);
+        assert_eq!(*subscriber.try_recv().unwrap().1, 2);
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Length of the windows the publish rate is measured over.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Tracks the high-water marks of the subscribers' lag and of the publish rate,
+/// enabled through `Builder::track_capacity`.
+#[derive(Debug)]
+pub(crate) struct Advisor {
+    base: Instant,
+    /// Nanoseconds since base the current window started at
+    window_start: AtomicU64,
+    /// Items published in the current window
+    window_count: AtomicU64,
+    /// Items per second published during the last complete window
+    last_rate: AtomicU64,
+    /// Highest rate of a complete window
+    peak_rate: AtomicU64,
+    /// Highest number of items a subscriber was behind when receiving
+    peak_lag: AtomicUsize,
+    /// Rate of the window before the one the peak lag was observed in
+    lag_rate: AtomicU64,
+}
+
+impl Advisor {
+    pub fn new() -> Self {
+        Self {
+            base: Instant::now(),
+            window_start: AtomicU64::new(0),
+            window_count: AtomicU64::new(0),
+            last_rate: AtomicU64::new(0),
+            peak_rate: AtomicU64::new(0),
+            peak_lag: AtomicUsize::new(0),
+            lag_rate: AtomicU64::new(0),
+        }
+    }
+
+    /// Counts a published item, closing the current window if it is over.
+    pub fn record_publish(&self) {
+        let now = self.base.elapsed().as_nanos() as u64;
+        let start = self.window_start.load(Ordering::Acquire);
+        let elapsed = now.saturating_sub(start);
+        if elapsed >= RATE_WINDOW.as_nanos() as u64
+            && self
+                .window_start
+                .compare_exchange(start, now, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            let count = self.window_count.swap(1, Ordering::AcqRel);
+            // Windows stretched by pauses in publishing are scaled back to one second.
+            let rate = (count as u128 * 1_000_000_000 / elapsed as u128) as u64;
+            self.last_rate.store(rate, Ordering::Release);
+            self.peak_rate.fetch_max(rate, Ordering::AcqRel);
+        } else {
+            self.window_count.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Records how many items a receiving subscriber is behind, overwritten ones included.
+    pub fn record_lag(&self, lag: usize) {
+        if lag > self.peak_lag.fetch_max(lag, Ordering::AcqRel) {
+            self.lag_rate
+                .store(self.last_rate.load(Ordering::Acquire), Ordering::Release);
+        }
+    }
+
+    /// Returns a capacity holding the peak lag, scaled up to the peak publish rate
+    /// if the lag was observed while publishing slower, with a quarter of headroom.
+    pub fn advice(&self) -> usize {
+        let lag = self.peak_lag.load(Ordering::Acquire) as u128;
+        let lag_rate = self.lag_rate.load(Ordering::Acquire) as u128;
+        let peak_rate = self.peak_rate.load(Ordering::Acquire) as u128;
+        let scaled = if lag_rate > 0 && peak_rate > lag_rate {
+            div_ceil(lag * peak_rate, lag_rate)
+        } else {
+            lag
+        };
+        std::cmp::max((scaled + div_ceil(scaled, 4)) as usize, 1)
+    }
+}
+
+/// `u128::div_ceil`, which needs Rust 1.73.
+fn div_ceil(n: u128, d: u128) -> u128 {
+    n.checked_sub(1).map_or(0, |n| n / d + 1)
+}
@@ -1,55 +1,428 @@
-use crate::publisher::Publisher;
-use crate::ring_buffer::SendError;
+use crate::event::{Event, EventListener};
+use crate::publisher::{PausePolicy, Publisher};
+use crate::ring_buffer::{ChannelState, RingBuffer, SendError};
 use crate::swap_slot::SwapSlot;
-// use piper::Event;
-use event_listener::Event;
-use futures_core::task::{self, Poll};
+use crate::timer;
+use futures_core::{
+    future::Future,
+    task::{self, Poll},
+};
 use futures_sink::Sink;
+use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::task::{Wake, Waker};
+use std::time::{Duration, Instant};
+
+/// When an `AsyncPublisher` wakes the subscribers waiting for items, see
+/// `AsyncPublisher::set_notify_policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyPolicy {
+    /// On every flush, the default
+    Flush,
+    /// On every item published
+    PerItem,
+    /// Once every given number of items published. The last items are only announced
+    /// once enough follow, or on close.
+    EveryN(usize),
+    /// At most once per interval, the items published in between being announced
+    /// together once it ends
+    Coalesced(Duration),
+}
+
+/// Wakes the subscribers waiting on the bus, the critical one first,
+/// up to `limit` of those waiting on `event`.
+fn wake<T, S: SwapSlot<T>>(buffer: &RingBuffer<T, S>, event: &Event, limit: Option<usize>) {
+    buffer.notify_critical();
+    buffer.notify_lanes();
+    match limit {
+        Some(limit) => event.notify_additional(limit),
+        None => event.notify(usize::MAX),
+    }
+}
+
+/// Time after a limited notification the subscribers it left waiting are woken,
+/// see `AsyncPublisher::set_wakeup_limit`
+const STRAGGLERS_DELAY: Duration = Duration::from_millis(10);
+
+/// Wakes the subscribers once a coalescing interval ends, or the subscribers left
+/// waiting by a wakeup limit, from the timer thread.
+struct DelayedWake<T, S: SwapSlot<T>> {
+    buffer: Arc<RingBuffer<T, S>>,
+    event: Arc<Event>,
+    /// true while the wakeup is scheduled
+    scheduled: Arc<AtomicBool>,
+}
+
+impl<T, S: SwapSlot<T>> Wake for DelayedWake<T, S> {
+    fn wake(self: Arc<Self>) {
+        self.scheduled.store(false, Ordering::Release);
+        wake(&self.buffer, &self.event, None);
+    }
+}
 
 pub struct AsyncPublisher<T, S: SwapSlot<T>> {
     pub(super) publisher: Publisher<T, S>,
     pub(super) event: Arc<Event>,
+    /// Maximum time poll_ready stays pending while subscribers ask to slow down
+    pub(super) max_slowdown: Option<Duration>,
+    /// Start of the current slowdown
+    pub(super) slowdown_since: Option<Instant>,
+    pub(super) slowdown_listener: Option<EventListener>,
+    /// true if items are only published on flush
+    pub(super) strict: bool,
+    /// Item sent in strict mode, waiting for the next flush
+    pub(super) pending: Option<T>,
+    pub(super) notify_policy: NotifyPolicy,
+    /// Number of items published since the subscribers were last woken
+    pub(super) unnotified: usize,
+    /// Time the subscribers were last woken, or are to be woken by the coalescing timer
+    pub(super) last_notify: Option<Instant>,
+    /// Wakes the subscribers from the coalescing timer
+    pub(super) delayed_wake: Option<Waker>,
+    /// true while the coalescing timer is running
+    pub(super) wake_scheduled: Arc<AtomicBool>,
+    /// Most subscribers woken per notification, all of them if None
+    pub(super) wakeup_limit: Option<usize>,
+    /// Wakes the subscribers a limited notification left waiting, from the timer
+    pub(super) stragglers_wake: Option<Waker>,
+    /// true while the stragglers are to be woken by the timer
+    pub(super) stragglers_scheduled: Arc<AtomicBool>,
 }
 
+// The pending item is never pinned.
+impl<T, S: SwapSlot<T>> Unpin for AsyncPublisher<T, S> {}
+
 impl<T, S: SwapSlot<T>> From<(Publisher<T, S>, Arc<Event>)> for AsyncPublisher<T, S> {
     fn from(input: (Publisher<T, S>, Arc<Event>)) -> Self {
         Self {
             publisher: input.0,
             event: input.1,
+            max_slowdown: None,
+            slowdown_since: None,
+            slowdown_listener: None,
+            strict: false,
+            pending: None,
+            notify_policy: NotifyPolicy::Flush,
+            unnotified: 0,
+            last_notify: None,
+            delayed_wake: None,
+            wake_scheduled: Arc::new(AtomicBool::new(false)),
+            wakeup_limit: None,
+            stragglers_wake: None,
+            stragglers_scheduled: Arc::new(AtomicBool::new(false)),
         }
     }
 }
 
+impl<T, S: SwapSlot<T>> AsyncPublisher<T, S> {
+    /// Makes poll_ready stay pending for up to `max_slowdown` per item while any
+    /// subscriber asks to slow down, see `AsyncSubscriber::request_slowdown`.
+    ///
+    /// None, the default, ignores slowdown requests.
+    pub fn set_max_slowdown(&mut self, max_slowdown: Option<Duration>) {
+        self.max_slowdown = max_slowdown;
+    }
+
+    /// Makes the sink follow the `Sink` contract to the letter: `start_send` only buffers
+    /// the item, which is published by the next `poll_flush` or `poll_ready`.
+    ///
+    /// By default, items are published as soon as they are sent, and flushing only
+    /// wakes the subscribers.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Pauses publishing, see `Publisher::pause`.
+    pub fn pause(&self, policy: PausePolicy) {
+        self.publisher.pause(policy);
+    }
+
+    /// Resumes publishing, publishing the latest item kept while paused, if any,
+    /// and waking the subscribers.
+    pub fn resume(&self) -> Result<(), SendError<T>> {
+        self.publisher.resume()?;
+        self.notify();
+        Ok(())
+    }
+
+    /// Adds a middleware run on every published item, see `Publisher::add_middleware`.
+    pub fn add_middleware<F>(&mut self, middleware: F)
+    where
+        F: Fn(T) -> Option<T> + Send + Sync + 'static,
+    {
+        self.publisher.add_middleware(middleware);
+    }
+
+    /// Returns the id of the bus, shared by all of its handles, see `Publisher::bus_id`.
+    pub fn bus_id(&self) -> u64 {
+        self.publisher.bus_id()
+    }
+
+    /// Returns the state of the bus, see `Publisher::state`.
+    pub fn state(&self) -> ChannelState {
+        self.publisher.state()
+    }
+
+    /// Returns the number of subscribers asking the publisher to slow down.
+    pub fn slowdown_requests(&self) -> usize {
+        self.publisher.buffer.slowdown_requests()
+    }
+
+    /// Returns the number of subscribers currently waiting for the next item.
+    ///
+    /// When none are, anything published might be overwritten before it is read.
+    pub fn waiting_subscribers(&self) -> usize {
+        self.publisher.buffer.listeners()
+    }
+
+    /// Returns a guard sending through this publisher, which publishes the item buffered
+    /// in strict mode and wakes the subscribers when dropped, e.g. when the task sending
+    /// is cancelled by a select loop before flushing.
+    ///
+    /// ```rust
+    /// use bus_queue::flavors::arc_swap::async_bounded;
+    /// use futures::executor::block_on;
+    /// use futures::StreamExt;
+    /// use futures::sink::Sink;
+    /// use std::pin::Pin;
+    ///
+    /// let (mut publisher, subscriber) = async_bounded(10);
+    /// publisher.set_strict(true);
+    /// let mut guard = publisher.guard();
+    /// Pin::new(&mut guard).start_send(1).unwrap();
+    /// // Cancelled before flushing.
+    /// drop(guard);
+    /// drop(publisher);
+    ///
+    /// assert_eq!(block_on(subscriber.map(|x| *x).collect::<Vec<_>>()), vec![1]);
+    /// ```
+    pub fn guard(&mut self) -> PublisherGuard<'_, T, S> {
+        PublisherGuard { publisher: self }
+    }
+
+    /// Publishes the buffered item, if any, and wakes the subscribers if some of the
+    /// items were not announced yet.
+    fn settle(&mut self) -> Result<(), SendError<T>> {
+        let published = self.publish_pending();
+        if self.unnotified > 0 {
+            self.notify_batch();
+        }
+        published
+    }
+
+    /// Wakes the waiting subscribers, the critical one first.
+    pub(super) fn notify(&self) {
+        wake(&self.publisher.buffer, &self.event, self.wakeup_limit);
+        if let (Some(_), Some(stragglers_wake)) = (self.wakeup_limit, &self.stragglers_wake) {
+            if !self.stragglers_scheduled.swap(true, Ordering::AcqRel) {
+                timer::wake_at(Instant::now() + STRAGGLERS_DELAY, stragglers_wake.clone());
+            }
+        }
+    }
+
+    /// Wakes the waiting subscribers, ending the current batch.
+    fn notify_batch(&mut self) {
+        self.unnotified = 0;
+        self.last_notify = Some(Instant::now());
+        self.notify();
+    }
+
+    /// Wakes the waiting subscribers if the notify policy asks to after a publish.
+    fn published(&mut self) {
+        self.unnotified += 1;
+        match self.notify_policy {
+            NotifyPolicy::Flush => {}
+            NotifyPolicy::PerItem => self.notify_batch(),
+            NotifyPolicy::EveryN(n) => {
+                if self.unnotified >= n {
+                    self.notify_batch();
+                }
+            }
+            NotifyPolicy::Coalesced(interval) => {
+                if self.wake_scheduled.load(Ordering::Acquire) {
+                    return;
+                }
+                let now = Instant::now();
+                let due = match self.last_notify {
+                    Some(last) if now < last + interval => last + interval,
+                    _ => return self.notify_batch(),
+                };
+                if let Some(delayed_wake) = &self.delayed_wake {
+                    self.wake_scheduled.store(true, Ordering::Release);
+                    self.last_notify = Some(due);
+                    self.unnotified = 0;
+                    timer::wake_at(due, delayed_wake.clone());
+                }
+            }
+        }
+    }
+
+    /// Wakes the waiting subscribers if the notify policy asks to on flush.
+    fn flushed(&mut self) {
+        if self.notify_policy == NotifyPolicy::Flush {
+            self.notify_batch();
+        }
+    }
+
+    /// Publishes the item buffered in strict mode, if any.
+    fn publish_pending(&mut self) -> Result<(), SendError<T>> {
+        match self.pending.take() {
+            Some(item) => {
+                self.publisher.broadcast(item)?;
+                self.published();
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn end_slowdown(&mut self) -> Poll<Result<(), SendError<T>>> {
+        self.slowdown_since = None;
+        self.slowdown_listener = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T, S> AsyncPublisher<T, S>
+where
+    T: Send + Sync + 'static,
+    S: SwapSlot<T> + Send + Sync + 'static,
+    S::Shared: Send + Sync,
+{
+    /// Sets when the waiting subscribers are woken, trading their latency for the CPU time
+    /// spent waking them. Can be changed at any time, e.g. to batch wakeups during a
+    /// load spike, the subscribers being woken for the items batched so far.
+    ///
+    /// `NotifyPolicy::Coalesced` wakes them from a timer thread shared by all buses
+    /// when the interval ends.
+    ///
+    /// # Panics
+    ///
+    /// If the policy is `EveryN(0)`.
+    pub fn set_notify_policy(&mut self, policy: NotifyPolicy) {
+        assert!(
+            policy != NotifyPolicy::EveryN(0),
+            "EveryN needs at least one item"
+        );
+        if self.delayed_wake.is_none() {
+            self.delayed_wake = Some(Waker::from(Arc::new(DelayedWake {
+                buffer: self.publisher.buffer.clone(),
+                event: self.event.clone(),
+                scheduled: self.wake_scheduled.clone(),
+            })));
+        }
+        self.notify_policy = policy;
+        self.notify_batch();
+    }
+
+    /// Limits the number of waiting subscribers each notification wakes, the others
+    /// being woken by the following ones, so that a burst of items is handled by a few
+    /// woken subscribers instead of all of them. None, the default, wakes all of them.
+    ///
+    /// The subscribers still waiting 10ms after a limited notification are all woken,
+    /// so that none is left behind once publishing stops. The critical subscriber and
+    /// those in fair wakeup lanes are always woken.
+    pub fn set_wakeup_limit(&mut self, limit: Option<usize>) {
+        if self.stragglers_wake.is_none() {
+            self.stragglers_wake = Some(Waker::from(Arc::new(DelayedWake {
+                buffer: self.publisher.buffer.clone(),
+                event: self.event.clone(),
+                scheduled: self.stragglers_scheduled.clone(),
+            })));
+        }
+        self.wakeup_limit = limit;
+    }
+}
+
+impl<T, S: SwapSlot<T>> std::fmt::Debug for AsyncPublisher<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncPublisher")
+            .field("publisher", &self.publisher)
+            .field("max_slowdown", &self.max_slowdown)
+            .field("strict", &self.strict)
+            .field("notify_policy", &self.notify_policy)
+            .field("wakeup_limit", &self.wakeup_limit)
+            .finish()
+    }
+}
+
 impl<T, S: SwapSlot<T>> Sink<T> for AsyncPublisher<T, S> {
     type Error = SendError<T>;
 
     fn poll_ready(
         self: Pin<&mut Self>,
-        _: &mut task::Context<'_>,
+        cx: &mut task::Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
-        Poll::Ready(Ok(()))
+        let this = self.get_mut();
+        // Makes room for the next item.
+        if this.pending.is_some() {
+            this.publish_pending()?;
+            this.flushed();
+        }
+        let max_slowdown = match this.max_slowdown {
+            Some(max_slowdown) => max_slowdown,
+            None => return Poll::Ready(Ok(())),
+        };
+        loop {
+            if this.slowdown_requests() == 0 {
+                return this.end_slowdown();
+            }
+            let deadline = match this.slowdown_since {
+                Some(since) => since + max_slowdown,
+                None => {
+                    let now = Instant::now();
+                    this.slowdown_since = Some(now);
+                    // Nothing else would wake the task once the slowdown expires.
+                    timer::wake_at(now + max_slowdown, cx.waker().clone());
+                    now + max_slowdown
+                }
+            };
+            if Instant::now() >= deadline {
+                return this.end_slowdown();
+            }
+            match this.slowdown_listener.as_mut() {
+                None => this.slowdown_listener = Some(this.publisher.buffer.listen_slowdown()),
+                Some(listener) => match Pin::new(listener).poll(cx) {
+                    Poll::Ready(()) => this.slowdown_listener = None,
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
     }
 
     fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
-        self.publisher.broadcast(item).and_then(|_| Ok(()))
+        let this = self.get_mut();
+        if this.strict {
+            this.publish_pending()?;
+            this.pending = Some(item);
+            return Ok(());
+        }
+        this.publisher.broadcast(item)?;
+        this.published();
+        Ok(())
     }
 
     fn poll_flush(
         self: Pin<&mut Self>,
         _: &mut task::Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
-        self.event.notify_all();
-        Poll::Ready(Ok(()))
+        let this = self.get_mut();
+        let published = this.publish_pending();
+        this.flushed();
+        Poll::Ready(published)
     }
 
     fn poll_close(
         self: Pin<&mut Self>,
-        cx: &mut task::Context<'_>,
+        _: &mut task::Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
-        self.publisher.close();
-        self.poll_flush(cx)
+        let this = self.get_mut();
+        let published = this.publish_pending();
+        this.publisher.close();
+        this.notify();
+        Poll::Ready(published)
     }
 }
 
@@ -61,9 +434,76 @@ impl<T, S: SwapSlot<T>> PartialEq for AsyncPublisher<T, S> {
 
 impl<T, S: SwapSlot<T>> Drop for AsyncPublisher<T, S> {
     fn drop(&mut self) {
+        // Whoever dropped the sink without flushing it is not told about failures.
+        let _ = self.publish_pending();
         self.publisher.close();
-        self.event.notify_all();
+        self.notify();
     }
 }
 
 impl<T, S: SwapSlot<T>> Eq for AsyncPublisher<T, S> {}
+
+/// Sink borrowing an `AsyncPublisher`, returned by `AsyncPublisher::guard`, that settles
+/// the items sent through it when dropped instead of leaving them unannounced.
+pub struct PublisherGuard<'a, T, S: SwapSlot<T>> {
+    publisher: &'a mut AsyncPublisher<T, S>,
+}
+
+impl<T, S: SwapSlot<T>> Deref for PublisherGuard<'_, T, S> {
+    type Target = AsyncPublisher<T, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.publisher
+    }
+}
+
+impl<T, S: SwapSlot<T>> DerefMut for PublisherGuard<'_, T, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.publisher
+    }
+}
+
+impl<T, S: SwapSlot<T>> Sink<T> for PublisherGuard<'_, T, S> {
+    type Error = SendError<T>;
+
+    fn poll_ready(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut **self).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        Pin::new(&mut **self).start_send(item)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut **self).poll_flush(cx)
+    }
+
+    fn poll_close(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut **self).poll_close(cx)
+    }
+}
+
+impl<T, S: SwapSlot<T>> Drop for PublisherGuard<'_, T, S> {
+    fn drop(&mut self) {
+        // Whoever dropped the guard without flushing it is not told about failures.
+        let _ = self.publisher.settle();
+    }
+}
+
+impl<T, S: SwapSlot<T>> std::fmt::Debug for PublisherGuard<'_, T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PublisherGuard")
+            .field("pending", &self.publisher.pending.is_some())
+            .field("unnotified", &self.publisher.unnotified)
+            .finish()
+    }
+}
@@ -1,8 +1,8 @@
-use crate::ring_buffer::TryRecvError;
-use crate::subscriber::Subscriber;
+use crate::event::{Event, EventListener};
+use crate::ring_buffer::{ChannelState, RingBuffer, TryRecvError};
+use crate::subscriber::{RecvStatus, SkipItemsError, SkipMode, Subscriber};
 use crate::swap_slot::SwapSlot;
-use event_listener::{Event, EventListener};
-//use piper::{Event, EventListener};
+use crate::timer;
 use futures_core::{
     future::Future,
     task::{self, Poll},
@@ -10,33 +10,142 @@ use futures_core::{
 };
 use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Instant;
+
+/// Most slots a single poll loads, or listener wakeups it handles, before yielding,
+/// so that a lapped stream catching up under heavy writes cannot starve the other
+/// tasks of a cooperative executor.
+const POLL_BUDGET: usize = 64;
 
 pub struct AsyncSubscriber<T, S: SwapSlot<T>> {
     pub(super) subscriber: Subscriber<T, S>,
     pub(super) event: Arc<Event>,
     pub(super) listener: Option<EventListener>,
+    /// true if this subscriber is woken ahead of the others
+    pub(super) critical: bool,
+    /// Event lane the subscriber waits in, if fair wakeups are enabled
+    pub(super) lane: Option<usize>,
+    /// Listener for the closing of the bus, kept from the first wait on
+    pub(super) close_listener: Option<EventListener>,
 }
 
 impl<T, S: SwapSlot<T>> From<(Subscriber<T, S>, Arc<Event>)> for AsyncSubscriber<T, S> {
     fn from(input: (Subscriber<T, S>, Arc<Event>)) -> Self {
+        let lane = input.0.buffer.assign_lane();
         Self {
             subscriber: input.0,
             event: input.1,
             listener: None,
+            critical: false,
+            lane,
+            close_listener: None,
         }
     }
 }
 
 impl<T, S: SwapSlot<T>> std::fmt::Debug for AsyncSubscriber<T, S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Subscriber").finish()
+        f.debug_struct("AsyncSubscriber")
+            .field("subscriber", &self.subscriber)
+            .field("listening", &self.listener.is_some())
+            .field("critical", &self.critical)
+            .field("lane", &self.lane)
+            .finish()
     }
 }
 
 impl<T, S: SwapSlot<T>> AsyncSubscriber<T, S> {
-    #[allow(dead_code)]
-    pub fn set_skip_items(&mut self, skip_items: usize) {
-        self.subscriber.set_skip_items(skip_items);
+    /// Sets the number of items the stream skips when lapped, see
+    /// `Subscriber::set_skip_items`.
+    pub fn set_skip_items(&mut self, skip_items: usize) -> usize {
+        self.subscriber.set_skip_items(skip_items)
+    }
+
+    /// Sets the number of items the stream skips when lapped, see
+    /// `Subscriber::set_skip_items_with`.
+    pub fn set_skip_items_with(
+        &mut self,
+        skip_items: usize,
+        mode: SkipMode,
+    ) -> Result<usize, SkipItemsError> {
+        self.subscriber.set_skip_items_with(skip_items, mode)
+    }
+
+    /// Returns the number of items the stream skips when lapped.
+    pub fn skip_items(&self) -> usize {
+        self.subscriber.skip_items()
+    }
+
+    /// Makes the stream only yield every nth published item.
+    pub fn set_sample_every(&mut self, n: usize) {
+        self.subscriber.set_sample_every(n);
+    }
+
+    /// Asks the publisher to slow down, see `Subscriber::request_slowdown`.
+    pub fn request_slowdown(&mut self) {
+        self.subscriber.request_slowdown();
+    }
+
+    /// Withdraws the request made by `request_slowdown`.
+    pub fn release_slowdown(&mut self) {
+        self.subscriber.release_slowdown();
+    }
+
+    /// Marks the stream as the critical subscriber of the bus, woken ahead of the others
+    /// when an item is published. Returns false if another subscriber already is critical.
+    ///
+    /// Clones of the stream are not critical.
+    pub fn set_critical(&mut self, critical: bool) -> bool {
+        if critical == self.critical {
+            return true;
+        }
+        if critical && !self.subscriber.buffer.claim_critical() {
+            return false;
+        }
+        if !critical {
+            self.subscriber.buffer.release_critical();
+        }
+        self.critical = critical;
+        // The next poll listens on the matching event.
+        self.set_listener(None);
+        true
+    }
+
+    /// Returns the retained items published at or after `since`, see `Subscriber::retained_since`.
+    pub fn retained_since(&self, since: std::time::Instant) -> Vec<S::Shared> {
+        self.subscriber.retained_since(since)
+    }
+
+    /// Makes the stream record publish to receive latencies, see `Subscriber::record_latency`.
+    pub fn record_latency(&mut self, enabled: bool) {
+        self.subscriber.record_latency(enabled);
+    }
+
+    /// Returns the latencies recorded so far, None unless enabled with `record_latency`.
+    pub fn latency_histogram(&self) -> Option<crate::LatencyHistogram> {
+        self.subscriber.latency_histogram()
+    }
+
+    /// Returns how many times the stream fell so far behind that it had to skip items.
+    pub fn lap_count(&self) -> usize {
+        self.subscriber.lap_count()
+    }
+
+    /// Checks if the stream is behind by more than half of the queue.
+    pub fn is_lagging(&self) -> bool {
+        self.subscriber.is_lagging()
+    }
+
+    /// Returns the id of the bus, shared by all of its handles, see `Publisher::bus_id`.
+    pub fn bus_id(&self) -> u64 {
+        self.subscriber.bus_id()
+    }
+
+    /// Returns the state of the bus as seen by this stream, see `Subscriber::state`.
+    pub fn state(&self) -> ChannelState {
+        self.subscriber.state()
     }
 
     /// Returns the number of remaining in the stream.
@@ -48,38 +157,144 @@ impl<T, S: SwapSlot<T>> AsyncSubscriber<T, S> {
     pub fn is_empty(&self) -> bool {
         self.subscriber.is_empty()
     }
-}
 
-impl<T, S: SwapSlot<T>> Stream for AsyncSubscriber<T, S> {
-    type Item = Arc<T>;
+    /// Turns the stream into one yielding owned items, cloned out of the shared pointers.
+    pub fn cloned(self) -> Cloned<T, S>
+    where
+        T: Clone,
+    {
+        Cloned { subscriber: self }
+    }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+    /// Turns the stream into one yielding each item along with the number of items lost
+    /// to a lap just before it, see `Subscriber::try_recv_status`.
+    pub fn with_status(self) -> WithStatus<T, S> {
+        WithStatus { subscriber: self }
+    }
+
+    /// Turns the stream into one reconfigured by every policy the given stream yields,
+    /// so that a controller task can loosen or tighten the freshness requirements
+    /// without owning the subscriber. Once the policies end, the last one stays.
+    pub fn with_policy<P>(self, policies: P) -> WithPolicy<T, S, P>
+    where
+        P: Stream<Item = SkipPolicy> + Unpin,
+    {
+        WithPolicy {
+            subscriber: self,
+            policies: Some(policies),
+        }
+    }
+
+    /// Turns the stream into a blocking iterator for synchronous callers, parking the
+    /// thread until the stream is woken instead of spinning, without needing a runtime.
+    pub fn into_blocking_iter(self) -> BlockingIter<T, S> {
+        BlockingIter { subscriber: self }
+    }
+
+    /// Returns a future collecting every item until the publisher is gone,
+    /// for processing the tail of the stream at shutdown.
+    pub fn drain_remaining(&mut self) -> DrainRemaining<'_, T, S> {
+        DrainRemaining {
+            subscriber: self,
+            items: Vec::new(),
+        }
+    }
+
+    /// Returns a future resolving once the publisher is closed or dropped, whether or not
+    /// the remaining items have been received.
+    pub fn closed(&self) -> Closed<T, S> {
+        Closed {
+            buffer: self.subscriber.buffer.clone(),
+            listener: None,
+        }
+    }
+
+    /// Replaces the listener, keeping count of the subscribers waiting on the event.
+    fn set_listener(&mut self, listener: Option<EventListener>) {
+        match (self.listener.is_some(), listener.is_some()) {
+            (false, true) => self.subscriber.buffer.inc_listeners(),
+            (true, false) => self.subscriber.buffer.dec_listeners(),
+            _ => {}
+        }
+        self.listener = listener;
+    }
+}
+
+impl<T, S: SwapSlot<T>> AsyncSubscriber<T, S> {
+    /// Polls for the next item, along with the number of items lost just before it.
+    fn poll_recv(&mut self, cx: &mut task::Context<'_>) -> Poll<Option<(S::Shared, usize)>> {
+        let mut budget = POLL_BUDGET;
         loop {
-            // If this stream is blocked on an event, first make sure it is unblocked.
+            // If this stream is blocked on an event, first make sure it is unblocked,
+            // by an item or by the bus closing, whoever closed it.
             if let Some(listener) = self.listener.as_mut() {
-                futures_core::ready!(Pin::new(listener).poll(cx));
-                self.listener = None;
+                let notified = Pin::new(listener).poll(cx).is_ready();
+                let closed = match self.close_listener.as_mut() {
+                    Some(close_listener) => Pin::new(close_listener).poll(cx).is_ready(),
+                    None => false,
+                };
+                if closed {
+                    self.close_listener = None;
+                }
+                if !notified && !closed {
+                    return Poll::Pending;
+                }
+                self.set_listener(None);
             }
             loop {
-                // Attempt to receive a message.
-                match self.subscriber.try_recv() {
+                // Out of budget with work left: yield, asking to be polled again.
+                if budget == 0 {
+                    self.set_listener(None);
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                // Attempt to receive a message, only touching the slots if there is one.
+                let received = if self.subscriber.has_pending() {
+                    let received = self.subscriber.try_recv_within(&mut budget);
+                    if matches!(received, Err(TryRecvError::Empty)) && budget == 0 {
+                        continue;
+                    }
+                    received.map(|(_, item, skipped)| (item, skipped))
+                } else {
+                    budget -= 1;
+                    Err(TryRecvError::Empty)
+                };
+                match received {
                     Ok(item) => {
                         // The stream is not blocked on an event - drop the listener.
-                        self.listener = None;
+                        self.set_listener(None);
                         return Poll::Ready(Some(item));
                     }
                     Err(TryRecvError::Disconnected) => {
                         // The stream is not blocked on an event - drop the listener.
-                        self.listener = None;
+                        self.set_listener(None);
                         return Poll::Ready(None);
                     }
                     Err(TryRecvError::Empty) => {}
                 }
+                // Over the quota, nothing announces that it allows receiving again.
+                if let Some(backoff) = self.subscriber.over_quota_backoff() {
+                    self.set_listener(None);
+                    timer::wake_at(Instant::now() + backoff, cx.waker().clone());
+                    return Poll::Pending;
+                }
                 // Listen for a send event.
                 match self.listener.as_mut() {
                     None => {
+                        if self.close_listener.is_none() {
+                            let close_listener = self.subscriber.buffer.listen_close();
+                            self.close_listener = Some(close_listener);
+                        }
                         // Store a listener and try sending the message again.
-                        self.listener = Some(self.event.listen())
+                        let listener = if self.critical {
+                            self.subscriber.buffer.listen_critical()
+                        } else {
+                            match self.lane {
+                                Some(lane) => self.subscriber.buffer.listen_lane(lane).unwrap(),
+                                None => self.event.listen(),
+                            }
+                        };
+                        self.set_listener(Some(listener));
                     }
                     Some(_) => {
                         // Go back to the outer loop to poll the listener.
@@ -91,16 +306,34 @@ impl<T, S: SwapSlot<T>> Stream for AsyncSubscriber<T, S> {
     }
 }
 
+impl<T, S: SwapSlot<T>> Stream for AsyncSubscriber<T, S> {
+    type Item = S::Shared;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_recv(cx).map(|item| item.map(|(item, _)| item))
+    }
+}
+
 impl<T, S: SwapSlot<T>> Clone for AsyncSubscriber<T, S> {
     fn clone(&self) -> Self {
         Self {
             subscriber: self.subscriber.clone(),
             event: self.event.clone(),
             listener: None,
+            critical: false,
+            lane: self.subscriber.buffer.assign_lane(),
+            close_listener: None,
         }
     }
 }
 
+impl<T, S: SwapSlot<T>> Drop for AsyncSubscriber<T, S> {
+    fn drop(&mut self) {
+        self.set_listener(None);
+        self.set_critical(false);
+    }
+}
+
 impl<T, S: SwapSlot<T>> PartialEq for AsyncSubscriber<T, S> {
     fn eq(&self, other: &AsyncSubscriber<T, S>) -> bool {
         self.subscriber == other.subscriber
@@ -108,3 +341,231 @@ impl<T, S: SwapSlot<T>> PartialEq for AsyncSubscriber<T, S> {
 }
 
 impl<T, S: SwapSlot<T>> Eq for AsyncSubscriber<T, S> {}
+
+/// Stream yielding owned items, returned by `AsyncSubscriber::cloned`.
+pub struct Cloned<T, S: SwapSlot<T>> {
+    subscriber: AsyncSubscriber<T, S>,
+}
+
+impl<T, S: SwapSlot<T>> Cloned<T, S> {
+    /// Returns the underlying subscriber.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T: Clone, S: SwapSlot<T>> Stream for Cloned<T, S> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.subscriber)
+            .poll_next(cx)
+            .map(|item| item.map(|item| (*item).clone()))
+    }
+}
+
+impl<T, S: SwapSlot<T>> std::fmt::Debug for Cloned<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cloned").finish()
+    }
+}
+
+/// Stream yielding the items along with the drops, returned by `AsyncSubscriber::with_status`.
+pub struct WithStatus<T, S: SwapSlot<T>> {
+    subscriber: AsyncSubscriber<T, S>,
+}
+
+impl<T, S: SwapSlot<T>> WithStatus<T, S> {
+    /// Returns the underlying subscriber.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T, S: SwapSlot<T>> Stream for WithStatus<T, S> {
+    type Item = RecvStatus<S::Shared>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.subscriber
+            .poll_recv(cx)
+            .map(|item| item.map(|(item, skipped)| RecvStatus::new(item, skipped)))
+    }
+}
+
+impl<T, S: SwapSlot<T>> std::fmt::Debug for WithStatus<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WithStatus").finish()
+    }
+}
+
+/// Blocking iterator over a subscriber, returned by `AsyncSubscriber::into_blocking_iter`.
+pub struct BlockingIter<T, S: SwapSlot<T>> {
+    subscriber: AsyncSubscriber<T, S>,
+}
+
+impl<T, S: SwapSlot<T>> BlockingIter<T, S> {
+    /// Returns the underlying subscriber.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T, S: SwapSlot<T>> Iterator for BlockingIter<T, S> {
+    type Item = S::Shared;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        struct Unpark(Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = task::Context::from_waker(&waker);
+        loop {
+            match Pin::new(&mut self.subscriber).poll_next(&mut cx) {
+                Poll::Ready(item) => return item,
+                // Parking returns spuriously at times, polling again is harmless.
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> std::fmt::Debug for BlockingIter<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockingIter")
+            .field("subscriber", &self.subscriber)
+            .finish()
+    }
+}
+
+/// Items a subscriber skips, see `AsyncSubscriber::with_policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkipPolicy {
+    /// Items skipped past the oldest retained one when lapped, see `set_skip_items`
+    pub skip_items: usize,
+    /// Only every nth item is received, see `set_sample_every`
+    pub sample_every: usize,
+}
+
+impl Default for SkipPolicy {
+    fn default() -> Self {
+        Self {
+            skip_items: 0,
+            sample_every: 1,
+        }
+    }
+}
+
+/// Stream reconfigured by a stream of policies, returned by `AsyncSubscriber::with_policy`.
+pub struct WithPolicy<T, S: SwapSlot<T>, P> {
+    subscriber: AsyncSubscriber<T, S>,
+    /// None once the policies have ended
+    policies: Option<P>,
+}
+
+impl<T, S: SwapSlot<T>, P> WithPolicy<T, S, P> {
+    /// Returns the underlying subscriber, configured by the last policy applied.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T, S: SwapSlot<T>, P> Stream for WithPolicy<T, S, P>
+where
+    P: Stream<Item = SkipPolicy> + Unpin,
+{
+    type Item = S::Shared;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        // Applies the latest policy before receiving.
+        while let Some(policies) = this.policies.as_mut() {
+            match Pin::new(policies).poll_next(cx) {
+                Poll::Ready(Some(policy)) => {
+                    this.subscriber.set_skip_items(policy.skip_items);
+                    this.subscriber.set_sample_every(policy.sample_every);
+                }
+                Poll::Ready(None) => this.policies = None,
+                Poll::Pending => break,
+            }
+        }
+        Pin::new(&mut this.subscriber).poll_next(cx)
+    }
+}
+
+impl<T, S: SwapSlot<T>, P> std::fmt::Debug for WithPolicy<T, S, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WithPolicy")
+            .field("subscriber", &self.subscriber)
+            .field("policies_ended", &self.policies.is_none())
+            .finish()
+    }
+}
+
+/// Future returned by `AsyncSubscriber::closed`.
+pub struct Closed<T, S: SwapSlot<T>> {
+    buffer: Arc<RingBuffer<T, S>>,
+    listener: Option<EventListener>,
+}
+
+impl<T, S: SwapSlot<T>> Future for Closed<T, S> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        loop {
+            if !self.buffer.is_available() {
+                self.listener = None;
+                return Poll::Ready(());
+            }
+            match self.listener.as_mut() {
+                // Listen first and check again, so that closing in between is not missed.
+                None => self.listener = Some(self.buffer.listen_close()),
+                Some(listener) => {
+                    futures_core::ready!(Pin::new(listener).poll(cx));
+                    self.listener = None;
+                }
+            }
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> std::fmt::Debug for Closed<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Closed")
+            .field("closed", &!self.buffer.is_available())
+            .finish()
+    }
+}
+
+/// Future returned by `AsyncSubscriber::drain_remaining`.
+pub struct DrainRemaining<'a, T, S: SwapSlot<T>> {
+    subscriber: &'a mut AsyncSubscriber<T, S>,
+    items: Vec<S::Shared>,
+}
+
+// The collected items are never pinned.
+impl<'a, T, S: SwapSlot<T>> Unpin for DrainRemaining<'a, T, S> {}
+
+impl<'a, T, S: SwapSlot<T>> Future for DrainRemaining<'a, T, S> {
+    type Output = Vec<S::Shared>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            match futures_core::ready!(Pin::new(&mut *this.subscriber).poll_next(cx)) {
+                Some(item) => this.items.push(item),
+                None => return Poll::Ready(std::mem::take(&mut this.items)),
+            }
+        }
+    }
+}
+
+impl<'a, T, S: SwapSlot<T>> std::fmt::Debug for DrainRemaining<'a, T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DrainRemaining")
+            .field("items", &self.items.len())
+            .finish()
+    }
+}
@@ -1,5 +1,5 @@
 use std::fmt;
-use std::sync::{atomic::AtomicUsize, atomic::Ordering};
+use std::sync::{atomic::AtomicU64, atomic::AtomicUsize, atomic::Ordering};
 
 pub struct AtomicCounter {
     count: AtomicUsize,
@@ -42,3 +42,54 @@ impl PartialEq for AtomicCounter {
 }
 
 impl Eq for AtomicCounter {}
+
+/// Counter of the items published to a buffer, 64 bit wide whatever the pointer width.
+///
+/// It is read and set as usize like the other counters, but keeps the bits above them,
+/// so that the slot of an item stays the same when usize wraps on 32 bit targets.
+pub(crate) struct SequenceCounter {
+    count: AtomicU64,
+}
+
+impl SequenceCounter {
+    pub(crate) fn new() -> Self {
+        SequenceCounter {
+            count: AtomicU64::new(0),
+        }
+    }
+    #[inline]
+    pub(crate) fn get(&self) -> usize {
+        self.get_wide() as usize
+    }
+    #[inline]
+    pub(crate) fn get_wide(&self) -> u64 {
+        self.count.load(Ordering::Acquire)
+    }
+    /// Sets the counter to the sequence closest to its value whose lower bits are `val`.
+    #[inline]
+    pub(crate) fn set(&self, val: usize) {
+        let wide = self.widen(val);
+        self.count.store(wide, Ordering::Release);
+    }
+    #[cfg(test)]
+    pub(crate) fn set_wide(&self, val: u64) {
+        self.count.store(val, Ordering::Release);
+    }
+    #[inline]
+    pub(crate) fn inc(&self) {
+        self.count.fetch_add(1, Ordering::AcqRel);
+    }
+    /// Returns the sequence closest to the counter whose lower bits are `val`.
+    #[inline]
+    pub(crate) fn widen(&self, val: usize) -> u64 {
+        let wide = self.get_wide();
+        let delta = val.wrapping_sub(wide as usize) as isize as i64;
+        wide.wrapping_add(delta as u64)
+    }
+}
+
+impl fmt::Debug for SequenceCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SequenceCounter: {}", self.get())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::ring_buffer::TryRecvError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Command sent to a receiver through its backchannel, see `Subscriber::with_backchannel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Stops receiving, `try_recv` returning `Empty` until resumed.
+    Pause,
+    /// Receives again after a pause.
+    Resume,
+    /// Skips every pending item but the latest one.
+    SkipToLatest,
+}
+
+/// Receiver managed through a backchannel, returned by `Subscriber::with_backchannel`.
+///
+/// Commands are applied at the next receive. Dropping every sender lifts a pause,
+/// so that a receiver is never left paused without a controller.
+pub struct ControlledSubscriber<T, S: SwapSlot<T>> {
+    subscriber: Subscriber<T, S>,
+    commands: Receiver<Command>,
+    paused: bool,
+}
+
+impl<T, S: SwapSlot<T>> Subscriber<T, S> {
+    /// Returns the receiver along with a sender through which a controller can pause,
+    /// resume or skip it forward without sharing it behind a lock.
+    pub fn with_backchannel(self) -> (ControlledSubscriber<T, S>, Sender<Command>) {
+        let (sender, commands) = mpsc::channel();
+        let controlled = ControlledSubscriber {
+            subscriber: self,
+            commands,
+            paused: false,
+        };
+        (controlled, sender)
+    }
+}
+
+impl<T, S: SwapSlot<T>> ControlledSubscriber<T, S> {
+    /// Applies the pending commands, then receives like `Subscriber::try_recv`.
+    pub fn try_recv(&mut self) -> Result<S::Shared, TryRecvError> {
+        self.apply_commands();
+        if self.paused {
+            return Err(TryRecvError::Empty);
+        }
+        self.subscriber.try_recv()
+    }
+
+    /// Checks if the receiver was paused by the last applied command.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns the underlying receiver, dropping the backchannel.
+    pub fn into_inner(self) -> Subscriber<T, S> {
+        self.subscriber
+    }
+
+    fn apply_commands(&mut self) {
+        loop {
+            match self.commands.try_recv() {
+                Ok(Command::Pause) => self.paused = true,
+                Ok(Command::Resume) => self.paused = false,
+                Ok(Command::SkipToLatest) => {
+                    let wi = self.subscriber.buffer.write_index();
+                    if self.subscriber.ri.get() != wi {
+                        self.subscriber.ri.set(wi.wrapping_sub(1));
+                        self.subscriber.fences.notify();
+                        self.subscriber.sync_cursor();
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.paused = false;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> Iterator for ControlledSubscriber<T, S> {
+    type Item = S::Shared;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_recv().ok()
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for ControlledSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlledSubscriber")
+            .field("subscriber", &self.subscriber)
+            .field("paused", &self.paused)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Command;
+    use crate::flavors::arc_swap::bounded;
+    use crate::ring_buffer::TryRecvError;
+
+    #[test]
+    fn commands_apply_at_next_receive() {
+        let (publisher, subscriber) = bounded(10);
+        let (mut subscriber, controller) = subscriber.with_backchannel();
+        publisher.broadcast(1).unwrap();
+        controller.send(Command::Pause).unwrap();
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+        assert!(subscriber.is_paused());
+        (2..5).for_each(|x| publisher.broadcast(x).unwrap());
+        controller.send(Command::Resume).unwrap();
+        controller.send(Command::SkipToLatest).unwrap();
+        assert_eq!(*subscriber.try_recv().unwrap(), 4);
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn dropped_controller_lifts_pause() {
+        let (publisher, subscriber) = bounded(10);
+        let (mut subscriber, controller) = subscriber.with_backchannel();
+        publisher.broadcast(1).unwrap();
+        controller.send(Command::Pause).unwrap();
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+        drop(controller);
+        assert_eq!(*subscriber.try_recv().unwrap(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::ring_buffer::{RecvError, TryRecvError};
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::hint;
+use std::thread;
+use std::time::Duration;
+
+/// Strategy waiting between the attempts of a polling receiver: spinning first, then
+/// yielding the thread, then sleeping for doubling durations, trading a little latency
+/// for much less CPU the longer nothing is published. See `Subscriber::recv_with_backoff`.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use bus_queue::Backoff;
+/// use std::time::Duration;
+///
+/// let (tx, rx) = bounded(10);
+/// let backoff = Backoff::new()
+///     .spins(16)
+///     .sleeps(Duration::from_micros(10), Duration::from_millis(1));
+/// std::thread::spawn(move || tx.broadcast(1).unwrap());
+///
+/// assert_eq!(*rx.recv_with_backoff(backoff).unwrap(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    spins: u32,
+    yields: u32,
+    min_sleep: Duration,
+    max_sleep: Duration,
+}
+
+impl Backoff {
+    /// Spins for 64 attempts, yields for 16, then sleeps from 50µs up to 1ms.
+    pub fn new() -> Self {
+        Self {
+            spins: 64,
+            yields: 16,
+            min_sleep: Duration::from_micros(50),
+            max_sleep: Duration::from_millis(1),
+        }
+    }
+
+    /// Sets the number of attempts spun between.
+    pub fn spins(mut self, attempts: u32) -> Self {
+        self.spins = attempts;
+        self
+    }
+
+    /// Sets the number of attempts the thread is yielded between, once done spinning.
+    pub fn yields(mut self, attempts: u32) -> Self {
+        self.yields = attempts;
+        self
+    }
+
+    /// Sets the first and the longest sleep between the attempts, once done yielding.
+    pub fn sleeps(mut self, min: Duration, max: Duration) -> Self {
+        self.min_sleep = min;
+        self.max_sleep = max.max(min);
+        self
+    }
+
+    /// Waits after the given number of failed attempts.
+    pub fn snooze(&self, attempt: u32) {
+        if attempt < self.spins {
+            (0..1u32 << attempt.min(6)).for_each(|_| hint::spin_loop());
+        } else if attempt - self.spins < self.yields {
+            thread::yield_now();
+        } else {
+            let doublings = attempt - self.spins - self.yields;
+            let sleep = self
+                .min_sleep
+                .checked_mul(1 << doublings.min(16))
+                .map_or(self.max_sleep, |sleep| sleep.min(self.max_sleep));
+            thread::sleep(sleep);
+        }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, S: SwapSlot<T>> Subscriber<T, S> {
+    /// Receives the next item by polling, waiting between the attempts according to the
+    /// backoff, for consumers which would rather not depend on being woken.
+    /// Fails once the publisher is gone and every retained item was received.
+    pub fn recv_with_backoff(&self, backoff: Backoff) -> Result<S::Shared, RecvError> {
+        let mut attempt = 0u32;
+        loop {
+            match self.try_recv() {
+                Ok(item) => return Ok(item),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {
+                    backoff.snooze(attempt);
+                    attempt = attempt.saturating_add(1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Backoff;
+    use crate::flavors::arc_swap::bounded;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn receives_until_disconnected() {
+        let (tx, rx) = bounded(10);
+        let backoff = Backoff::new().sleeps(Duration::from_micros(1), Duration::from_micros(100));
+        let publisher = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            tx.broadcast(1).unwrap();
+        });
+
+        assert_eq!(*rx.recv_with_backoff(backoff).unwrap(), 1);
+        publisher.join().unwrap();
+        assert!(rx.recv_with_backoff(backoff).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::ring_buffer::TryRecvError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+
+/// Key items are matched by across buses, see `BarrierSubscriber::with_matcher`.
+pub type Matcher<T> = Box<dyn Fn(&T) -> u64 + Send + Sync>;
+
+/// Receiver over several buses yielding aligned snapshots: one item from every bus,
+/// all with the same key, e.g. the book and the trades of the same exchange sequence.
+///
+/// Items without a match on every bus are skipped.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use bus_queue::BarrierSubscriber;
+///
+/// let (book_tx, book_rx) = bounded(10);
+/// let (trades_tx, trades_rx) = bounded(10);
+/// let mut snapshots =
+///     BarrierSubscriber::with_matcher(vec![book_rx, trades_rx], |item: &(u64, &str)| item.0);
+/// book_tx.broadcast((1, "book 1")).unwrap();
+/// book_tx.broadcast((2, "book 2")).unwrap();
+/// trades_tx.broadcast((2, "trades 2")).unwrap();
+///
+/// let snapshot = snapshots.try_recv().unwrap();
+/// assert_eq!((snapshot[0].1, snapshot[1].1), ("book 2", "trades 2"));
+/// ```
+pub struct BarrierSubscriber<T, S: SwapSlot<T>> {
+    subscribers: Vec<Subscriber<T, S>>,
+    /// Oldest unmatched item received from each bus, along with its key
+    heads: Vec<Option<(u64, S::Shared)>>,
+    /// Key of the items, their sequence if None
+    matcher: Option<Matcher<T>>,
+}
+
+impl<T, S: SwapSlot<T>> BarrierSubscriber<T, S> {
+    /// Matches the items by sequence, see `Subscriber::try_recv_sequenced`.
+    pub fn new(subscribers: Vec<Subscriber<T, S>>) -> Self {
+        Self {
+            heads: subscribers.iter().map(|_| None).collect(),
+            subscribers,
+            matcher: None,
+        }
+    }
+
+    /// Matches the items by the key the matcher returns, e.g. a timestamp.
+    /// Keys have to increase on every bus.
+    pub fn with_matcher<F>(subscribers: Vec<Subscriber<T, S>>, matcher: F) -> Self
+    where
+        F: Fn(&T) -> u64 + Send + Sync + 'static,
+    {
+        Self {
+            matcher: Some(Box::new(matcher)),
+            ..Self::new(subscribers)
+        }
+    }
+
+    /// Receives the next snapshot, one item per bus in the order of the subscribers.
+    ///
+    /// Returns `Empty` until every bus has an item matching the others, and
+    /// `Disconnected` once one of the buses has no more items to match.
+    pub fn try_recv(&mut self) -> Result<Vec<S::Shared>, TryRecvError> {
+        loop {
+            for (subscriber, head) in self.subscribers.iter().zip(self.heads.iter_mut()) {
+                if head.is_none() {
+                    let (sequence, item) = subscriber.try_recv_sequenced()?;
+                    let key = match &self.matcher {
+                        Some(matcher) => matcher(&item),
+                        None => sequence,
+                    };
+                    *head = Some((key, item));
+                }
+            }
+            let newest = self.heads.iter().flatten().map(|(key, _)| *key).max();
+            // Drops the items older than the newest one, they can no longer be matched.
+            let mut aligned = true;
+            for head in self.heads.iter_mut() {
+                if matches!(head, Some((key, _)) if Some(*key) != newest) {
+                    *head = None;
+                    aligned = false;
+                }
+            }
+            if aligned {
+                return Ok(self
+                    .heads
+                    .iter_mut()
+                    .filter_map(|head| head.take().map(|(_, item)| item))
+                    .collect());
+            }
+        }
+    }
+
+    /// Returns the underlying subscribers.
+    pub fn into_inner(self) -> Vec<Subscriber<T, S>> {
+        self.subscribers
+    }
+}
+
+impl<T, S: SwapSlot<T>> Iterator for BarrierSubscriber<T, S> {
+    type Item = Vec<S::Shared>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_recv().ok()
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for BarrierSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BarrierSubscriber")
+            .field("subscribers", &self.subscribers)
+            .field(
+                "heads",
+                &self
+                    .heads
+                    .iter()
+                    .map(|head| head.as_ref().map(|(key, _)| *key))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BarrierSubscriber;
+    use crate::flavors::arc_swap::bounded;
+    use crate::ring_buffer::TryRecvError;
+
+    #[test]
+    fn snapshots_are_aligned_by_sequence() {
+        let (publisher1, subscriber1) = bounded(10);
+        let (publisher2, subscriber2) = bounded(10);
+        let mut barrier = BarrierSubscriber::new(vec![subscriber1, subscriber2]);
+        (0..3).for_each(|x| publisher1.broadcast(x).unwrap());
+        assert_eq!(barrier.try_recv(), Err(TryRecvError::Empty));
+        (10..12).for_each(|x| publisher2.broadcast(x).unwrap());
+
+        let snapshots: Vec<_> = barrier.by_ref().map(|s| (*s[0], *s[1])).collect();
+        assert_eq!(snapshots, vec![(0, 10), (1, 11)]);
+        drop(publisher2);
+        assert_eq!(barrier.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn unmatched_items_are_skipped() {
+        let (publisher1, subscriber1) = bounded(10);
+        let (publisher2, subscriber2) = bounded(10);
+        let barrier = BarrierSubscriber::with_matcher(vec![subscriber1, subscriber2], |x: &u64| *x);
+        [1, 3, 4, 6]
+            .iter()
+            .for_each(|x| publisher1.broadcast(*x).unwrap());
+        [2, 3, 5, 6]
+            .iter()
+            .for_each(|x| publisher2.broadcast(*x).unwrap());
+
+        let snapshots: Vec<_> = barrier.map(|s| (*s[0], *s[1])).collect();
+        assert_eq!(snapshots, vec![(3, 3), (6, 6)]);
+    }
+}
@@ -0,0 +1,297 @@
+#[cfg(feature = "async")]
+use crate::async_publisher::AsyncPublisher;
+#[cfg(feature = "async")]
+use crate::async_subscriber::AsyncSubscriber;
+use crate::codec::Codec;
+#[cfg(feature = "async")]
+use crate::event::Event;
+#[cfg(feature = "fault-injection")]
+use crate::fault_injection::Faults;
+use crate::metadata::{Metadata, NewSidecar};
+use crate::overflow::OverflowPolicy;
+use crate::publisher::Publisher;
+use crate::ring_buffer::RingBuffer;
+use crate::sequence::SequenceSource;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builder for buses configured beyond their size.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::Slot;
+/// use bus_queue::Builder;
+///
+/// let (tx, rx) = Builder::new(10)
+///     .allow_no_subscribers(true)
+///     .build::<i32, Slot<i32>>();
+/// drop(rx);
+/// // Retained for the subscribers joining later.
+/// tx.broadcast(1).unwrap();
+/// assert_eq!(*tx.subscribe_at(0).try_recv().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Builder {
+    size: usize,
+    allow_no_subscribers: bool,
+    timestamps: bool,
+    reap_inactive: Option<Duration>,
+    sequence_source: Option<Arc<dyn SequenceSource>>,
+    track_capacity: bool,
+    fair_wakeups: usize,
+    read_quota: Option<u32>,
+    write_quota: Option<u32>,
+    origin_tags: bool,
+    bus_id: Option<u64>,
+    pin_for: Option<usize>,
+    metadata: Option<NewSidecar>,
+    prefault: bool,
+    #[cfg(feature = "fault-injection")]
+    faults: Option<Arc<Faults>>,
+}
+
+impl Builder {
+    /// Starts building a bus retaining up to `size` items.
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            allow_no_subscribers: false,
+            timestamps: false,
+            reap_inactive: None,
+            sequence_source: None,
+            track_capacity: false,
+            fair_wakeups: 0,
+            read_quota: None,
+            write_quota: None,
+            origin_tags: false,
+            bus_id: None,
+            pin_for: None,
+            metadata: None,
+            prefault: false,
+            #[cfg(feature = "fault-injection")]
+            faults: None,
+        }
+    }
+
+    /// Makes broadcasting without subscribers store the item and succeed instead of
+    /// returning it back in an error, so that late subscribers can read the retained items.
+    pub fn allow_no_subscribers(mut self, allow: bool) -> Self {
+        self.allow_no_subscribers = allow;
+        self
+    }
+
+    /// Makes the bus record the time every item is published at,
+    /// enabling `Subscriber::retained_since`.
+    pub fn timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    /// Makes the publisher mark subscribers which have not received for `timeout` as stale,
+    /// excluding them from the subscriber count until they receive again,
+    /// see `Publisher::reap_inactive`.
+    pub fn reap_inactive(mut self, timeout: Duration) -> Self {
+        self.reap_inactive = Some(timeout);
+        self
+    }
+
+    /// Makes the bus stamp every item with a sequence taken from the source,
+    /// e.g. exchange sequence numbers, see `Subscriber::try_recv_sequenced`.
+    pub fn sequence_source<Q: SequenceSource + 'static>(mut self, source: Q) -> Self {
+        self.sequence_source = Some(Arc::new(source));
+        self
+    }
+
+    /// Makes the bus track how far behind subscribers get and how fast items are
+    /// published, enabling `Publisher::capacity_advice`.
+    pub fn track_capacity(mut self, enabled: bool) -> Self {
+        self.track_capacity = enabled;
+        self
+    }
+
+    /// Spreads the async subscribers over `lanes` groups woken in rotating order,
+    /// so that under load the same subscribers are not always woken last and lapped.
+    pub fn fair_wakeups(mut self, lanes: usize) -> Self {
+        self.fair_wakeups = lanes;
+        self
+    }
+
+    /// Limits every subscriber to receiving `per_second` items per second, so that
+    /// tenants sharing a bus cannot starve each other, see `Subscriber::quota_backoff`.
+    pub fn read_quota(mut self, per_second: u32) -> Self {
+        self.read_quota = Some(per_second);
+        self
+    }
+
+    /// Limits every publisher to broadcasting `per_second` items per second,
+    /// see `Publisher::quota_backoff`.
+    pub fn write_quota(mut self, per_second: u32) -> Self {
+        self.write_quota = Some(per_second);
+        self
+    }
+
+    /// Makes the bus tag every item with the origin of its publisher, so that subscribers
+    /// can skip the items of some publishers, see `Subscriber::exclude_origin`.
+    pub fn origin_tags(mut self, enabled: bool) -> Self {
+        self.origin_tags = enabled;
+        self
+    }
+
+    /// Sets the id of the bus instead of a random one, so that the positions saved from
+    /// a bus can be restored on the bus replacing it, e.g. after a restart,
+    /// see `Subscriber::restore`.
+    pub fn bus_id(mut self, id: u64) -> Self {
+        self.bus_id = Some(id);
+        self
+    }
+
+    /// Keeps the items broadcast with `Publisher::broadcast_pinned` for the subscribers
+    /// lapped before receiving them until `publishes` more items are published,
+    /// the size of the bus by default.
+    pub fn pin_for(mut self, publishes: usize) -> Self {
+        self.pin_for = Some(publishes);
+        self
+    }
+
+    /// Makes the bus store metadata of type M alongside the items, see
+    /// `Publisher::broadcast_with_meta`.
+    pub fn metadata<M: Copy + Send + 'static>(mut self) -> Self {
+        self.metadata = Some(|size| Box::new(Metadata::<M>::new(size)));
+        self
+    }
+
+    /// Makes the bus touch its memory once created, on the building thread,
+    /// see `RingBuffer::prefault`.
+    pub fn prefault(mut self, enabled: bool) -> Self {
+        self.prefault = enabled;
+        self
+    }
+
+    /// Makes the bus inject the faults, see the `fault_injection` module.
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_injection(mut self, faults: Arc<Faults>) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Creates a (Publisher, Subscriber) tuple.
+    pub fn build<T, S: SwapSlot<T>>(&self) -> (Publisher<T, S>, Subscriber<T, S>) {
+        let arc_channel = Arc::new(self.ring_buffer());
+        (
+            Publisher::from(arc_channel.clone()),
+            Subscriber::from(arc_channel),
+        )
+    }
+
+    /// Creates an (AsyncPublisher, AsyncSubscriber) tuple.
+    #[cfg(feature = "async")]
+    pub fn build_async<T, S: SwapSlot<T>>(&self) -> (AsyncPublisher<T, S>, AsyncSubscriber<T, S>) {
+        let (publisher, subscriber) = self.build();
+        Self::into_async(publisher, subscriber)
+    }
+
+    /// Creates a (Publisher, Subscriber) tuple whose bus holds the items encoded with
+    /// the codec, see `Codec`.
+    pub fn build_with_codec<T, S, C>(&self, codec: C) -> (Publisher<T, S>, Subscriber<T, S>)
+    where
+        S: SwapSlot<T>,
+        C: Codec<T> + 'static,
+    {
+        let mut buffer = self.ring_buffer();
+        buffer.set_codec(Some(Box::new(codec)));
+        let arc_channel = Arc::new(buffer);
+        (
+            Publisher::from(arc_channel.clone()),
+            Subscriber::from(arc_channel),
+        )
+    }
+
+    /// Creates an (AsyncPublisher, AsyncSubscriber) tuple whose bus holds the items
+    /// encoded with the codec, see `Codec`.
+    #[cfg(feature = "async")]
+    pub fn build_async_with_codec<T, S, C>(
+        &self,
+        codec: C,
+    ) -> (AsyncPublisher<T, S>, AsyncSubscriber<T, S>)
+    where
+        S: SwapSlot<T>,
+        C: Codec<T> + 'static,
+    {
+        let (publisher, subscriber) = self.build_with_codec(codec);
+        Self::into_async(publisher, subscriber)
+    }
+
+    /// Creates a (Publisher, Subscriber) tuple whose bus follows the policy when
+    /// broadcasting to it while full, see `OverflowPolicy`.
+    pub fn build_with_overflow_policy<T, S, P>(
+        &self,
+        policy: P,
+    ) -> (Publisher<T, S>, Subscriber<T, S>)
+    where
+        S: SwapSlot<T>,
+        P: OverflowPolicy<T> + 'static,
+    {
+        let mut buffer = self.ring_buffer();
+        buffer.set_overflow_policy(Some(Box::new(policy)));
+        let arc_channel = Arc::new(buffer);
+        (
+            Publisher::from(arc_channel.clone()),
+            Subscriber::from(arc_channel),
+        )
+    }
+
+    /// Creates an (AsyncPublisher, AsyncSubscriber) tuple whose bus follows the policy
+    /// when broadcasting to it while full, see `OverflowPolicy`.
+    #[cfg(feature = "async")]
+    pub fn build_async_with_overflow_policy<T, S, P>(
+        &self,
+        policy: P,
+    ) -> (AsyncPublisher<T, S>, AsyncSubscriber<T, S>)
+    where
+        S: SwapSlot<T>,
+        P: OverflowPolicy<T> + 'static,
+    {
+        let (publisher, subscriber) = self.build_with_overflow_policy(policy);
+        Self::into_async(publisher, subscriber)
+    }
+
+    #[cfg(feature = "async")]
+    fn into_async<T, S: SwapSlot<T>>(
+        publisher: Publisher<T, S>,
+        subscriber: Subscriber<T, S>,
+    ) -> (AsyncPublisher<T, S>, AsyncSubscriber<T, S>) {
+        let event = Arc::new(Event::new());
+        (
+            AsyncPublisher::from((publisher, event.clone())),
+            AsyncSubscriber::from((subscriber, event)),
+        )
+    }
+
+    fn ring_buffer<T, S: SwapSlot<T>>(&self) -> RingBuffer<T, S> {
+        let mut buffer = RingBuffer::new(self.size);
+        buffer.set_allow_no_subscribers(self.allow_no_subscribers);
+        buffer.set_timestamps(self.timestamps);
+        buffer.set_reap_inactive(self.reap_inactive);
+        buffer.set_sequence_source(self.sequence_source.clone());
+        buffer.set_track_capacity(self.track_capacity);
+        buffer.set_fair_wakeups(self.fair_wakeups);
+        buffer.set_quotas(self.read_quota, self.write_quota);
+        buffer.set_origin_tags(self.origin_tags);
+        if let Some(id) = self.bus_id {
+            buffer.set_id(id);
+        }
+        if let Some(publishes) = self.pin_for {
+            buffer.set_pin_for(publishes);
+        }
+        if let Some(new) = self.metadata {
+            buffer.set_sidecar(new);
+        }
+        #[cfg(feature = "fault-injection")]
+        buffer.set_faults(self.faults.clone());
+        if self.prefault {
+            buffer.prefault();
+        }
+        buffer
+    }
+}
@@ -0,0 +1,174 @@
+use crate::publisher::Publisher;
+use crate::ring_buffer::SendError;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+
+/// Publisher front-end for producers emitting bursts of items.
+///
+/// Items are written into slots claimed up front and published together when the
+/// claim is dropped, with a single write index advance and a single wake-up.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use bus_queue::BurstWriter;
+///
+/// let (tx, rx) = bounded(10);
+/// let mut writer = BurstWriter::new(tx);
+/// {
+///     let mut claim = writer.claim(3);
+///     (0..3).for_each(|x| claim.push(x).unwrap());
+///     // Nothing is visible until the claim is dropped.
+///     assert!(rx.is_empty());
+/// }
+/// assert_eq!(rx.map(|x| *x).collect::<Vec<_>>(), vec![0, 1, 2]);
+/// ```
+pub struct BurstWriter<T, S: SwapSlot<T>> {
+    publisher: Publisher<T, S>,
+}
+
+impl<T, S: SwapSlot<T>> BurstWriter<T, S> {
+    pub fn new(publisher: Publisher<T, S>) -> Self {
+        Self { publisher }
+    }
+
+    /// Claims up to `n` slots, at most the bus's capacity, published when the claim is dropped.
+    ///
+    /// While the publisher is paused, items pushed into the claim are broadcast one by one
+    /// according to the pause policy. Nothing is claimed while over the write quota.
+    pub fn claim(&mut self, n: usize) -> Claim<'_, T, S> {
+        let granted = if self.publisher.is_paused() || self.publisher.quota_backoff().is_some() {
+            0
+        } else {
+            self.publisher.reap_if_due();
+            self.publisher.buffer.claim(n)
+        };
+        Claim {
+            publisher: &self.publisher,
+            granted,
+            written: 0,
+        }
+    }
+
+    /// Returns the underlying publisher.
+    pub fn into_inner(self) -> Publisher<T, S> {
+        self.publisher
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for BurstWriter<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BurstWriter")
+            .field("publisher", &self.publisher)
+            .finish()
+    }
+}
+
+/// Slots claimed by `BurstWriter::claim`, published when dropped.
+///
+/// On a byte bounded bus, the items of a claim can evict each other.
+pub struct Claim<'a, T, S: SwapSlot<T>> {
+    publisher: &'a Publisher<T, S>,
+    /// Number of slots claimed
+    granted: usize,
+    /// Number of slots written to
+    written: usize,
+}
+
+impl<'a, T, S: SwapSlot<T>> Claim<'a, T, S> {
+    /// Writes an item into the next claimed slot, after running the middleware on it.
+    ///
+    /// Fails if every claimed slot has been written to, or if none could be claimed
+    /// because the bus has no subscribers.
+    pub fn push(&mut self, object: T) -> Result<(), SendError<T>> {
+        if self.publisher.is_paused() && self.granted == 0 {
+            return self.publisher.broadcast(object);
+        }
+        if self.written == self.granted {
+            return Err(SendError(object));
+        }
+        if let Some(object) = self.publisher.run_middleware(object) {
+            self.publisher.buffer.store_claimed(self.written, object);
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of items written so far.
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    /// Checks if no item has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// Returns the number of claimed slots left.
+    pub fn remaining(&self) -> usize {
+        self.granted - self.written
+    }
+}
+
+impl<'a, T, S: SwapSlot<T>> Drop for Claim<'a, T, S> {
+    fn drop(&mut self) {
+        if self.granted > 0 {
+            self.publisher.buffer.publish_claimed(self.written);
+            self.publisher.charge_quota(self.written);
+        }
+    }
+}
+
+impl<'a, T, S: SwapSlot<T>> fmt::Debug for Claim<'a, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Claim")
+            .field("granted", &self.granted)
+            .field("written", &self.written)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BurstWriter;
+    use crate::flavors::arc_swap::bounded;
+    use crate::PausePolicy;
+
+    #[test]
+    fn claim_is_bounded() {
+        let (publisher, subscriber) = bounded(2);
+        let mut writer = BurstWriter::new(publisher);
+        {
+            let mut claim = writer.claim(5);
+            assert_eq!(claim.remaining(), 2);
+            claim.push(1).unwrap();
+            claim.push(2).unwrap();
+            assert_eq!(claim.push(3).unwrap_err().0, 3);
+        }
+        let values = subscriber.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![1, 2]);
+    }
+
+    #[test]
+    fn claim_without_subscribers() {
+        let (publisher, subscriber) = bounded(2);
+        drop(subscriber);
+        let mut writer = BurstWriter::new(publisher);
+        let mut claim = writer.claim(1);
+        assert_eq!(claim.push(1).unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn claim_while_paused() {
+        let (publisher, subscriber) = bounded(4);
+        publisher.pause(PausePolicy::KeepLatest);
+        let mut writer = BurstWriter::new(publisher);
+        {
+            let mut claim = writer.claim(2);
+            claim.push(1).unwrap();
+            claim.push(2).unwrap();
+        }
+        assert!(subscriber.is_empty());
+        writer.into_inner().resume().unwrap();
+        assert_eq!(*subscriber.try_recv().unwrap(), 2);
+    }
+}
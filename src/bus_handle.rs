@@ -0,0 +1,220 @@
+use crate::event::{Event, EventListener};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::task::{self, Poll};
+
+/// One-shot flag which can be awaited.
+#[derive(Default)]
+struct Signal {
+    set: AtomicBool,
+    event: Event,
+}
+
+impl Signal {
+    fn set(&self) {
+        self.set.store(true, Ordering::SeqCst);
+        self.event.notify(usize::MAX);
+    }
+
+    fn is_set(&self) -> bool {
+        self.set.load(Ordering::SeqCst)
+    }
+
+    async fn wait(&self) {
+        while !self.is_set() {
+            let listener = self.event.listen();
+            // Check again in case the signal was set before the listener was registered.
+            if self.is_set() {
+                return;
+            }
+            listener.await;
+        }
+    }
+}
+
+/// Ties together the tasks built around a bus (publisher drivers, bridges, exporters, ...)
+/// so they can be torn down in order.
+///
+/// Nothing is spawned by the handle, every task it manages is returned as a
+/// `ManagedTask` future which the application spawns on the executor of its choice.
+#[derive(Default)]
+pub struct BusHandle {
+    /// Stop and done signals of the tasks feeding the bus, in registration order.
+    sources: Vec<(Arc<Signal>, Arc<Signal>)>,
+    /// Done signals of the tasks consuming the bus, in registration order.
+    consumers: Vec<Arc<Signal>>,
+}
+
+impl BusHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Manages a task feeding the bus, such as an `AsyncPublisherDriver`.
+    ///
+    /// Source tasks are stopped (and dropped, closing their publishers) on shutdown.
+    pub fn manage_source<F: Future>(&mut self, task: F) -> ManagedTask<F> {
+        let stop = Arc::new(Signal::default());
+        let done = Arc::new(Signal::default());
+        self.sources.push((stop.clone(), done.clone()));
+        ManagedTask::new(task, Some(stop), done)
+    }
+
+    /// Manages a task consuming the bus, such as a bridge or a metrics exporter.
+    ///
+    /// Consumer tasks are never interrupted, on shutdown they are expected to
+    /// drain what is left in the bus and finish once they observe it is closed.
+    pub fn manage_consumer<F: Future>(&mut self, task: F) -> ManagedTask<F> {
+        let done = Arc::new(Signal::default());
+        self.consumers.push(done.clone());
+        ManagedTask::new(task, None, done)
+    }
+
+    /// Stops the source tasks one by one, closing the bus, and then waits
+    /// for the consumer tasks to drain it.
+    pub async fn shutdown(self) {
+        for (stop, done) in self.sources.iter() {
+            stop.set();
+            done.wait().await;
+        }
+        for done in self.consumers.iter() {
+            done.wait().await;
+        }
+    }
+}
+
+impl std::fmt::Debug for BusHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BusHandle")
+            .field("sources", &self.sources.len())
+            .field("consumers", &self.consumers.len())
+            .finish()
+    }
+}
+
+/// Future wrapping a task managed by a `BusHandle`.
+///
+/// Resolves to the task's output, or to None if it was stopped by `BusHandle::shutdown`.
+pub struct ManagedTask<F> {
+    task: Option<Pin<Box<F>>>,
+    stop: Option<Arc<Signal>>,
+    listener: Option<EventListener>,
+    done: Arc<Signal>,
+}
+
+impl<F> ManagedTask<F> {
+    fn new(task: F, stop: Option<Arc<Signal>>, done: Arc<Signal>) -> Self {
+        Self {
+            task: Some(Box::pin(task)),
+            stop,
+            listener: None,
+            done,
+        }
+    }
+
+    fn finish(&mut self) {
+        self.task = None;
+        self.listener = None;
+        self.done.set();
+    }
+}
+
+impl<F: Future> Future for ManagedTask<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if let Some(stop) = this.stop.as_ref() {
+            loop {
+                if stop.is_set() {
+                    this.finish();
+                    return Poll::Ready(None);
+                }
+                match this.listener.as_mut() {
+                    None => this.listener = Some(stop.event.listen()),
+                    Some(listener) => match Pin::new(listener).poll(cx) {
+                        Poll::Ready(()) => this.listener = None,
+                        Poll::Pending => break,
+                    },
+                }
+            }
+        }
+        match this.task.as_mut() {
+            Some(task) => match task.as_mut().poll(cx) {
+                Poll::Ready(output) => {
+                    this.finish();
+                    Poll::Ready(Some(output))
+                }
+                Poll::Pending => Poll::Pending,
+            },
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// A managed task which is dropped without completing no longer holds up the shutdown.
+impl<F> Drop for ManagedTask<F> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl<F> std::fmt::Debug for ManagedTask<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManagedTask")
+            .field("finished", &self.done.is_set())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BusHandle;
+    use crate::flavors::arc_swap::Slot;
+    use crate::StreamExt2;
+    use futures::executor::{block_on, ThreadPool};
+    use futures::stream::{self, StreamExt};
+    use futures::FutureExt;
+    use futures_test::task::noop_context;
+
+    #[test]
+    fn shutdown_stops_sources_and_drains_consumers() {
+        let pool = ThreadPool::new().unwrap();
+        let mut handle = BusHandle::new();
+
+        // A source which never ends on its own.
+        let (driver, subscriber) = stream::iter(0..3)
+            .chain(stream::pending())
+            .into_bus::<Slot<_>>(10);
+        let mut source = handle.manage_source(driver);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let consumer = handle.manage_consumer(async move {
+            let items: Vec<u32> = subscriber.map(|x| *x).collect().await;
+            tx.send(items).unwrap();
+        });
+
+        // Publish the first items before handing the tasks to the executor.
+        assert!(source.poll_unpin(&mut noop_context()).is_pending());
+        pool.spawn_ok(source.map(drop));
+        pool.spawn_ok(consumer.map(drop));
+        block_on(handle.shutdown());
+
+        // The consumer drained everything published before the source was stopped.
+        assert_eq!(rx.recv().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn shutdown_with_dropped_tasks() {
+        let mut handle = BusHandle::new();
+        let (driver, subscriber) = stream::pending::<u32>().into_bus::<Slot<_>>(1);
+        drop(handle.manage_source(driver));
+        drop(handle.manage_consumer(subscriber.collect::<Vec<_>>()));
+
+        // Dropped tasks do not hold up the shutdown.
+        block_on(handle.shutdown());
+    }
+}
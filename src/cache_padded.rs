@@ -0,0 +1,76 @@
+use crate::swap_slot::SwapSlot;
+
+/// Slot wrapper aligning every slot of the buffer to its own cache line.
+///
+/// Adjacent slots otherwise share cache lines, so subscribers loading neighbouring
+/// slots contend with each other and with the publisher storing into them.
+/// Padding trades memory (one cache line per slot) for less false sharing:
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::Slot;
+/// use bus_queue::CachePadded;
+///
+/// let (tx, rx) = bus_queue::bounded::<u64, CachePadded<Slot<u64>>>(10);
+/// tx.broadcast(1).unwrap();
+/// assert_eq!(*rx.try_recv().unwrap(), 1);
+/// ```
+// x86_64 and aarch64 prefetch cache lines in pairs, hence 128 bytes.
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64")),
+    repr(align(64))
+)]
+#[derive(Debug)]
+pub struct CachePadded<S> {
+    slot: S,
+}
+
+impl<T, S: SwapSlot<T>> SwapSlot<T> for CachePadded<S> {
+    type Shared = S::Shared;
+    const IS_LOCK_FREE: bool = S::IS_LOCK_FREE;
+    const LOAD_ALLOCATES: bool = S::LOAD_ALLOCATES;
+
+    fn store(&self, item: T) {
+        self.slot.store(item)
+    }
+
+    fn load(&self) -> Option<Self::Shared> {
+        self.slot.load()
+    }
+
+    fn clear(&self) {
+        self.slot.clear()
+    }
+
+    fn none() -> Self {
+        CachePadded { slot: S::none() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CachePadded;
+    use crate::flavors::arc_swap::Slot;
+    use crate::swap_slot::SwapSlot;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_padded_alignment() {
+        let slots: Vec<CachePadded<Slot<u8>>> = (0..2).map(|_| CachePadded::none()).collect();
+        let first = &slots[0] as *const _ as usize;
+        let second = &slots[1] as *const _ as usize;
+
+        assert_eq!(first % 64, 0);
+        assert!(second - first >= 64);
+    }
+
+    #[test]
+    fn test_padded_store_load() {
+        let slot = CachePadded::<Slot<_>>::none();
+        slot.store(10);
+
+        assert_eq!(slot.load(), Some(Arc::new(10)));
+        slot.clear();
+        assert_eq!(slot.load(), None);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::publisher::Publisher;
+use crate::ring_buffer::SendError;
+use crate::swap_slot::SwapSlot;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Publisher front-end conflating items by key before they are published.
+///
+/// Items are accumulated for up to `max_delay` or `max_items` distinct keys, an item
+/// replacing the pending item with the same key. The pending items are then published
+/// in the order their keys were first seen.
+///
+/// There is no timer behind the delay, it is checked on every push and by `flush_if_due`,
+/// which producers with gaps in their input are expected to call periodically.
+pub struct CoalescingBuffer<K, T, S: SwapSlot<T>> {
+    publisher: Publisher<T, S>,
+    max_items: usize,
+    max_delay: Duration,
+    /// Pending items in the order their keys were first seen
+    pending: Vec<T>,
+    /// Position of each key's item in pending
+    keys: HashMap<K, usize>,
+    /// Time the oldest pending item was pushed
+    since: Option<Instant>,
+}
+
+impl<K: Hash + Eq, T, S: SwapSlot<T>> CoalescingBuffer<K, T, S> {
+    pub fn new(publisher: Publisher<T, S>, max_items: usize, max_delay: Duration) -> Self {
+        Self {
+            publisher,
+            max_items: std::cmp::max(max_items, 1),
+            max_delay,
+            pending: Vec::new(),
+            keys: HashMap::new(),
+            since: None,
+        }
+    }
+
+    /// Adds an item, replacing the pending one with the same key,
+    /// and publishes the pending items if the batch is full or due.
+    pub fn push(&mut self, key: K, item: T) -> Result<(), SendError<T>> {
+        match self.keys.get(&key) {
+            Some(&index) => self.pending[index] = item,
+            None => {
+                self.keys.insert(key, self.pending.len());
+                self.pending.push(item);
+            }
+        }
+        self.since.get_or_insert_with(Instant::now);
+        if self.pending.len() >= self.max_items {
+            self.flush()
+        } else {
+            self.flush_if_due()
+        }
+    }
+
+    /// Publishes the pending items if the oldest one has waited for `max_delay`.
+    pub fn flush_if_due(&mut self) -> Result<(), SendError<T>> {
+        match self.since {
+            Some(since) if since.elapsed() >= self.max_delay => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Publishes the pending items.
+    ///
+    /// Stops at the first item the bus refuses, dropping the rest of the batch.
+    pub fn flush(&mut self) -> Result<(), SendError<T>> {
+        self.keys.clear();
+        self.since = None;
+        for item in self.pending.drain(..) {
+            self.publisher.broadcast(item)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of pending items.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Checks if there are no pending items.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the underlying publisher, dropping the pending items.
+    pub fn into_inner(self) -> Publisher<T, S> {
+        self.publisher
+    }
+}
+
+impl<K, T, S: SwapSlot<T>> fmt::Debug for CoalescingBuffer<K, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoalescingBuffer")
+            .field("publisher", &self.publisher)
+            .field("max_items", &self.max_items)
+            .field("max_delay", &self.max_delay)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CoalescingBuffer;
+    use crate::flavors::arc_swap::bounded;
+    use std::time::Duration;
+
+    #[test]
+    fn conflates_by_key() {
+        let (publisher, subscriber) = bounded(10);
+        let mut buffer = CoalescingBuffer::new(publisher, 3, Duration::from_secs(60));
+
+        buffer.push("a", 1).unwrap();
+        buffer.push("b", 2).unwrap();
+        buffer.push("a", 3).unwrap();
+        assert_eq!(buffer.len(), 2);
+        assert!(subscriber.is_empty());
+
+        // The third distinct key fills the batch.
+        buffer.push("c", 4).unwrap();
+        assert!(buffer.is_empty());
+
+        let values = subscriber.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![3, 2, 4]);
+    }
+
+    #[test]
+    fn flushes_when_due() {
+        let (publisher, subscriber) = bounded(10);
+        let mut buffer = CoalescingBuffer::new(publisher, 10, Duration::from_millis(5));
+
+        buffer.push(1, 1).unwrap();
+        buffer.flush_if_due().unwrap();
+        assert!(subscriber.is_empty());
+
+        std::thread::sleep(Duration::from_millis(10));
+        buffer.flush_if_due().unwrap();
+        assert_eq!(*subscriber.try_recv().unwrap(), 1);
+    }
+}
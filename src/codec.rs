@@ -0,0 +1,112 @@
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Transform of the items held by a bus, e.g. encryption of sensitive payloads kept
+/// in memory for a long retention, set up with `Builder::build_with_codec`.
+///
+/// Items are encoded when published and decoded when first received, subscribers
+/// receiving the decoded items.
+pub trait Codec<T>: Send + Sync {
+    /// Encodes an item before it is stored.
+    fn encode(&self, item: T) -> T;
+
+    /// Decodes a stored item.
+    fn decode(&self, item: &T) -> T;
+}
+
+/// Sequence and decoded item of a slot.
+type Cached<T, S> = Mutex<Option<(usize, <S as SwapSlot<T>>::Shared)>>;
+
+/// Codec of a buffer, along with the last item decoded from each slot, so that an item
+/// is decoded once however many subscribers receive it.
+pub(crate) struct Decoded<T, S: SwapSlot<T>> {
+    codec: Box<dyn Codec<T>>,
+    cache: Vec<Cached<T, S>>,
+}
+
+impl<T, S: SwapSlot<T>> Decoded<T, S> {
+    pub(crate) fn new(codec: Box<dyn Codec<T>>, size: usize) -> Self {
+        Self {
+            codec,
+            cache: (0..size).map(|_| Mutex::new(None)).collect(),
+        }
+    }
+
+    pub(crate) fn encode(&self, item: T) -> T {
+        self.codec.encode(item)
+    }
+
+    /// Drops the decoded item of a slot being overwritten, not to hold on to it
+    /// any longer than the encoded one.
+    pub(crate) fn forget(&self, index: usize) {
+        *self.cache[index].lock().unwrap() = None;
+    }
+
+    /// Returns the decoded item at `seq` held in the slot at `index`, decoding it unless cached.
+    ///
+    /// Like loading the slot, the item might have been overwritten, so it has to be
+    /// checked after this returns.
+    pub(crate) fn load(&self, seq: usize, index: usize, slot: &S) -> Option<S::Shared> {
+        let mut cached = self.cache[index].lock().unwrap();
+        if let Some((cached_seq, item)) = &*cached {
+            if *cached_seq == seq {
+                return Some(item.clone());
+            }
+        }
+        let decoded = slot.load_with(|item| self.codec.decode(item))?;
+        // Wraps the decoded item in the flavor's shared pointer.
+        let shared = S::none();
+        shared.store(decoded);
+        let item = shared.load()?;
+        *cached = Some((seq, item.clone()));
+        Some(item)
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for Decoded<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decoded")
+            .field("size", &self.cache.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Codec;
+    use crate::flavors::arc_swap::Slot;
+    use crate::Builder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Flips every bit, counting the decoded items.
+    struct Flip(Arc<AtomicUsize>);
+
+    impl Codec<Vec<u8>> for Flip {
+        fn encode(&self, item: Vec<u8>) -> Vec<u8> {
+            item.into_iter().map(|byte| !byte).collect()
+        }
+
+        fn decode(&self, item: &Vec<u8>) -> Vec<u8> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            item.iter().map(|byte| !byte).collect()
+        }
+    }
+
+    #[test]
+    fn items_are_decoded_once() {
+        let decoded = Arc::new(AtomicUsize::new(0));
+        let (publisher, subscriber1) =
+            Builder::new(4).build_with_codec::<_, Slot<_>, _>(Flip(decoded.clone()));
+        let subscriber2 = subscriber1.clone();
+        publisher.broadcast(vec![1, 2]).unwrap();
+        publisher.broadcast(vec![3]).unwrap();
+
+        assert_eq!(*subscriber1.try_recv().unwrap(), vec![1, 2]);
+        assert_eq!(subscriber1.try_recv_with(|item| item[0]), Ok(3));
+        assert_eq!(*subscriber2.try_recv().unwrap(), vec![1, 2]);
+        assert_eq!(subscriber2.drain(), vec![Arc::new(vec![3])]);
+        assert_eq!(decoded.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,309 @@
+//! A look-alike of the synchronous `bus` crate's API built on the arc_swap flavor.
+//!
+//! Broadcasting never blocks by default, slow readers miss items instead.
+//! `Bus::set_lossless` restores the blocking behaviour of the `bus` crate,
+//! where broadcast waits until no reader would miss the item it overwrites.
+use crate::atomic_counter::AtomicCounter;
+use crate::event::Event;
+use crate::flavors::arc_swap::{self, Publisher, Subscriber};
+use crate::ring_buffer::{RecvError, RecvTimeoutError, TryRecvError};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+/// Progress of a reader, shared with the Bus.
+struct ReaderState {
+    /// Read position
+    position: AtomicCounter,
+    /// Nanoseconds since the Bus's creation the reader last read at
+    last_read: AtomicU64,
+    /// true once the reader is dropped
+    dropped: AtomicBool,
+}
+
+/// Broadcasting half of the bus.
+pub struct Bus<T> {
+    publisher: Publisher<T>,
+    len: usize,
+    lossless: bool,
+    /// Readers which have not read for this long are ignored in lossless mode.
+    inactivity_timeout: Option<Duration>,
+    /// Creation time of the bus.
+    base: Instant,
+    /// Progress of the readers, used in lossless mode.
+    readers: Vec<Weak<ReaderState>>,
+    /// Notified whenever a reader reads an item.
+    read_event: Arc<Event>,
+}
+
+impl<T> Bus<T> {
+    /// Creates a bus retaining up to `len` items.
+    pub fn new(len: usize) -> Self {
+        let (publisher, _) = arc_swap::bounded(len);
+        Bus {
+            publisher,
+            len,
+            lossless: false,
+            inactivity_timeout: None,
+            base: Instant::now(),
+            readers: Vec::new(),
+            read_event: Arc::new(Event::new()),
+        }
+    }
+
+    /// Makes broadcast block until no reader would miss an item, like the `bus` crate does.
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    /// Makes lossless mode ignore the readers which have not read for `timeout`,
+    /// so that forgotten readers do not block broadcasting forever.
+    pub fn set_inactivity_timeout(&mut self, timeout: Option<Duration>) {
+        self.inactivity_timeout = timeout;
+    }
+
+    /// Adds a reader which receives the items broadcast from now on.
+    pub fn add_rx(&mut self) -> BusReader<T> {
+        let subscriber = self.publisher.subscribe();
+        let state = Arc::new(ReaderState {
+            position: AtomicCounter::new(subscriber.ri.get()),
+            last_read: AtomicU64::new(self.base.elapsed().as_nanos() as u64),
+            dropped: AtomicBool::new(false),
+        });
+        self.readers.push(Arc::downgrade(&state));
+        BusReader {
+            subscriber,
+            state,
+            base: self.base,
+            read_event: self.read_event.clone(),
+        }
+    }
+
+    /// Broadcasts a value, blocking in lossless mode while a reader would miss an item.
+    pub fn broadcast(&mut self, val: T) {
+        if self.lossless {
+            loop {
+                let listener = self.read_event.listen();
+                if !self.would_lap() {
+                    break;
+                }
+                match self.inactivity_timeout {
+                    // Wake up in time to notice the reader becoming inactive.
+                    Some(timeout) => {
+                        listener.wait_timeout(timeout);
+                    }
+                    None => listener.wait(),
+                }
+            }
+        }
+        // Broadcasting without readers simply drops the value, as in the `bus` crate.
+        let _ = self.publisher.broadcast(val);
+    }
+
+    /// Broadcasts a value, returning it back in lossless mode if a reader would miss an item.
+    pub fn try_broadcast(&mut self, val: T) -> Result<(), T> {
+        if self.lossless && self.would_lap() {
+            return Err(val);
+        }
+        let _ = self.publisher.broadcast(val);
+        Ok(())
+    }
+
+    /// Checks if broadcasting would overwrite an item a reader has not read yet.
+    fn would_lap(&mut self) -> bool {
+        let wi = self.publisher.buffer.write_index();
+        let now = self.base.elapsed().as_nanos() as u64;
+        let timeout = self
+            .inactivity_timeout
+            .map_or(u64::MAX, |timeout| timeout.as_nanos() as u64);
+        self.readers.retain(|reader| reader.strong_count() > 0);
+        self.readers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|reader| !reader.dropped.load(Ordering::Acquire))
+            .filter(|reader| {
+                now.saturating_sub(reader.last_read.load(Ordering::Acquire)) <= timeout
+            })
+            .any(|reader| wi.wrapping_sub(reader.position.get()) >= self.len)
+    }
+}
+
+impl<T> fmt::Debug for Bus<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bus")
+            .field("len", &self.len)
+            .field("lossless", &self.lossless)
+            .field("inactivity_timeout", &self.inactivity_timeout)
+            .finish()
+    }
+}
+
+/// Reading half of the bus, created by `Bus::add_rx`.
+pub struct BusReader<T> {
+    subscriber: Subscriber<T>,
+    /// Progress shared with the Bus.
+    state: Arc<ReaderState>,
+    /// Creation time of the bus.
+    base: Instant,
+    read_event: Arc<Event>,
+}
+
+impl<T: Clone> BusReader<T> {
+    /// Receives the next value without blocking.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let item = self.subscriber.try_recv()?;
+        self.read();
+        Ok((*item).clone())
+    }
+
+    /// Receives the next value, blocking until one is broadcast.
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        let item = self.subscriber.recv()?;
+        self.read();
+        Ok((*item).clone())
+    }
+
+    /// Receives the next value, blocking until one is broadcast or the timeout runs out.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let item = self.subscriber.recv_timeout(timeout)?;
+        self.read();
+        Ok((*item).clone())
+    }
+
+    /// Receives the next value, blocking until one is broadcast or the deadline is reached.
+    pub fn recv_deadline(&mut self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        let item = self.subscriber.recv_deadline(deadline)?;
+        self.read();
+        Ok((*item).clone())
+    }
+
+    /// Returns a blocking iterator over the values, which ends once the bus is dropped.
+    pub fn iter(&mut self) -> BusIter<'_, T> {
+        BusIter { reader: self }
+    }
+
+    /// Lets the Bus know the reader has made progress.
+    fn read(&self) {
+        self.state.position.set(self.subscriber.ri.get());
+        self.state
+            .last_read
+            .store(self.base.elapsed().as_nanos() as u64, Ordering::Release);
+        self.read_event.notify(1);
+    }
+}
+
+impl<T> Drop for BusReader<T> {
+    fn drop(&mut self) {
+        // Unregistered before waking a lossless broadcast waiting for this reader.
+        self.state.dropped.store(true, Ordering::Release);
+        self.read_event.notify(usize::MAX);
+    }
+}
+
+impl<T> fmt::Debug for BusReader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BusReader").finish()
+    }
+}
+
+/// Blocking iterator returned by `BusReader::iter`.
+#[derive(Debug)]
+pub struct BusIter<'a, T> {
+    reader: &'a mut BusReader<T>,
+}
+
+impl<'a, T: Clone> Iterator for BusIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.reader.recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bus;
+    use crate::ring_buffer::{RecvTimeoutError, TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn broadcast_recv() {
+        let mut bus = Bus::new(2);
+        // Broadcasting without readers drops the value.
+        bus.broadcast(0);
+        let mut rx1 = bus.add_rx();
+        let mut rx2 = bus.add_rx();
+
+        bus.broadcast(1);
+        assert_eq!(rx1.recv(), Ok(1));
+        assert_eq!(rx2.try_recv(), Ok(1));
+        assert_eq!(rx1.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            rx1.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+    }
+
+    #[test]
+    fn lossy_by_default() {
+        let mut bus = Bus::new(2);
+        let mut rx = bus.add_rx();
+        for i in 0..4 {
+            assert_eq!(bus.try_broadcast(i), Ok(()));
+        }
+        drop(bus);
+
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn lossless() {
+        let mut bus = Bus::new(2);
+        bus.set_lossless(true);
+        let mut rx = bus.add_rx();
+        bus.broadcast(0);
+        bus.broadcast(1);
+        assert_eq!(bus.try_broadcast(2), Err(2));
+
+        let reader = thread::spawn(move || rx.iter().collect::<Vec<_>>());
+        for i in 2..10 {
+            bus.broadcast(i);
+        }
+        drop(bus);
+
+        assert_eq!(reader.join().unwrap(), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn lossless_ignores_inactive_readers() {
+        let mut bus = Bus::new(1);
+        bus.set_lossless(true);
+        bus.set_inactivity_timeout(Some(Duration::from_millis(10)));
+        let _forgotten = bus.add_rx();
+        bus.broadcast(0);
+        assert_eq!(bus.try_broadcast(1), Err(1));
+
+        // Blocks until the reader is considered inactive.
+        bus.broadcast(1);
+        assert_eq!(bus.try_broadcast(2), Ok(()));
+    }
+
+    #[test]
+    fn lossless_wakes_on_reader_drop() {
+        let mut bus = Bus::new(1);
+        bus.set_lossless(true);
+        let lagging = bus.add_rx();
+        bus.broadcast(0);
+
+        let broadcaster = thread::spawn(move || {
+            // Blocks until the lagging reader is dropped.
+            bus.broadcast(1);
+            bus
+        });
+        drop(lagging);
+        let mut bus = broadcaster.join().unwrap();
+        assert_eq!(bus.try_broadcast(2), Ok(()));
+    }
+}
@@ -0,0 +1,10 @@
+//! Shims mirroring the API of other broadcast channels on top of this crate,
+//! easing the migration of existing code bases.
+#[cfg(all(feature = "arcswap", feature = "blocking"))]
+pub mod bus;
+
+#[cfg(all(feature = "arcswap", feature = "async"))]
+pub mod tokio_broadcast;
+
+#[cfg(feature = "async")]
+pub mod tokio_stream;
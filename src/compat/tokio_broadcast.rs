@@ -0,0 +1,273 @@
+//! A `tokio::sync::broadcast` look-alike built on the arc_swap flavor.
+//!
+//! Unlike tokio's channel, the Sender can not be cloned since the bus has a single publisher,
+//! and sending never waits for slow receivers, they get a `RecvError::Lagged` instead.
+use crate::flavors::arc_swap;
+use crate::ring_buffer::TryRecvError as RawTryRecvError;
+use futures_core::{
+    future::Future,
+    task::{self, Poll},
+    Stream,
+};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub use crate::ring_buffer::SendError;
+
+/// Error returned by `Receiver::recv`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvError {
+    /// The Sender was dropped and every item has been received.
+    Closed,
+    /// The receiver lagged behind and the given number of items was overwritten.
+    /// The next call returns the oldest retained item.
+    Lagged(u64),
+}
+
+/// Error returned by `Receiver::try_recv`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    /// No item is available at the moment.
+    Empty,
+    /// The Sender was dropped and every item has been received.
+    Closed,
+    /// The receiver lagged behind and the given number of items was overwritten.
+    /// The next call returns the oldest retained item.
+    Lagged(u64),
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Closed => write!(f, "channel closed"),
+            RecvError::Lagged(n) => write!(f, "channel lagged by {}", n),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Closed => write!(f, "channel closed"),
+            TryRecvError::Lagged(n) => write!(f, "channel lagged by {}", n),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+/// Creates a channel retaining up to `capacity` items.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (publisher, subscriber) = arc_swap::async_bounded(capacity);
+    (
+        Sender { inner: publisher },
+        Receiver {
+            inner: subscriber,
+            lagged_item: None,
+        },
+    )
+}
+
+/// Sending half of the channel.
+pub struct Sender<T> {
+    inner: arc_swap::AsyncPublisher<T>,
+}
+
+impl<T> Sender<T> {
+    /// Sends a value to all receivers, returning how many receivers there are.
+    ///
+    /// Fails only if there are no receivers.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        self.inner.publisher.broadcast(value)?;
+        self.inner.notify();
+        Ok(self.inner.publisher.sub_count())
+    }
+
+    /// Creates a new Receiver which receives the values sent from now on.
+    pub fn subscribe(&self) -> Receiver<T> {
+        Receiver {
+            inner: (self.inner.publisher.subscribe(), self.inner.event.clone()).into(),
+            lagged_item: None,
+        }
+    }
+
+    /// Returns the number of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.inner.publisher.sub_count()
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender").finish()
+    }
+}
+
+/// Receiving half of the channel.
+pub struct Receiver<T> {
+    inner: arc_swap::AsyncSubscriber<T>,
+    /// Item received along with a lag, returned by the next call
+    lagged_item: Option<Arc<T>>,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receives the next value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        if let Some(item) = self.lagged_item.take() {
+            return Ok((*item).clone());
+        }
+        let ri = self.inner.subscriber.ri.get();
+        match self.inner.subscriber.try_recv() {
+            Ok(item) => self.check_lag(ri, item).map_err(TryRecvError::Lagged),
+            Err(RawTryRecvError::Empty) => Err(TryRecvError::Empty),
+            Err(RawTryRecvError::Disconnected) => Err(TryRecvError::Closed),
+        }
+    }
+
+    /// Receives the next value, waiting for one to be sent.
+    pub fn recv(&mut self) -> Recv<'_, T> {
+        Recv { receiver: self }
+    }
+
+    /// Returns the received value, or holds it back if items were skipped to reach it.
+    fn check_lag(&mut self, ri: usize, item: Arc<T>) -> Result<T, u64> {
+        let missed = self
+            .inner
+            .subscriber
+            .ri
+            .get()
+            .wrapping_sub(1)
+            .wrapping_sub(ri);
+        if missed == 0 {
+            Ok((*item).clone())
+        } else {
+            self.lagged_item = Some(item);
+            Err(missed as u64)
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Creates a new Receiver which receives the values sent from now on.
+    pub fn resubscribe(&self) -> Self {
+        let subscriber = self.inner.subscriber.clone();
+        subscriber
+            .ri
+            .set(self.inner.subscriber.buffer.write_index());
+        Receiver {
+            inner: (subscriber, self.inner.event.clone()).into(),
+            lagged_item: None,
+        }
+    }
+
+    /// Returns the number of values which have not been received yet, at most the
+    /// capacity if the receiver lagged behind.
+    pub fn len(&self) -> usize {
+        let buffer = &self.inner.subscriber.buffer;
+        let ri = buffer.clamp_sequence(self.inner.subscriber.ri.get());
+        buffer.pending(ri) + usize::from(self.lagged_item.is_some())
+    }
+
+    /// Checks if every value sent has been received.
+    pub fn is_empty(&self) -> bool {
+        self.lagged_item.is_none() && self.inner.is_empty()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish()
+    }
+}
+
+/// Future returned by `Receiver::recv`.
+#[derive(Debug)]
+pub struct Recv<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<'a, T: Clone> Future for Recv<'a, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let receiver = &mut *self.receiver;
+        if let Some(item) = receiver.lagged_item.take() {
+            return Poll::Ready(Ok((*item).clone()));
+        }
+        let ri = receiver.inner.subscriber.ri.get();
+        match Pin::new(&mut receiver.inner).poll_next(cx) {
+            Poll::Ready(Some(item)) => {
+                Poll::Ready(receiver.check_lag(ri, item).map_err(RecvError::Lagged))
+            }
+            Poll::Ready(None) => Poll::Ready(Err(RecvError::Closed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{channel, RecvError, TryRecvError};
+    use futures::executor::block_on;
+
+    #[test]
+    fn send_recv() {
+        let (tx, mut rx1) = channel(4);
+        let mut rx2 = tx.subscribe();
+        assert_eq!(tx.receiver_count(), 2);
+
+        assert_eq!(tx.send(10).unwrap(), 2);
+        assert_eq!(block_on(rx1.recv()), Ok(10));
+        assert_eq!(block_on(rx2.recv()), Ok(10));
+        assert_eq!(rx1.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn lagged() {
+        let (tx, mut rx) = channel(2);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+
+        assert_eq!(rx.len(), 2);
+        assert_eq!(block_on(rx.recv()), Err(RecvError::Lagged(3)));
+        assert_eq!(rx.len(), 2);
+        assert!(!rx.is_empty());
+        assert_eq!(block_on(rx.recv()), Ok(3));
+        assert_eq!(rx.try_recv(), Ok(4));
+    }
+
+    #[test]
+    fn closed() {
+        let (tx, mut rx) = channel(2);
+        tx.send(1).unwrap();
+        drop(tx);
+
+        assert_eq!(block_on(rx.recv()), Ok(1));
+        assert_eq!(block_on(rx.recv()), Err(RecvError::Closed));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    }
+
+    #[test]
+    fn no_receivers() {
+        let (tx, rx) = channel(2);
+        drop(rx);
+
+        assert_eq!(tx.send(1).unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn resubscribe() {
+        let (tx, rx) = channel(2);
+        tx.send(1).unwrap();
+        let mut rx2 = rx.resubscribe();
+        tx.send(2).unwrap();
+
+        assert_eq!(rx2.try_recv(), Ok(2));
+        assert_eq!(rx.len(), 2);
+    }
+}
@@ -0,0 +1,109 @@
+//! A `tokio_stream::wrappers::BroadcastStream` look-alike over an `AsyncSubscriber`.
+//!
+//! Items are yielded with the same shape, a `Lagged` error telling how many items were
+//! overwritten before the oldest retained one, which the next poll yields.
+use crate::async_subscriber::{AsyncSubscriber, WithStatus};
+use crate::subscriber::RecvStatus;
+use crate::swap_slot::SwapSlot;
+use futures_core::{
+    task::{self, Poll},
+    Stream,
+};
+use std::fmt;
+use std::pin::Pin;
+
+/// Error yielded by `BroadcastStream`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BroadcastStreamRecvError {
+    /// The receiver lagged behind and the given number of items was overwritten.
+    /// The stream yields the oldest retained item next.
+    Lagged(u64),
+}
+
+impl fmt::Display for BroadcastStreamRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BroadcastStreamRecvError::Lagged(n) => write!(f, "channel lagged by {}", n),
+        }
+    }
+}
+
+impl std::error::Error for BroadcastStreamRecvError {}
+
+/// Stream of the items received by a subscriber, cloned out of their shared pointers.
+pub struct BroadcastStream<T, S: SwapSlot<T>> {
+    inner: WithStatus<T, S>,
+    /// Item received along with a lag, yielded by the next poll
+    lagged_item: Option<S::Shared>,
+}
+
+// The lagged item is never pinned.
+impl<T, S: SwapSlot<T>> Unpin for BroadcastStream<T, S> {}
+
+impl<T, S: SwapSlot<T>> BroadcastStream<T, S> {
+    pub fn new(subscriber: AsyncSubscriber<T, S>) -> Self {
+        Self {
+            inner: subscriber.with_status(),
+            lagged_item: None,
+        }
+    }
+
+    /// Returns the underlying subscriber.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T, S: SwapSlot<T>> From<AsyncSubscriber<T, S>> for BroadcastStream<T, S> {
+    fn from(subscriber: AsyncSubscriber<T, S>) -> Self {
+        Self::new(subscriber)
+    }
+}
+
+impl<T: Clone, S: SwapSlot<T>> Stream for BroadcastStream<T, S> {
+    type Item = Result<T, BroadcastStreamRecvError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(item) = self.lagged_item.take() {
+            return Poll::Ready(Some(Ok((*item).clone())));
+        }
+        let item = match futures_core::ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+            Some(RecvStatus::Fresh(item)) => Ok((*item).clone()),
+            Some(RecvStatus::AfterSkip { item, skipped }) => {
+                self.lagged_item = Some(item);
+                Err(BroadcastStreamRecvError::Lagged(skipped as u64))
+            }
+            None => return Poll::Ready(None),
+        };
+        Poll::Ready(Some(item))
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for BroadcastStream<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BroadcastStream").finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BroadcastStream, BroadcastStreamRecvError};
+    use crate::flavors::arc_swap::async_bounded;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+
+    #[test]
+    fn lagged() {
+        let (mut publisher, subscriber) = async_bounded(2);
+        let stream = BroadcastStream::new(subscriber);
+        for i in 0..5 {
+            block_on(publisher.send(i)).unwrap();
+        }
+        drop(publisher);
+
+        assert_eq!(
+            block_on(stream.collect::<Vec<_>>()),
+            vec![Err(BroadcastStreamRecvError::Lagged(3)), Ok(3), Ok(4)]
+        );
+    }
+}
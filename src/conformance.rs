@@ -0,0 +1,96 @@
+//! Conformance kit for `SwapSlot` implementations.
+//!
+//! `check` runs the semantics every flavor has to share, panicking on the first
+//! violation, so third-party flavors can prove they behave like the in-tree ones:
+//!
+//! ```rust
+//! # #[cfg(feature = "arcswap")]
+//! bus_queue::conformance::check::<bus_queue::flavors::arc_swap::Slot<u64>>();
+//! ```
+use crate::swap_slot::SwapSlot;
+use std::sync::Arc;
+use std::thread;
+
+/// Runs every conformance check against the slot type `S`.
+pub fn check<S>()
+where
+    S: SwapSlot<u64> + Send + Sync + 'static,
+{
+    check_overwrite_visibility::<S>();
+    check_lap_behavior::<S>();
+    check_refcount_hygiene::<S>();
+    check_concurrent_load_store::<S>();
+}
+
+/// A load returns the latest stored item, or nothing for empty and cleared slots.
+pub fn check_overwrite_visibility<S: SwapSlot<u64>>() {
+    let slot = S::none();
+    assert!(slot.load().is_none(), "an empty slot loads an item");
+    slot.store(1);
+    assert_eq!(slot.load().map(|item| *item), Some(1));
+    slot.store(2);
+    assert_eq!(
+        slot.load().map(|item| *item),
+        Some(2),
+        "overwrite not visible"
+    );
+    assert_eq!(slot.load_with(|item| *item), Some(2), "load_with differs");
+    slot.clear();
+    assert!(slot.load().is_none(), "a cleared slot loads an item");
+    assert!(
+        slot.load_with(|_| ()).is_none(),
+        "a cleared slot runs load_with"
+    );
+}
+
+/// A subscriber overtaken by the publisher resumes from the oldest retained item.
+pub fn check_lap_behavior<S: SwapSlot<u64>>() {
+    let (publisher, subscriber) = crate::bounded::<u64, S>(4);
+    for i in 0..10 {
+        publisher.broadcast(i).unwrap();
+    }
+    let received = subscriber.map(|item| *item).collect::<Vec<_>>();
+    assert_eq!(received, vec![6, 7, 8, 9], "lapped subscriber");
+}
+
+/// Loaded items stay valid and unchanged after their slot is overwritten or cleared.
+pub fn check_refcount_hygiene<S: SwapSlot<u64>>() {
+    let slot = S::none();
+    slot.store(1);
+    let first = slot.load().unwrap();
+    let second = first.clone();
+    slot.store(2);
+    assert_eq!(*first, 1, "overwrite changed a loaded item");
+    slot.clear();
+    drop(first);
+    assert_eq!(*second, 1, "clear changed a loaded item");
+}
+
+/// Concurrent loads observe the items of a single writer in order, never torn.
+pub fn check_concurrent_load_store<S>()
+where
+    S: SwapSlot<u64> + Send + Sync + 'static,
+{
+    const ITEMS: u64 = 10_000;
+    let slot = Arc::new(S::none());
+    let readers = (0..4)
+        .map(|_| {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                let mut last = 0;
+                while last < ITEMS {
+                    if let Some(item) = slot.load() {
+                        assert!(*item >= last, "load went back from {} to {}", last, *item);
+                        last = *item;
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for i in 1..=ITEMS {
+        slot.store(i);
+    }
+    for reader in readers {
+        reader.join().unwrap();
+    }
+}
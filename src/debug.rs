@@ -0,0 +1,136 @@
+//! Debugging helpers for meshes of buses.
+//!
+//! There is no registry of the buses alive, so the topology to export is described
+//! explicitly: buses are added through their publishers, and the tasks moving items
+//! between them (bridges, routers, ...) as links between bus ids.
+//!
+//! ```rust
+//! use bus_queue::debug::{dot_graph, Topology};
+//! use bus_queue::flavors::arc_swap::bounded;
+//!
+//! let (prices, _rx) = bounded::<f64>(10);
+//! let (alerts, _rx) = bounded::<String>(10);
+//!
+//! let mut topology = Topology::new();
+//! topology
+//!     .add_bus("prices", &prices)
+//!     .add_bus("alerts", &alerts)
+//!     .add_link(prices.bus_id(), alerts.bus_id(), "threshold");
+//! assert!(dot_graph(&topology).starts_with("digraph buses {"));
+//! ```
+use crate::publisher::Publisher;
+use crate::swap_slot::SwapSlot;
+use std::fmt::Write;
+
+/// Snapshot of a bus's stats.
+#[derive(Debug, Clone)]
+struct BusNode {
+    name: String,
+    bus_id: u64,
+    size: usize,
+    published: usize,
+    sub_count: usize,
+    closed: bool,
+}
+
+/// Buses and the links between them, exported by `dot_graph`.
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    buses: Vec<BusNode>,
+    links: Vec<(u64, u64, String)>,
+}
+
+impl Topology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a bus, taking a snapshot of its stats.
+    pub fn add_bus<T, S: SwapSlot<T>>(
+        &mut self,
+        name: &str,
+        publisher: &Publisher<T, S>,
+    ) -> &mut Self {
+        self.buses.push(BusNode {
+            name: name.to_string(),
+            bus_id: publisher.bus_id(),
+            size: publisher.len(),
+            published: publisher.buffer.write_index(),
+            sub_count: publisher.sub_count(),
+            closed: !publisher.buffer.is_available(),
+        });
+        self
+    }
+
+    /// Adds a link for items moved from one bus to another, identified by their `bus_id`.
+    pub fn add_link(&mut self, from: u64, to: u64, label: &str) -> &mut Self {
+        self.links.push((from, to, label.to_string()));
+        self
+    }
+}
+
+/// Describes the topology in the Graphviz dot language, labelling every bus with its stats.
+pub fn dot_graph(topology: &Topology) -> String {
+    let mut dot = String::from("digraph buses {\n");
+    for bus in &topology.buses {
+        let _ = writeln!(
+            dot,
+            "    bus_{} [shape=box, label=\"{}\\nsize: {}\\npublished: {}\\nsubscribers: {}{}\"];",
+            bus.bus_id,
+            escape(&bus.name),
+            bus.size,
+            bus.published,
+            bus.sub_count,
+            if bus.closed { "\\nclosed" } else { "" },
+        );
+    }
+    for (from, to, label) in &topology.links {
+        let _ = writeln!(
+            dot,
+            "    bus_{} -> bus_{} [label=\"{}\"];",
+            from,
+            to,
+            escape(label)
+        );
+    }
+    dot.push('}');
+    dot.push('\n');
+    dot
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{dot_graph, Topology};
+    use crate::flavors::arc_swap::bounded;
+
+    #[test]
+    fn exports_buses_and_links() {
+        let (source, _receiver) = bounded::<i32>(4);
+        let (sink, receiver) = bounded::<i32>(2);
+        source.broadcast(1).unwrap();
+        drop(receiver);
+
+        let mut topology = Topology::new();
+        topology
+            .add_bus("source", &source)
+            .add_bus("the \"sink\"", &sink)
+            .add_link(source.bus_id(), sink.bus_id(), "bridge");
+        let dot = dot_graph(&topology);
+
+        assert!(dot.contains(&format!(
+            "bus_{} [shape=box, label=\"source\\nsize: 4\\npublished: 1\\nsubscribers: 1\"];",
+            source.bus_id()
+        )));
+        assert!(dot.contains("the \\\"sink\\\"\\nsize: 2\\npublished: 0\\nsubscribers: 0\""));
+        assert!(dot.contains(&format!(
+            "bus_{} -> bus_{} [label=\"bridge\"];",
+            source.bus_id(),
+            sink.bus_id()
+        )));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::publisher::Publisher;
+use crate::ring_buffer::{ChannelState, SendError, TryRecvError};
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Handle that both broadcasts to and receives from a bus, for actor-style components
+/// publishing their state and observing their peers', created with `duplex_bounded`.
+///
+/// Clones share the publishing side and receive on their own. Broadcasts of the
+/// handles sharing a bus are serialized by a lock, unlike those of a `Publisher`.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::duplex_bounded;
+///
+/// let mut a = duplex_bounded(10);
+/// let mut b = a.clone();
+/// a.set_exclude_own(true);
+/// b.set_exclude_own(true);
+/// a.broadcast("from a").unwrap();
+/// b.broadcast("from b").unwrap();
+///
+/// assert_eq!(*a.try_recv().unwrap(), "from b");
+/// assert_eq!(*b.try_recv().unwrap(), "from a");
+/// ```
+pub struct Duplex<T, S: SwapSlot<T>> {
+    publisher: Arc<Mutex<Publisher<T, S>>>,
+    subscriber: Subscriber<T, S>,
+    /// Sequences of the items broadcast by this handle not received yet, if excluded
+    own: Option<Mutex<VecDeque<usize>>>,
+}
+
+impl<T, S: SwapSlot<T>> Duplex<T, S> {
+    /// Publishes the object like `Publisher::broadcast`.
+    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        let publisher = self.publisher.lock().unwrap();
+        let seq = publisher.buffer.write_index();
+        publisher.broadcast(object)?;
+        if let Some(own) = &self.own {
+            // The middleware might have dropped the object.
+            if publisher.buffer.write_index() != seq {
+                own.lock().unwrap().push_back(seq);
+            }
+        }
+        Ok(())
+    }
+
+    /// Receives the next item like `Subscriber::try_recv`, leaving out the items
+    /// broadcast by this handle if they are excluded.
+    pub fn try_recv(&self) -> Result<S::Shared, TryRecvError> {
+        loop {
+            let item = self.subscriber.try_recv()?;
+            let own = match &self.own {
+                Some(own) => own,
+                None => return Ok(item),
+            };
+            let seq = self.subscriber.ri.get().wrapping_sub(1);
+            let mut own = own.lock().unwrap();
+            // Drops the sequences of the items missed, lapped before being received.
+            while matches!(own.front(), Some(front) if (seq.wrapping_sub(*front) as isize) > 0) {
+                own.pop_front();
+            }
+            if own.front() == Some(&seq) {
+                own.pop_front();
+            } else {
+                return Ok(item);
+            }
+        }
+    }
+
+    /// Makes `try_recv` leave out the items broadcast by this handle from then on.
+    pub fn set_exclude_own(&mut self, exclude: bool) {
+        if exclude != self.own.is_some() {
+            self.own = if exclude {
+                Some(Mutex::new(VecDeque::new()))
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Returns the state of the bus as seen by this handle, see `Subscriber::state`.
+    pub fn state(&self) -> ChannelState {
+        self.subscriber.state()
+    }
+
+    /// Returns the number of Receivers
+    pub fn sub_count(&self) -> usize {
+        self.subscriber.buffer.sub_count()
+    }
+
+    /// Returns the id of the bus, shared by all of its handles, see `Publisher::bus_id`.
+    pub fn bus_id(&self) -> u64 {
+        self.subscriber.bus_id()
+    }
+}
+
+impl<T, S: SwapSlot<T>> From<(Publisher<T, S>, Subscriber<T, S>)> for Duplex<T, S> {
+    fn from(input: (Publisher<T, S>, Subscriber<T, S>)) -> Self {
+        Self {
+            publisher: Arc::new(Mutex::new(input.0)),
+            subscriber: input.1,
+            own: None,
+        }
+    }
+}
+
+/// Clones share the publishing side, receive from the same position and include
+/// their own items until `set_exclude_own` is called on them.
+impl<T, S: SwapSlot<T>> Clone for Duplex<T, S> {
+    fn clone(&self) -> Self {
+        Self {
+            publisher: self.publisher.clone(),
+            subscriber: self.subscriber.clone(),
+            own: None,
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for Duplex<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Duplex")
+            .field("subscriber", &self.subscriber)
+            .field("exclude_own", &self.own.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::duplex_bounded;
+    use crate::ring_buffer::TryRecvError;
+
+    #[test]
+    fn own_items_are_excluded() {
+        let mut a = duplex_bounded(10);
+        let b = a.clone();
+        a.set_exclude_own(true);
+        a.broadcast(1).unwrap();
+        b.broadcast(2).unwrap();
+        a.broadcast(3).unwrap();
+        assert_eq!(*a.try_recv().unwrap(), 2);
+        assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+        // b includes its own items.
+        let received: Vec<_> = (0..3).map(|_| *b.try_recv().unwrap()).collect();
+        assert_eq!(received, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn lapped_own_items_are_forgotten() {
+        let mut a = duplex_bounded(2);
+        let b = a.clone();
+        a.set_exclude_own(true);
+        (0..4).for_each(|x| a.broadcast(x).unwrap());
+        b.broadcast(4).unwrap();
+        a.broadcast(5).unwrap();
+        assert_eq!(*a.try_recv().unwrap(), 4);
+        assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn bus_closes_with_the_last_handle() {
+        let a = duplex_bounded::<i32>(2);
+        let b = a.clone();
+        drop(a);
+        assert_eq!(b.try_recv(), Err(TryRecvError::Empty));
+        let subscriber = b.subscriber.clone();
+        drop(b);
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Disconnected));
+    }
+}
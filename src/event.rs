@@ -0,0 +1,17 @@
+//! Notifications of the threads and tasks waiting on a bus. Without the `blocking`
+//! feature nothing can wait on a bus, and notifying does nothing.
+#[cfg(feature = "blocking")]
+pub(crate) use event_listener::{Event, EventListener};
+
+#[cfg(not(feature = "blocking"))]
+#[derive(Debug, Default)]
+pub(crate) struct Event;
+
+#[cfg(not(feature = "blocking"))]
+impl Event {
+    pub(crate) fn new() -> Self {
+        Event
+    }
+
+    pub(crate) fn notify(&self, _: usize) {}
+}
@@ -0,0 +1,148 @@
+use crate::publisher::Publisher;
+use crate::ring_buffer::SendError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Leadership of a failover pair, handed over under the lock broadcasts are made under.
+struct Leadership<T, S: SwapSlot<T>> {
+    publisher: Publisher<T, S>,
+    /// Role of the handle whose broadcasts are accepted
+    active: usize,
+    /// Number of times the leadership was handed over
+    epoch: u64,
+}
+
+/// One of a pair of handles publishing to the same bus, only the active one's broadcasts
+/// being accepted, for a hot standby feed handler to take over from a failing primary
+/// without the subscribers noticing: they keep receiving from the same bus.
+///
+/// The standby becomes active with `promote`, or when the active handle is dropped.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use bus_queue::FailoverPublisher;
+///
+/// let (publisher, subscriber) = bounded(10);
+/// let (primary, standby) = FailoverPublisher::new(publisher);
+/// primary.broadcast(1).unwrap();
+/// assert!(standby.broadcast(2).is_err());
+///
+/// standby.promote();
+/// assert!(primary.broadcast(3).is_err());
+/// standby.broadcast(4).unwrap();
+///
+/// assert_eq!(subscriber.map(|x| *x).collect::<Vec<_>>(), vec![1, 4]);
+/// ```
+pub struct FailoverPublisher<T, S: SwapSlot<T>> {
+    leadership: Arc<Mutex<Leadership<T, S>>>,
+    role: usize,
+}
+
+impl<T, S: SwapSlot<T>> FailoverPublisher<T, S> {
+    /// Creates the primary and standby handles of the publisher, the primary being active.
+    pub fn new(publisher: Publisher<T, S>) -> (Self, Self) {
+        let leadership = Arc::new(Mutex::new(Leadership {
+            publisher,
+            active: 0,
+            epoch: 0,
+        }));
+        let standby = Self {
+            leadership: leadership.clone(),
+            role: 1,
+        };
+        (
+            Self {
+                leadership,
+                role: 0,
+            },
+            standby,
+        )
+    }
+
+    /// Publishes the object like `Publisher::broadcast` if this handle is active,
+    /// failing otherwise.
+    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        let leadership = self.leadership.lock().unwrap();
+        if leadership.active != self.role {
+            return Err(SendError(object));
+        }
+        leadership.publisher.broadcast(object)
+    }
+
+    /// Makes this handle the active one, returning the epoch of its leadership.
+    /// Broadcasts of the other handle fail from now on.
+    pub fn promote(&self) -> u64 {
+        let mut leadership = self.leadership.lock().unwrap();
+        if leadership.active != self.role {
+            leadership.active = self.role;
+            leadership.epoch += 1;
+        }
+        leadership.epoch
+    }
+
+    /// Checks if this handle's broadcasts are accepted.
+    pub fn is_active(&self) -> bool {
+        self.leadership.lock().unwrap().active == self.role
+    }
+
+    /// Returns the number of times the leadership was handed over.
+    pub fn epoch(&self) -> u64 {
+        self.leadership.lock().unwrap().epoch
+    }
+
+    /// Creates a new Receiver like `Publisher::subscribe`, whichever handle is active.
+    pub fn subscribe(&self) -> Subscriber<T, S> {
+        self.leadership.lock().unwrap().publisher.subscribe()
+    }
+}
+
+/// A failing active handle hands the leadership over to the standby.
+impl<T, S: SwapSlot<T>> Drop for FailoverPublisher<T, S> {
+    fn drop(&mut self) {
+        // A panic while broadcasting still hands over.
+        let mut leadership = match self.leadership.lock() {
+            Ok(leadership) => leadership,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if leadership.active == self.role {
+            leadership.active = 1 - self.role;
+            leadership.epoch += 1;
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for FailoverPublisher<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailoverPublisher")
+            .field("role", &if self.role == 0 { "primary" } else { "standby" })
+            .field("active", &self.is_active())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FailoverPublisher;
+    use crate::flavors::arc_swap::bounded;
+    use std::thread;
+
+    #[test]
+    fn standby_takes_over_from_a_dropped_primary() {
+        let (publisher, subscriber) = bounded(10);
+        let (primary, standby) = FailoverPublisher::new(publisher);
+        thread::spawn(move || {
+            primary.broadcast(1).unwrap();
+            panic!("feed handler failed");
+        })
+        .join()
+        .unwrap_err();
+
+        assert!(standby.is_active());
+        assert_eq!(standby.epoch(), 1);
+        standby.broadcast(2).unwrap();
+        drop(standby);
+        assert_eq!(subscriber.map(|x| *x).collect::<Vec<_>>(), vec![1, 2]);
+    }
+}
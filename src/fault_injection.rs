@@ -0,0 +1,125 @@
+//! Faults injected into a bus at given sequences, for deterministic tests of how
+//! consumers handle dropped items, slow publishers, spurious wakeups and disconnects.
+//!
+//! ```rust
+//! use bus_queue::fault_injection::Faults;
+//! use bus_queue::flavors::arc_swap::Slot;
+//! use bus_queue::Builder;
+//! use std::sync::Arc;
+//!
+//! let faults = Arc::new(Faults::new());
+//! faults.lap_at(1);
+//! let (tx, rx) = Builder::new(10)
+//!     .fault_injection(faults)
+//!     .build::<i32, Slot<i32>>();
+//! (0..3).for_each(|x| tx.broadcast(x).unwrap());
+//!
+//! let received: Vec<i32> = rx.map(|x| *x).collect();
+//! assert_eq!(received, vec![0, 2]);
+//! ```
+//!
+//! Sequences are positions in the bus, the nth item published having sequence n.
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Set of faults a bus injects, enabled through `Builder::fault_injection`.
+///
+/// Faults can be added while the bus is in use.
+#[derive(Debug, Default)]
+pub struct Faults {
+    laps: Mutex<HashSet<usize>>,
+    delays: Mutex<HashMap<usize, Duration>>,
+    wakeups: Mutex<HashSet<usize>>,
+    disconnect: Mutex<Option<usize>>,
+}
+
+impl Faults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes every subscriber miss the item at `seq`, as if it was lapped right before
+    /// receiving it. It counts as a lap.
+    pub fn lap_at(&self, seq: usize) {
+        self.laps.lock().unwrap().insert(seq);
+    }
+
+    /// Makes the publisher stall for `delay` before storing the item at `seq`.
+    pub fn delay_store_at(&self, seq: usize, delay: Duration) {
+        self.delays.lock().unwrap().insert(seq, delay);
+    }
+
+    /// Makes the publisher wake the blocked `recv` calls and the critical and fair lane
+    /// async subscribers right before storing the item at `seq`, with nothing to receive.
+    pub fn spurious_wakeup_at(&self, seq: usize) {
+        self.wakeups.lock().unwrap().insert(seq);
+    }
+
+    /// Makes the bus close instead of publishing the item at `seq`, which is refused.
+    pub fn disconnect_at(&self, seq: usize) {
+        *self.disconnect.lock().unwrap() = Some(seq);
+    }
+
+    /// Checks if the item at `seq` is to be missed by the subscribers.
+    pub(crate) fn is_lapped(&self, seq: usize) -> bool {
+        self.laps.lock().unwrap().contains(&seq)
+    }
+
+    /// Checks if the bus is to close at `seq`.
+    pub(crate) fn is_disconnect(&self, seq: usize) -> bool {
+        *self.disconnect.lock().unwrap() == Some(seq)
+    }
+
+    /// Stalls if the store at `seq` is to be delayed.
+    pub(crate) fn delay_store(&self, seq: usize) {
+        let delay = self.delays.lock().unwrap().get(&seq).copied();
+        if let Some(delay) = delay {
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// Checks if a spurious wakeup is due before the store at `seq`.
+    pub(crate) fn is_spurious_wakeup(&self, seq: usize) -> bool {
+        self.wakeups.lock().unwrap().contains(&seq)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Faults;
+    use crate::flavors::arc_swap::Slot;
+    use crate::ring_buffer::TryRecvError;
+    use crate::Builder;
+    use std::sync::Arc;
+
+    #[test]
+    fn disconnect() {
+        let faults = Arc::new(Faults::new());
+        let (tx, rx) = Builder::new(4)
+            .fault_injection(faults.clone())
+            .build::<i32, Slot<i32>>();
+        faults.disconnect_at(1);
+        tx.broadcast(0).unwrap();
+        assert_eq!(tx.broadcast(1).unwrap_err().0, 1);
+        assert_eq!(*rx.try_recv().unwrap(), 0);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn lap() {
+        let faults = Arc::new(Faults::new());
+        faults.lap_at(0);
+        faults.lap_at(2);
+        let (tx, rx) = Builder::new(4)
+            .fault_injection(faults)
+            .build::<i32, Slot<i32>>();
+        let rx2 = rx.clone();
+        (0..4).for_each(|x| tx.broadcast(x).unwrap());
+        assert_eq!(*rx.try_recv().unwrap(), 1);
+        assert_eq!(*rx.try_recv().unwrap(), 3);
+        assert_eq!(rx.lap_count(), 2);
+        let drained = rx2.drain().into_iter().map(|x| *x).collect::<Vec<_>>();
+        assert_eq!(drained, vec![1, 3]);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::atomic_counter::AtomicCounter;
+use crate::event::Event;
+#[cfg(feature = "blocking")]
+use crate::event::EventListener;
+use std::sync::atomic::{self, Ordering};
+
+/// Point in the sequence of a bus's items, handed out by `Publisher::fence_token`:
+/// a subscriber is past it once it received every item published before it.
+///
+/// Tokens can be passed to other components to wait until a subscriber has seen
+/// everything that happened before, see `Subscriber::wait_for_token`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FenceToken {
+    pub(crate) bus_id: u64,
+    /// Sequence of the first item published after the token
+    pub(crate) sequence: usize,
+}
+
+/// Threads waiting for a subscriber to get past a token.
+#[derive(Debug)]
+pub(crate) struct Fences {
+    waiting: AtomicCounter,
+    event: Event,
+}
+
+impl Fences {
+    pub(crate) fn new() -> Self {
+        Self {
+            waiting: AtomicCounter::new(0),
+            event: Event::new(),
+        }
+    }
+
+    /// Registers a thread about to wait, has to be paired with a call to `unregister`.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn register(&self) -> EventListener {
+        self.waiting.inc();
+        let listener = self.event.listen();
+        atomic::fence(Ordering::SeqCst);
+        listener
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn unregister(&self) {
+        self.waiting.dec();
+    }
+
+    /// Wakes the waiting threads, if any, after the subscriber was moved, e.g. by receiving.
+    pub(crate) fn notify(&self) {
+        // Pairs with the fence in register, either the waiter sees the subscriber past
+        // its token or the subscriber sees the waiter.
+        atomic::fence(Ordering::SeqCst);
+        if self.waiting.get() > 0 {
+            self.event.notify(usize::MAX);
+        }
+    }
+}
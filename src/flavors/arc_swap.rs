@@ -1,5 +1,7 @@
 #![allow(dead_code)]
-use crate::{async_publisher, async_subscriber, publisher, subscriber, SwapSlot};
+#[cfg(feature = "async")]
+use crate::{async_publisher, async_subscriber};
+use crate::{duplex, publisher, sharded, subscriber, topics, SwapSlot};
 use arc_swap::ArcSwapOption;
 use std::sync::Arc;
 
@@ -8,6 +10,10 @@ pub struct Slot<T> {
 }
 
 impl<T> SwapSlot<T> for Slot<T> {
+    type Shared = Arc<T>;
+    const IS_LOCK_FREE: bool = true;
+    const LOAD_ALLOCATES: bool = false;
+
     fn store(&self, item: T) {
         self.shared.store(Some(Arc::new(item)))
     }
@@ -16,6 +22,14 @@ impl<T> SwapSlot<T> for Slot<T> {
         self.shared.load_full()
     }
 
+    fn load_with<R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        self.shared.load().as_deref().map(f)
+    }
+
+    fn clear(&self) {
+        self.shared.store(None)
+    }
+
     fn none() -> Self {
         Slot {
             shared: ArcSwapOption::new(None),
@@ -30,9 +44,37 @@ pub fn bounded<T>(size: usize) -> (Publisher<T>, Subscriber<T>) {
     crate::bounded::<T, Slot<T>>(size)
 }
 
+pub fn bounded_bytes<T, F>(
+    size: usize,
+    max_bytes: usize,
+    size_of: F,
+) -> (Publisher<T>, Subscriber<T>)
+where
+    F: Fn(&T) -> usize + Send + Sync + 'static,
+{
+    crate::bounded_bytes::<T, Slot<T>, F>(size, max_bytes, size_of)
+}
+
+pub fn bounded_tiered<T>(hot: usize, cold: usize) -> (Publisher<T>, Subscriber<T>) {
+    crate::bounded_tiered::<T, Slot<T>>(hot, cold)
+}
+
+pub type Duplex<T> = duplex::Duplex<T, Slot<T>>;
+
+pub fn duplex_bounded<T>(size: usize) -> Duplex<T> {
+    crate::duplex_bounded::<T, Slot<T>>(size)
+}
+
+pub type ShardedBus<T> = sharded::ShardedBus<T, Slot<T>>;
+
+pub type TopicBus<K, T> = topics::TopicBus<K, T, Slot<T>>;
+
+#[cfg(feature = "async")]
 pub type AsyncPublisher<T> = async_publisher::AsyncPublisher<T, Slot<T>>;
+#[cfg(feature = "async")]
 pub type AsyncSubscriber<T> = async_subscriber::AsyncSubscriber<T, Slot<T>>;
 
+#[cfg(feature = "async")]
 pub fn async_bounded<T>(size: usize) -> (AsyncPublisher<T>, AsyncSubscriber<T>) {
     crate::async_bounded::<T, Slot<T>>(size)
 }
@@ -69,4 +111,14 @@ mod test {
         assert_eq!(arc, Some(Arc::new(10)));
         assert_eq!(Arc::strong_count(&arc.unwrap()), 2)
     }
+
+    #[test]
+    fn test_archswap_clear() {
+        let slot = Slot::none();
+        slot.store(15);
+
+        slot.clear();
+
+        assert_eq!(slot.shared.load_full(), None);
+    }
 }
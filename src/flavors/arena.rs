@@ -0,0 +1,141 @@
+//! Flavor recycling the payload allocations of the slots: once every subscriber dropped
+//! the item a slot held, the next item stored in the slot is written in place instead
+//! of being allocated, so that a publisher keeping ahead of its subscribers does not
+//! allocate in steady state.
+//!
+//! Subscribers receive the usual `Arc`, keeping the item alive for as long as they hold
+//! it: an item still held when its slot comes around again is left to its holders and
+//! the next one allocated. Best suited to medium-size plain data payloads, for which
+//! the allocation dominates the cost of a broadcast.
+#![allow(dead_code)]
+#[cfg(feature = "async")]
+use crate::{async_publisher, async_subscriber};
+use crate::{duplex, publisher, sharded, subscriber, topics, SwapSlot};
+use arc_swap::ArcSwapOption;
+use std::sync::Arc;
+
+pub struct Slot<T> {
+    shared: ArcSwapOption<T>,
+    /// Allocation of the item the slot held before, reused once no one else holds it
+    spare: ArcSwapOption<T>,
+}
+
+impl<T> SwapSlot<T> for Slot<T> {
+    type Shared = Arc<T>;
+    const IS_LOCK_FREE: bool = true;
+    const LOAD_ALLOCATES: bool = false;
+
+    fn store(&self, item: T) {
+        let item = match self.spare.swap(None) {
+            Some(mut spare) => match Arc::get_mut(&mut spare) {
+                Some(payload) => {
+                    *payload = item;
+                    spare
+                }
+                None => Arc::new(item),
+            },
+            None => Arc::new(item),
+        };
+        // Swapping pays the debts of the readers, the count of the previous item is exact.
+        let previous = self.shared.swap(Some(item));
+        self.spare.store(previous);
+    }
+
+    fn load(&self) -> Option<Arc<T>> {
+        self.shared.load_full()
+    }
+
+    fn load_with<R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        self.shared.load().as_deref().map(f)
+    }
+
+    fn clear(&self) {
+        // Evicted items are released rather than kept for reuse.
+        self.shared.store(None);
+        self.spare.store(None);
+    }
+
+    fn none() -> Self {
+        Slot {
+            shared: ArcSwapOption::new(None),
+            spare: ArcSwapOption::new(None),
+        }
+    }
+}
+
+pub type Publisher<T> = publisher::Publisher<T, Slot<T>>;
+pub type Subscriber<T> = subscriber::Subscriber<T, Slot<T>>;
+
+pub fn bounded<T>(size: usize) -> (Publisher<T>, Subscriber<T>) {
+    crate::bounded::<T, Slot<T>>(size)
+}
+
+pub fn bounded_bytes<T, F>(
+    size: usize,
+    max_bytes: usize,
+    size_of: F,
+) -> (Publisher<T>, Subscriber<T>)
+where
+    F: Fn(&T) -> usize + Send + Sync + 'static,
+{
+    crate::bounded_bytes::<T, Slot<T>, F>(size, max_bytes, size_of)
+}
+
+pub fn bounded_tiered<T>(hot: usize, cold: usize) -> (Publisher<T>, Subscriber<T>) {
+    crate::bounded_tiered::<T, Slot<T>>(hot, cold)
+}
+
+pub type Duplex<T> = duplex::Duplex<T, Slot<T>>;
+
+pub fn duplex_bounded<T>(size: usize) -> Duplex<T> {
+    crate::duplex_bounded::<T, Slot<T>>(size)
+}
+
+pub type ShardedBus<T> = sharded::ShardedBus<T, Slot<T>>;
+
+pub type TopicBus<K, T> = topics::TopicBus<K, T, Slot<T>>;
+
+#[cfg(feature = "async")]
+pub type AsyncPublisher<T> = async_publisher::AsyncPublisher<T, Slot<T>>;
+#[cfg(feature = "async")]
+pub type AsyncSubscriber<T> = async_subscriber::AsyncSubscriber<T, Slot<T>>;
+
+#[cfg(feature = "async")]
+pub fn async_bounded<T>(size: usize) -> (AsyncPublisher<T>, AsyncSubscriber<T>) {
+    crate::async_bounded::<T, Slot<T>>(size)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arena::Slot;
+    use crate::swap_slot::SwapSlot;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_arena_reuses_released_items() {
+        let slot = Slot::none();
+        slot.store(1);
+        let first = Arc::as_ptr(&slot.load().unwrap());
+        slot.store(2);
+        // The first item is no longer held, its allocation is reused.
+        slot.store(3);
+        let third = slot.load().unwrap();
+        assert_eq!((*third, Arc::as_ptr(&third)), (3, first));
+
+        // An item still held is left alone.
+        slot.store(4);
+        slot.store(5);
+        assert_eq!(*third, 3);
+        assert_ne!(Arc::as_ptr(&slot.load().unwrap()), Arc::as_ptr(&third));
+    }
+
+    #[test]
+    fn test_arena_clear() {
+        let slot = Slot::none();
+        slot.store(15);
+
+        slot.clear();
+
+        assert_eq!(slot.load(), None);
+    }
+}
@@ -6,3 +6,9 @@ pub mod rw_lock;
 
 #[cfg(feature = "atomic-arc")]
 pub mod atomic_arc;
+
+#[cfg(feature = "arcswap")]
+pub mod arena;
+
+#[cfg(feature = "small-copy")]
+pub mod small_copy;
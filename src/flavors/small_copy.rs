@@ -0,0 +1,193 @@
+//! Flavor for items fitting in 64 bits, e.g. `u64` ticks or `f64` prices: the items are
+//! packed in atomics in place of the slots' `Arc`s and subscribers receive them by copy,
+//! so that neither broadcasting nor receiving allocates or touches a ref count.
+//!
+//! Items implement `Packed`, which is implemented for the primitive numbers and `bool`,
+//! and can be implemented for small `Copy` types, e.g. newtypes around them.
+#![allow(dead_code)]
+#[cfg(feature = "async")]
+use crate::{async_publisher, async_subscriber};
+use crate::{duplex, publisher, sharded, subscriber, topics, SwapSlot};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Item that can be packed into 64 bits and back.
+pub trait Packed: Copy {
+    fn pack(self) -> u64;
+
+    /// Unpacks bits returned by `pack`.
+    fn unpack(bits: u64) -> Self;
+}
+
+macro_rules! impl_packed {
+    ($($int:ty),*) => {$(
+        impl Packed for $int {
+            fn pack(self) -> u64 {
+                self as u64
+            }
+
+            fn unpack(bits: u64) -> Self {
+                bits as $int
+            }
+        }
+    )*};
+}
+
+impl_packed!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl Packed for bool {
+    fn pack(self) -> u64 {
+        self as u64
+    }
+
+    fn unpack(bits: u64) -> Self {
+        bits != 0
+    }
+}
+
+impl Packed for f32 {
+    fn pack(self) -> u64 {
+        self.to_bits() as u64
+    }
+
+    fn unpack(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl Packed for f64 {
+    fn pack(self) -> u64 {
+        self.to_bits()
+    }
+
+    fn unpack(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
+
+/// Copy of an item handed out to subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Value<T>(pub T);
+
+impl<T> Deref for Value<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+pub struct Slot<T> {
+    bits: AtomicU64,
+    /// Whether the slot holds an item, set once its bits are stored
+    full: AtomicBool,
+    item: PhantomData<fn(T) -> T>,
+}
+
+impl<T: Packed> SwapSlot<T> for Slot<T> {
+    type Shared = Value<T>;
+    const IS_LOCK_FREE: bool = true;
+    const LOAD_ALLOCATES: bool = false;
+
+    fn store(&self, item: T) {
+        self.bits.store(item.pack(), Ordering::Release);
+        self.full.store(true, Ordering::Release);
+    }
+
+    fn load(&self) -> Option<Value<T>> {
+        if self.full.load(Ordering::Acquire) {
+            Some(Value(T::unpack(self.bits.load(Ordering::Acquire))))
+        } else {
+            None
+        }
+    }
+
+    fn clear(&self) {
+        self.full.store(false, Ordering::Release);
+    }
+
+    fn none() -> Self {
+        Slot {
+            bits: AtomicU64::new(0),
+            full: AtomicBool::new(false),
+            item: PhantomData,
+        }
+    }
+}
+
+pub type Publisher<T> = publisher::Publisher<T, Slot<T>>;
+pub type Subscriber<T> = subscriber::Subscriber<T, Slot<T>>;
+
+pub fn bounded<T: Packed>(size: usize) -> (Publisher<T>, Subscriber<T>) {
+    crate::bounded::<T, Slot<T>>(size)
+}
+
+pub fn bounded_bytes<T: Packed, F>(
+    size: usize,
+    max_bytes: usize,
+    size_of: F,
+) -> (Publisher<T>, Subscriber<T>)
+where
+    F: Fn(&T) -> usize + Send + Sync + 'static,
+{
+    crate::bounded_bytes::<T, Slot<T>, F>(size, max_bytes, size_of)
+}
+
+pub fn bounded_tiered<T: Packed>(hot: usize, cold: usize) -> (Publisher<T>, Subscriber<T>) {
+    crate::bounded_tiered::<T, Slot<T>>(hot, cold)
+}
+
+pub type Duplex<T> = duplex::Duplex<T, Slot<T>>;
+
+pub fn duplex_bounded<T: Packed>(size: usize) -> Duplex<T> {
+    crate::duplex_bounded::<T, Slot<T>>(size)
+}
+
+pub type ShardedBus<T> = sharded::ShardedBus<T, Slot<T>>;
+
+pub type TopicBus<K, T> = topics::TopicBus<K, T, Slot<T>>;
+
+#[cfg(feature = "async")]
+pub type AsyncPublisher<T> = async_publisher::AsyncPublisher<T, Slot<T>>;
+#[cfg(feature = "async")]
+pub type AsyncSubscriber<T> = async_subscriber::AsyncSubscriber<T, Slot<T>>;
+
+#[cfg(feature = "async")]
+pub fn async_bounded<T: Packed>(size: usize) -> (AsyncPublisher<T>, AsyncSubscriber<T>) {
+    crate::async_bounded::<T, Slot<T>>(size)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::small_copy::{bounded, Packed, Slot, Value};
+    use crate::swap_slot::SwapSlot;
+
+    #[test]
+    fn test_small_copy_round_trip() {
+        for item in [i64::MIN, -1, 0, i64::MAX] {
+            assert_eq!(i64::unpack(item.pack()), item);
+        }
+        assert_eq!(f32::unpack((-1.5f32).pack()), -1.5);
+        assert!(bool::unpack(true.pack()));
+    }
+
+    #[test]
+    fn test_small_copy_clear() {
+        let slot = Slot::none();
+        slot.store(15u32);
+        assert_eq!(slot.load(), Some(Value(15)));
+
+        slot.clear();
+
+        assert_eq!(slot.load(), None);
+    }
+
+    #[test]
+    fn test_small_copy_bounded() {
+        let (tx, rx) = bounded(4);
+        (0..6).for_each(|i| tx.broadcast(i as f64 / 2.0).unwrap());
+        let received: Vec<f64> = rx.map(|item| *item).collect();
+        assert_eq!(received, vec![1.0, 1.5, 2.0, 2.5]);
+    }
+}
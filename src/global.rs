@@ -0,0 +1,236 @@
+//! Process-wide registry of named buses, so that loosely coupled parts of a program
+//! can publish and subscribe by name instead of passing the handles around.
+//!
+//! ```rust
+//! use bus_queue::global;
+//!
+//! // Somewhere in a consumer...
+//! let rx = global::bus::<u64>("ticks", 10).unwrap().subscribe();
+//!
+//! // ...and somewhere in a producer.
+//! global::bus::<u64>("ticks", 10).unwrap().broadcast(7).unwrap();
+//! assert_eq!(*rx.try_recv().unwrap(), 7);
+//!
+//! // The name is taken by a bus of another type.
+//! assert!(global::bus::<String>("ticks", 10).is_err());
+//! ```
+use crate::flavors::arc_swap::{Publisher, Subscriber};
+use crate::ring_buffer::SendError;
+use std::any::{self, Any};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+struct Entry {
+    /// The GlobalBus<T> registered
+    bus: Box<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+static REGISTRY: Mutex<Option<HashMap<String, Entry>>> = Mutex::new(None);
+
+fn registry() -> MutexGuard<'static, Option<HashMap<String, Entry>>> {
+    match REGISTRY.lock() {
+        Ok(registry) => registry,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Returns the bus registered under the name, registering a new one retaining up to
+/// `size` items if there is none. The size of an already registered bus is kept.
+///
+/// Fails if the name is taken by a bus of another item type.
+pub fn bus<T: Send + Sync + 'static>(
+    name: &str,
+    size: usize,
+) -> Result<GlobalBus<T>, TypeMismatchError> {
+    let mut registry = registry();
+    let entry = registry
+        .get_or_insert_with(HashMap::new)
+        .entry(name.to_owned())
+        .or_insert_with(|| Entry {
+            bus: Box::new(GlobalBus::<T>::new(name, size)),
+            type_name: any::type_name::<T>(),
+        });
+    match entry.bus.downcast_ref::<GlobalBus<T>>() {
+        Some(bus) => Ok(bus.clone()),
+        None => Err(TypeMismatchError {
+            name: name.to_owned(),
+            requested: any::type_name::<T>(),
+            registered: entry.type_name,
+        }),
+    }
+}
+
+/// Returns the bus registered under the name, if there is one with items of type T.
+pub fn lookup<T: Send + Sync + 'static>(name: &str) -> Option<GlobalBus<T>> {
+    registry()
+        .as_ref()?
+        .get(name)?
+        .bus
+        .downcast_ref::<GlobalBus<T>>()
+        .cloned()
+}
+
+/// Unregisters the bus, returning true if there was one under the name.
+///
+/// The bus lives on as long as handles to it do, and is closed once they are dropped.
+/// The next `bus` call with the name registers a new bus.
+pub fn remove(name: &str) -> bool {
+    registry()
+        .as_mut()
+        .and_then(|buses| buses.remove(name))
+        .is_some()
+}
+
+/// Returns the names of the registered buses, in no particular order.
+pub fn names() -> Vec<String> {
+    registry()
+        .as_ref()
+        .map_or_else(Vec::new, |buses| buses.keys().cloned().collect())
+}
+
+/// Handle to a bus of the registry, returned by `bus`. Handles are cheap to clone.
+///
+/// Broadcasting from several handles at once is serialized.
+pub struct GlobalBus<T> {
+    name: Arc<str>,
+    publisher: Arc<Mutex<Publisher<T>>>,
+}
+
+impl<T: Send + Sync + 'static> GlobalBus<T> {
+    fn new(name: &str, size: usize) -> Self {
+        Self {
+            name: name.into(),
+            publisher: Arc::new(Mutex::new(crate::flavors::arc_swap::bounded(size).0)),
+        }
+    }
+
+    /// Returns the name the bus is registered under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Publishes the object, failing if the bus has no subscribers.
+    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        self.publisher.lock().unwrap().broadcast(object)
+    }
+
+    /// Creates a receiver of the items published from now on.
+    pub fn subscribe(&self) -> Subscriber<T> {
+        self.publisher.lock().unwrap().subscribe()
+    }
+
+    /// Closes the bus and unregisters it, if it is still the one registered
+    /// under its name.
+    pub fn close(&self) {
+        self.publisher.lock().unwrap().close();
+        if let Some(buses) = registry().as_mut() {
+            let registered = buses
+                .get(&*self.name)
+                .and_then(|entry| entry.bus.downcast_ref::<GlobalBus<T>>())
+                .filter(|bus| Arc::ptr_eq(&bus.publisher, &self.publisher))
+                .is_some();
+            if registered {
+                buses.remove(&*self.name);
+            }
+        }
+    }
+}
+
+impl<T> Clone for GlobalBus<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            publisher: self.publisher.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for GlobalBus<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobalBus")
+            .field("name", &self.name)
+            .field("publisher", &self.publisher)
+            .finish()
+    }
+}
+
+/// Error returned by `bus` when the name is taken by a bus of another item type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatchError {
+    pub name: String,
+    /// Item type asked for
+    pub requested: &'static str,
+    /// Item type of the registered bus
+    pub registered: &'static str,
+}
+
+impl fmt::Display for TypeMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bus {:?} holds {}, not {}",
+            self.name, self.registered, self.requested
+        )
+    }
+}
+
+impl std::error::Error for TypeMismatchError {}
+
+#[cfg(test)]
+mod test {
+    use super::{bus, lookup, names, remove};
+    use std::sync::mpsc::TryRecvError;
+
+    #[test]
+    fn concurrent_broadcasts() {
+        let ticks = bus::<usize>("test-concurrent", 8).unwrap();
+        let rx = ticks.subscribe();
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let ticks = ticks.clone();
+                std::thread::spawn(move || {
+                    for i in 0..20_000 {
+                        ticks.broadcast(i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        threads
+            .into_iter()
+            .for_each(|thread| thread.join().unwrap());
+        assert_eq!(rx.count(), 8);
+        assert!(remove("test-concurrent"));
+    }
+
+    #[test]
+    fn lifecycle() {
+        let orders = bus::<u32>("test-lifecycle", 4).unwrap();
+        let rx = orders.subscribe();
+        assert!(names().contains(&"test-lifecycle".to_owned()));
+        let error = bus::<i64>("test-lifecycle", 4).unwrap_err();
+        assert_eq!(error.registered, "u32");
+        assert!(lookup::<i64>("test-lifecycle").is_none());
+
+        lookup::<u32>("test-lifecycle")
+            .unwrap()
+            .broadcast(1)
+            .unwrap();
+        assert_eq!(*rx.try_recv().unwrap(), 1);
+
+        // Removed, the bus lives on for the handles.
+        assert!(remove("test-lifecycle"));
+        assert!(!remove("test-lifecycle"));
+        orders.broadcast(2).unwrap();
+        assert_eq!(*rx.try_recv().unwrap(), 2);
+
+        // A new bus is registered under the name, closing the old one leaves it be.
+        let replacement = bus::<i64>("test-lifecycle", 4).unwrap();
+        orders.close();
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert!(lookup::<i64>("test-lifecycle").is_some());
+        replacement.close();
+        assert!(lookup::<i64>("test-lifecycle").is_none());
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Bits of the value kept below its most significant bit, 16 buckets per power of two.
+const SUB_BUCKET_BITS: u32 = 4;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Buckets needed for any u64 value.
+const BUCKETS: usize = (65 - SUB_BUCKET_BITS as usize) * SUB_BUCKETS;
+
+/// Returns the bucket of a value, values below 32 having one each and larger ones
+/// sharing buckets spanning about 6% of them.
+fn bucket_of(value: u64) -> usize {
+    if value < 2 * SUB_BUCKETS as u64 {
+        return value as usize;
+    }
+    let shift = 63 - value.leading_zeros() - SUB_BUCKET_BITS;
+    let mantissa = (value >> shift) as usize;
+    (shift as usize + 1) * SUB_BUCKETS + mantissa - SUB_BUCKETS
+}
+
+/// Returns the highest value falling in the bucket.
+fn highest_of(bucket: usize) -> u64 {
+    if bucket < 2 * SUB_BUCKETS {
+        return bucket as u64;
+    }
+    let shift = (bucket / SUB_BUCKETS - 1) as u32;
+    let mantissa = (SUB_BUCKETS + bucket % SUB_BUCKETS) as u64;
+    // Overflows to 0 for the last bucket, whose highest value is u64::MAX.
+    ((mantissa + 1) << shift).wrapping_sub(1)
+}
+
+/// Records the publish to receive latencies of a subscriber.
+#[derive(Debug)]
+pub(crate) struct LatencyRecorder {
+    counts: Vec<AtomicU64>,
+    max: AtomicU64,
+}
+
+impl LatencyRecorder {
+    pub fn new() -> Self {
+        Self {
+            counts: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, nanos: u64) {
+        self.counts[bucket_of(nanos)].fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> LatencyHistogram {
+        LatencyHistogram {
+            counts: self
+                .counts
+                .iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+            max: self.max.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Histogram of the time between the publishing and the receiving of items,
+/// returned by `Subscriber::latency_histogram`.
+///
+/// Latencies are bucketed HDR-style, with a precision of about 6%.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    max: u64,
+}
+
+impl LatencyHistogram {
+    /// Returns the number of latencies recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the highest latency recorded.
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    /// Returns the latency `percentile` percent of the recorded ones are at or below,
+    /// zero if none were recorded.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        let count = self.count();
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * count as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, bucket_count) in self.counts.iter().enumerate() {
+            seen += bucket_count;
+            if seen >= rank.max(1) && *bucket_count > 0 {
+                return Duration::from_nanos(highest_of(bucket).min(self.max));
+            }
+        }
+        Duration::from_nanos(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bucket_of, highest_of, LatencyRecorder, BUCKETS};
+    use std::time::Duration;
+
+    #[test]
+    fn buckets() {
+        for value in [0, 31, 32, 33, 1000, 123_456_789, u64::MAX].iter().copied() {
+            let bucket = bucket_of(value);
+            assert!(bucket < BUCKETS);
+            assert!(highest_of(bucket) >= value);
+            assert!(bucket == 0 || highest_of(bucket - 1) < value);
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let recorder = LatencyRecorder::new();
+        assert_eq!(
+            recorder.snapshot().percentile(50.0),
+            Duration::from_nanos(0)
+        );
+        for nanos in 1..=100 {
+            recorder.record(nanos * 1000);
+        }
+        let histogram = recorder.snapshot();
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.max(), Duration::from_micros(100));
+        assert_eq!(histogram.percentile(100.0), Duration::from_micros(100));
+        let median = histogram.percentile(50.0).as_nanos() as f64;
+        assert!((median - 50_000.0).abs() / 50_000.0 < 0.07);
+    }
+}
@@ -0,0 +1,90 @@
+//! Spawning of the bus's tasks on tokio, named so that they show up in tokio-console.
+//!
+//! Tasks spawned through `spawn_named`, e.g. the driver returned by `StreamExt2::into_bus`
+//! or the loops consuming a subscriber, are listed in tokio-console under their name,
+//! with the poll and wakeup counts tokio records for them. Names are only recorded when
+//! built with `RUSTFLAGS="--cfg tokio_unstable"`, as tokio-console itself requires.
+//!
+//! ```rust,no_run
+//! use bus_queue::flavors::arc_swap::Slot;
+//! use bus_queue::instrument::spawn_named;
+//! use bus_queue::StreamExt2;
+//! use futures::stream;
+//!
+//! # async fn run() {
+//! let (driver, subscriber) = stream::iter(0..10).into_bus::<Slot<_>>(4);
+//! spawn_named("bus-forwarder", driver);
+//! # drop(subscriber);
+//! # }
+//! ```
+use std::future::Future;
+use tokio::task::JoinHandle;
+
+/// Spawns the future on the current tokio runtime as a task named `name`.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, like `tokio::spawn`.
+pub fn spawn_named<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(tokio_unstable)]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn(future)
+            .expect("failed to spawn task")
+    }
+    #[cfg(not(tokio_unstable))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// Spawns the future like `spawn_named` on the given runtime.
+pub fn spawn_named_on<F>(
+    name: &str,
+    future: F,
+    handle: &tokio::runtime::Handle,
+) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(tokio_unstable)]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn_on(future, handle)
+            .expect("failed to spawn task")
+    }
+    #[cfg(not(tokio_unstable))]
+    {
+        let _ = name;
+        handle.spawn(future)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::spawn_named_on;
+    use crate::flavors::arc_swap::Slot;
+    use crate::StreamExt2;
+    use futures::executor::block_on;
+    use futures::{stream, StreamExt};
+
+    #[test]
+    fn named_driver_forwards_the_stream() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let (driver, subscriber) = stream::iter(0..3).into_bus::<Slot<_>>(4);
+        let forwarder = spawn_named_on("bus-forwarder", driver, runtime.handle());
+        runtime.block_on(forwarder).unwrap().unwrap();
+        let received: Vec<i32> = block_on(subscriber.map(|x| *x).collect());
+        assert_eq!(received, vec![0, 1, 2]);
+    }
+}
@@ -16,11 +16,16 @@
 //!    their speed.
 //!  * Pub-Sub - Every Subscriber that can keep up with the Publisher will recieve all the data the
 //!    Publisher publishes.
-//!  * **channel** - a raw Pub/Sub channel implementation without the thread synchronisation and futures logic.
-//!  * **bus** - an async Pub/Sub queue with **futures::sink::Sink** and **futures::stream::Stream** traits.
+//!  * **bounded** - a raw Pub/Sub channel without the thread synchronisation and futures logic.
+//!  * **async_bounded** - an async Pub/Sub queue with **futures::sink::Sink** and **futures::stream::Stream** traits.
 //!
-//! **bus::Publisher**, and **channel::Sender** are used to broadcast data to **bus::Subscriber**, and
-//! **channel::Receiver** pools. Subscribers are clone-able such that many threads, or futures, can receive
+//! The async half is behind the default `async` feature, the blocking receives behind the
+//! default `blocking` feature. Building with `default-features = false, features = ["core-only"]`
+//! leaves the sync bus with the arc_swap flavor, without the futures and event-listener
+//! dependencies.
+//!
+//! **Publisher**, and **AsyncPublisher** are used to broadcast data to **Subscriber**, and
+//! **AsyncSubscriber** pools. Subscribers are clone-able such that many threads, or futures, can receive
 //! data simultaneously. The only limitation is that Subscribers have to keep up with the frequency of the
 //! Publisher. If a Subscriber is slow it will drop data.
 //!
@@ -82,28 +87,148 @@
 //! assert_eq!(received2, expected);
 //! ```
 
+mod acking_subscriber;
+mod advisor;
+#[cfg(feature = "async")]
 mod async_publisher;
+#[cfg(feature = "async")]
 mod async_subscriber;
 mod atomic_counter;
+mod backchannel;
+mod backoff;
+mod barrier_subscriber;
+mod builder;
+mod burst_writer;
+#[cfg(feature = "async")]
+mod bus_handle;
+mod cache_padded;
+mod coalescing_buffer;
+mod codec;
+pub mod compat;
+pub mod conformance;
+pub mod debug;
+mod duplex;
+mod event;
+mod failover;
+mod fence;
 pub mod flavors;
+#[cfg(feature = "arcswap")]
+pub mod global;
+mod histogram;
+#[cfg(feature = "async")]
+pub mod merge;
+mod metadata;
+#[cfg(feature = "async")]
+mod offload;
+#[cfg(feature = "ordering-checks")]
+mod ordering;
+mod overflow;
+#[cfg(feature = "blocking")]
+pub mod parker;
+mod pending_slices;
+mod pins;
+mod position;
+mod project;
 mod publisher;
+mod quota;
+mod reaper;
 mod ring_buffer;
+mod sequence;
+mod sharded;
+#[cfg(feature = "async")]
+mod stream_ext;
 mod subscriber;
 mod swap_slot;
+mod tiered;
+#[cfg(feature = "async")]
+mod timer;
+mod topics;
+pub mod unsync;
+mod weak_subscriber;
 
-pub use crate::async_publisher::AsyncPublisher;
-pub use crate::async_subscriber::AsyncSubscriber;
-pub use crate::publisher::Publisher;
-pub use crate::subscriber::Subscriber;
-pub use ring_buffer::RingBuffer;
+pub use crate::acking_subscriber::AckingSubscriber;
+#[cfg(feature = "async")]
+pub use crate::async_publisher::{AsyncPublisher, NotifyPolicy, PublisherGuard};
+#[cfg(feature = "async")]
+pub use crate::async_subscriber::{
+    AsyncSubscriber, BlockingIter, Cloned, Closed, DrainRemaining, SkipPolicy, WithPolicy,
+    WithStatus,
+};
+pub use crate::backchannel::{Command, ControlledSubscriber};
+pub use crate::backoff::Backoff;
+pub use crate::barrier_subscriber::{BarrierSubscriber, Matcher};
+pub use crate::builder::Builder;
+pub use crate::burst_writer::{BurstWriter, Claim};
+#[cfg(feature = "async")]
+pub use crate::bus_handle::{BusHandle, ManagedTask};
+pub use crate::cache_padded::CachePadded;
+pub use crate::coalescing_buffer::CoalescingBuffer;
+pub use crate::codec::Codec;
+pub use crate::duplex::Duplex;
+pub use crate::failover::FailoverPublisher;
+pub use crate::fence::FenceToken;
+pub use crate::histogram::LatencyHistogram;
+#[cfg(feature = "async")]
+pub use crate::offload::MapOffload;
+pub use crate::overflow::{ByPriority, Overflow, OverflowPolicy, RejectNewest};
+pub use crate::pending_slices::PendingSlices;
+pub use crate::position::{Position, RestoreError};
+#[cfg(feature = "async")]
+pub use crate::project::AsyncProjection;
+pub use crate::project::{Projected, Projection};
+#[cfg(feature = "blocking")]
+pub use crate::publisher::StartBarrierError;
+pub use crate::publisher::{Fallible, Middleware, PausePolicy, Publisher, TryBroadcastError};
+pub use crate::subscriber::{
+    RecvStatus, Remaining, SkipItemsError, SkipMode, Subscriber, TryRecvLaggedError,
+};
+pub use ring_buffer::{ChannelState, RingBuffer, ZeroCapacityError};
+pub use sequence::{Counter, SequenceSource};
+pub use sharded::{ShardedBus, ShardedSubscriber};
+#[cfg(feature = "async")]
+pub use stream_ext::{AsyncPublisherDriver, StreamExt2};
 pub use swap_slot::SwapSlot;
+pub use topics::{TopicBus, TopicSubscriber};
+pub use weak_subscriber::WeakSubscriber;
 
 #[cfg(feature = "atomic-arc")]
 mod atomic;
 
+#[cfg(feature = "persistent")]
+mod persistent;
+#[cfg(feature = "persistent")]
+pub use persistent::{
+    PayloadTooLargeError, PersistentPublisher, PersistentRecvError, PersistentSubscriber,
+};
+#[cfg(feature = "spill")]
+mod spilling_subscriber;
+#[cfg(feature = "spill")]
+pub use spilling_subscriber::{SpillRecvError, SpillingSubscriber};
+
+#[cfg(feature = "sse")]
+mod sse;
+#[cfg(feature = "sse")]
+pub use sse::SseStream;
+
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection;
+
+#[cfg(feature = "tokio")]
+pub mod instrument;
+
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+
+#[cfg(feature = "test-controls")]
+mod test_controls;
+#[cfg(feature = "test-controls")]
+pub use test_controls::TestBusControls;
+
 pub use atomic_counter::AtomicCounter;
 
 /// Function used to create and initialise a (Sender, Receiver) tuple.
+///
+/// A size of 0 holds the latest item only, see `RingBuffer::new`.
 pub fn bounded<T, S: SwapSlot<T>>(
     size: usize,
 ) -> (publisher::Publisher<T, S>, subscriber::Subscriber<T, S>) {
@@ -115,13 +240,53 @@ pub fn bounded<T, S: SwapSlot<T>>(
     )
 }
 
+/// Function used to create and initialise a (Sender, Receiver) tuple whose buffer,
+/// besides holding at most `size` items, evicts the oldest items whenever the sum
+/// of their `size_of` would exceed `max_bytes`.
+pub fn bounded_bytes<T, S: SwapSlot<T>, F>(
+    size: usize,
+    max_bytes: usize,
+    size_of: F,
+) -> (publisher::Publisher<T, S>, subscriber::Subscriber<T, S>)
+where
+    F: Fn(&T) -> usize + Send + Sync + 'static,
+{
+    use std::sync::Arc;
+    let arc_channel = Arc::new(RingBuffer::with_max_bytes(size, max_bytes, size_of));
+    (
+        publisher::Publisher::from(arc_channel.clone()),
+        subscriber::Subscriber::from(arc_channel),
+    )
+}
+
+/// Function used to create and initialise a (Sender, Receiver) tuple whose buffer holds
+/// the last `hot` items in a small ring, backed by a larger ring retaining the last `cold`
+/// items for the subscribers lapped on the small one or joining late.
+pub fn bounded_tiered<T, S: SwapSlot<T>>(
+    hot: usize,
+    cold: usize,
+) -> (publisher::Publisher<T, S>, subscriber::Subscriber<T, S>) {
+    use std::sync::Arc;
+    let arc_channel = Arc::new(RingBuffer::with_cold_tier(hot, cold));
+    (
+        publisher::Publisher::from(arc_channel.clone()),
+        subscriber::Subscriber::from(arc_channel),
+    )
+}
+
+/// Function used to create a handle which both broadcasts to and receives from a new bus.
+pub fn duplex_bounded<T, S: SwapSlot<T>>(size: usize) -> duplex::Duplex<T, S> {
+    duplex::Duplex::from(bounded(size))
+}
+
+#[cfg(feature = "async")]
 pub fn async_bounded<T, S: SwapSlot<T>>(
     size: usize,
 ) -> (
     async_publisher::AsyncPublisher<T, S>,
     async_subscriber::AsyncSubscriber<T, S>,
 ) {
-    use event_listener::Event;
+    use crate::event::Event;
     use std::sync::Arc;
     let (publisher, subscriber) = bounded(size);
     let event = Arc::new(Event::new());
@@ -0,0 +1,202 @@
+//! Merges of the streams of several subscribers.
+use crate::async_subscriber::AsyncSubscriber;
+use crate::swap_slot::SwapSlot;
+use futures_core::{
+    task::{self, Poll},
+    Stream,
+};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `Ordered` waits for an item of the lagging subscriber by default.
+pub const DEFAULT_SLACK: Duration = Duration::from_millis(10);
+
+/// Merges the items of two subscribers in the order of their keys, e.g. the exchange
+/// timestamps of the ticks of two venues, see `Ordered`.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::async_bounded;
+/// use bus_queue::merge;
+/// use futures::executor::block_on;
+/// use futures::{SinkExt, StreamExt};
+///
+/// let (mut venue_a, a) = async_bounded(10);
+/// let (mut venue_b, b) = async_bounded(10);
+/// block_on(async {
+///     for tick in [1, 4, 5] {
+///         venue_a.send(tick).await.unwrap();
+///     }
+///     for tick in [2, 3, 6] {
+///         venue_b.send(tick).await.unwrap();
+///     }
+/// });
+/// drop((venue_a, venue_b));
+///
+/// let merged = merge::ordered(a, b, |tick: &u64| *tick);
+/// let ticks: Vec<u64> = block_on(merged.map(|tick| *tick).collect());
+/// assert_eq!(ticks, vec![1, 2, 3, 4, 5, 6]);
+/// ```
+pub fn ordered<T, S, K, F>(
+    a: AsyncSubscriber<T, S>,
+    b: AsyncSubscriber<T, S>,
+    key: F,
+) -> Ordered<T, S, F>
+where
+    S: SwapSlot<T>,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    Ordered {
+        subscribers: [a, b],
+        key,
+        heads: [None, None],
+        done: [false, false],
+        slack: DEFAULT_SLACK,
+        waiting_since: None,
+        timer_armed: Arc::new(AtomicBool::new(false)),
+    }
+}
+
+/// Stream of the items of two subscribers in the order of their keys, returned by
+/// `ordered`, ties going to the first subscriber.
+///
+/// An item is yielded once the other subscriber has an item with a greater key, or is
+/// done, or after waiting for it for the slack. Items arriving later than the slack may
+/// be yielded out of order.
+pub struct Ordered<T, S: SwapSlot<T>, F> {
+    subscribers: [AsyncSubscriber<T, S>; 2],
+    key: F,
+    /// Next item of each subscriber
+    heads: [Option<S::Shared>; 2],
+    /// true once the subscriber's stream ended
+    done: [bool; 2],
+    slack: Duration,
+    /// Since when an item waits for the other subscriber
+    waiting_since: Option<Instant>,
+    /// true while a thread is to wake the stream once the slack ran out
+    timer_armed: Arc<AtomicBool>,
+}
+
+// The heads are never pinned.
+impl<T, S: SwapSlot<T>, F> Unpin for Ordered<T, S, F> {}
+
+impl<T, S: SwapSlot<T>, F> Ordered<T, S, F> {
+    /// Sets how long an item waits for the other subscriber's before being yielded,
+    /// `DEFAULT_SLACK` by default.
+    pub fn with_slack(mut self, slack: Duration) -> Self {
+        self.slack = slack;
+        self
+    }
+
+    /// Returns the underlying subscribers, dropping the items received but not yielded.
+    pub fn into_inner(self) -> (AsyncSubscriber<T, S>, AsyncSubscriber<T, S>) {
+        let [a, b] = self.subscribers;
+        (a, b)
+    }
+
+    fn take(&mut self, side: usize) -> Poll<Option<S::Shared>> {
+        self.waiting_since = None;
+        Poll::Ready(self.heads[side].take())
+    }
+
+    /// Wakes the task once the slack ran out, unless already scheduled to.
+    fn wake_after(&self, delay: Duration, cx: &task::Context<'_>) {
+        if self.timer_armed.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let armed = self.timer_armed.clone();
+        let waker = cx.waker().clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            armed.store(false, Ordering::Release);
+            waker.wake();
+        });
+    }
+}
+
+impl<T, S, K, F> Stream for Ordered<T, S, F>
+where
+    S: SwapSlot<T>,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    type Item = S::Shared;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        for side in 0..2 {
+            if this.heads[side].is_none() && !this.done[side] {
+                match Pin::new(&mut this.subscribers[side]).poll_next(cx) {
+                    Poll::Ready(Some(item)) => this.heads[side] = Some(item),
+                    Poll::Ready(None) => this.done[side] = true,
+                    Poll::Pending => {}
+                }
+            }
+        }
+        let side = match &this.heads {
+            [Some(a), Some(b)] => {
+                if (this.key)(b) < (this.key)(a) {
+                    1
+                } else {
+                    0
+                }
+            }
+            [Some(_), None] => 0,
+            [None, Some(_)] => 1,
+            [None, None] => {
+                return if this.done == [true, true] {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Pending
+                };
+            }
+        };
+        let other = 1 - side;
+        if this.heads[other].is_some() || this.done[other] {
+            return this.take(side);
+        }
+        let waited = this
+            .waiting_since
+            .get_or_insert_with(Instant::now)
+            .elapsed();
+        if waited >= this.slack {
+            return this.take(side);
+        }
+        this.wake_after(this.slack - waited, cx);
+        Poll::Pending
+    }
+}
+
+impl<T, S: SwapSlot<T>, F> fmt::Debug for Ordered<T, S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ordered")
+            .field("slack", &self.slack)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ordered;
+    use crate::flavors::arc_swap::async_bounded;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn lagging_side_is_waited_for_up_to_the_slack() {
+        let (mut publisher_a, a) = async_bounded(10);
+        let (_publisher_b, b) = async_bounded(10);
+        let mut merged = ordered(a, b, |item: &u32| *item).with_slack(Duration::from_millis(50));
+        block_on(publisher_a.send(1)).unwrap();
+
+        let start = Instant::now();
+        assert_eq!(block_on(merged.next()).map(|item| *item), Some(1));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
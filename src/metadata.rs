@@ -0,0 +1,81 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Metadata of each slot's item, of a type only known to the handles using it,
+/// enabled through `Builder::metadata`.
+pub(crate) trait Sidecar: Send + Sync {
+    /// Drops the metadata of a slot being overwritten by an item without any.
+    fn clear(&self, index: usize);
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl fmt::Debug for dyn Sidecar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sidecar").finish()
+    }
+}
+
+/// Creates the sidecar of a buffer of the given size.
+pub(crate) type NewSidecar = fn(usize) -> Box<dyn Sidecar>;
+
+/// Metadata of type M stored alongside each slot's item.
+pub(crate) struct Metadata<M> {
+    slots: Vec<Mutex<Option<M>>>,
+}
+
+impl<M: Copy + Send + 'static> Metadata<M> {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            slots: (0..size).map(|_| Mutex::new(None)).collect(),
+        }
+    }
+
+    pub(crate) fn set(&self, index: usize, meta: M) {
+        *self.slots[index].lock().unwrap() = Some(meta);
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<M> {
+        *self.slots[index].lock().unwrap()
+    }
+}
+
+impl<M: Copy + Send + 'static> Sidecar for Metadata<M> {
+    fn clear(&self, index: usize) {
+        *self.slots[index].lock().unwrap() = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::Slot;
+    use crate::Builder;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Meta {
+        trace_id: u64,
+    }
+
+    #[test]
+    fn metadata_is_received_along_with_the_items() {
+        let (publisher, subscriber) = Builder::new(4).metadata::<Meta>().build::<_, Slot<_>>();
+        publisher
+            .broadcast_with_meta("traced", Meta { trace_id: 7 })
+            .unwrap();
+        publisher.broadcast("untraced").unwrap();
+        // Metadata of an other type is not attached.
+        publisher.broadcast_with_meta("traced", 8u64).unwrap();
+
+        let (item, meta) = subscriber.try_recv_with_meta::<Meta>().unwrap();
+        assert_eq!((*item, meta), ("traced", Some(Meta { trace_id: 7 })));
+        let (item, meta) = subscriber.try_recv_with_meta::<Meta>().unwrap();
+        assert_eq!((*item, meta), ("untraced", None));
+        let (_, meta) = subscriber.try_recv_with_meta::<u64>().unwrap();
+        assert_eq!(meta, None);
+    }
+}
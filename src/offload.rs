@@ -0,0 +1,174 @@
+use crate::async_subscriber::AsyncSubscriber;
+use crate::swap_slot::SwapSlot;
+use futures_core::{
+    task::{self, Poll},
+    Stream,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Mutex};
+use std::task::Waker;
+use std::thread;
+
+impl<T, S: SwapSlot<T>> AsyncSubscriber<T, S> {
+    /// Turns the stream into one yielding the items mapped by `f` on a pool of
+    /// `pool_size` threads, in the order they were received.
+    ///
+    /// Heavy work such as decompression or deserialization then does not hold up the
+    /// task polling the subscriber, which keeps receiving while up to `pool_size` items
+    /// are being mapped. A panic of `f` is resumed by the stream.
+    ///
+    /// # Panics
+    ///
+    /// If `pool_size` is 0.
+    pub fn map_offload<R, F>(self, pool_size: usize, f: F) -> MapOffload<T, S, R>
+    where
+        S::Shared: Send + 'static,
+        R: Send + 'static,
+        F: Fn(S::Shared) -> R + Send + Sync + 'static,
+    {
+        assert!(pool_size > 0, "pool_size must be greater than 0");
+        let (jobs, queue) = mpsc::channel::<Job<S::Shared, R>>();
+        let queue = Arc::new(Mutex::new(queue));
+        let f = Arc::new(f);
+        for _ in 0..pool_size {
+            let queue = queue.clone();
+            let f = f.clone();
+            thread::Builder::new()
+                .name("bus-queue-offload".to_owned())
+                .spawn(move || loop {
+                    // The queue is disconnected once the stream is dropped.
+                    let Job { item, output } = match queue.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+                    output.complete(panic::catch_unwind(AssertUnwindSafe(|| f(item))));
+                })
+                .expect("failed to spawn an offload thread");
+        }
+        MapOffload {
+            subscriber: Some(self),
+            jobs,
+            in_flight: VecDeque::with_capacity(pool_size),
+            pool_size,
+        }
+    }
+}
+
+/// Item handed to the pool, along with where to put what it is mapped to.
+struct Job<I, R> {
+    item: I,
+    output: Arc<Output<R>>,
+}
+
+/// Result of a job, and the task to wake once it is there.
+struct Output<R> {
+    state: Mutex<(Option<thread::Result<R>>, Option<Waker>)>,
+}
+
+impl<R> Output<R> {
+    fn complete(&self, output: thread::Result<R>) {
+        let mut state = self.state.lock().unwrap();
+        state.0 = Some(output);
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Stream of items mapped on a pool of threads, returned by `AsyncSubscriber::map_offload`.
+///
+/// The threads exit once the stream is dropped and the items being mapped are done.
+pub struct MapOffload<T, S: SwapSlot<T>, R> {
+    /// None once the subscriber ended
+    subscriber: Option<AsyncSubscriber<T, S>>,
+    jobs: mpsc::Sender<Job<S::Shared, R>>,
+    /// Outputs of the items being mapped, in the order they were received
+    in_flight: VecDeque<Arc<Output<R>>>,
+    pool_size: usize,
+}
+
+impl<T, S: SwapSlot<T>, R> Stream for MapOffload<T, S, R> {
+    type Item = R;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while this.in_flight.len() < this.pool_size {
+            let subscriber = match this.subscriber.as_mut() {
+                Some(subscriber) => subscriber,
+                None => break,
+            };
+            match Pin::new(subscriber).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let output = Arc::new(Output {
+                        state: Mutex::new((None, None)),
+                    });
+                    this.in_flight.push_back(output.clone());
+                    // NOTE: unwrap is safe to use, the threads only exit once the sender is dropped.
+                    this.jobs.send(Job { item, output }).unwrap();
+                }
+                Poll::Ready(None) => this.subscriber = None,
+                Poll::Pending => break,
+            }
+        }
+        let output = match this.in_flight.front() {
+            Some(output) => output,
+            None if this.subscriber.is_none() => return Poll::Ready(None),
+            None => return Poll::Pending,
+        };
+        let mut state = output.state.lock().unwrap();
+        match state.0.take() {
+            Some(output) => {
+                drop(state);
+                this.in_flight.pop_front();
+                match output {
+                    Ok(output) => Poll::Ready(Some(output)),
+                    Err(payload) => panic::resume_unwind(payload),
+                }
+            }
+            None => {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>, R> fmt::Debug for MapOffload<T, S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapOffload")
+            .field("subscriber", &self.subscriber)
+            .field("in_flight", &self.in_flight.len())
+            .field("pool_size", &self.pool_size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::async_bounded;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn items_are_mapped_in_order() {
+        let (mut publisher, subscriber) = async_bounded(10);
+        let decoded = subscriber.map_offload(3, |item: std::sync::Arc<u64>| {
+            // Later items are mapped faster, they are still yielded in order.
+            thread::sleep(Duration::from_millis(10 - *item));
+            *item * 2
+        });
+        for i in 0..8 {
+            block_on(publisher.send(i)).unwrap();
+        }
+        drop(publisher);
+        assert_eq!(
+            block_on(decoded.collect::<Vec<_>>()),
+            vec![0, 2, 4, 6, 8, 10, 12, 14]
+        );
+    }
+}
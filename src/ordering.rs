@@ -0,0 +1,69 @@
+use crate::atomic_counter::AtomicCounter;
+use std::sync::Mutex;
+
+/// Check that a receiver receives items in the order they were published, catching
+/// integration bugs like a receiver shared between threads or a faulty custom flavor.
+///
+/// A regression panics in debug builds and is counted in release builds.
+#[derive(Debug)]
+pub(crate) struct OrderingCheck {
+    /// Position past the last item received, if any
+    last: Mutex<Option<usize>>,
+    /// Number of regressions detected
+    anomalies: AtomicCounter,
+}
+
+impl Default for OrderingCheck {
+    fn default() -> Self {
+        Self {
+            last: Mutex::new(None),
+            anomalies: AtomicCounter::new(0),
+        }
+    }
+}
+
+impl OrderingCheck {
+    /// Checks that the receiver moved past `position` came from behind it.
+    pub(crate) fn received(&self, position: usize) {
+        let previous = self.last.lock().unwrap().replace(position);
+        match previous {
+            Some(previous) if position.wrapping_sub(previous) as isize <= 0 => {
+                self.anomalies.inc();
+                debug_assert!(
+                    false,
+                    "received the item of sequence {} after the one of sequence {}",
+                    position.wrapping_sub(1),
+                    previous.wrapping_sub(1)
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Forgets the last item received, for a receiver moved back on purpose.
+    pub(crate) fn reset(&self) {
+        *self.last.lock().unwrap() = None;
+    }
+
+    pub(crate) fn anomalies(&self) -> usize {
+        self.anomalies.get()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::bounded;
+
+    #[test]
+    #[should_panic(expected = "received the item of sequence 0 after the one of sequence 1")]
+    fn regressions_panic_in_debug_builds() {
+        let (tx, rx) = bounded(4);
+        (0..2).for_each(|x| tx.broadcast(x).unwrap());
+        rx.try_recv().unwrap();
+        rx.try_recv().unwrap();
+        assert_eq!(rx.ordering_anomalies(), 0);
+        // A cursor moved back behind the receiver's back.
+        rx.ri.set(0);
+        rx.try_recv().unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use crate::atomic_counter::AtomicCounter;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+/// What broadcasting an item to a full bus does, see `OverflowPolicy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The oldest item is overwritten, lapping the subscribers yet to receive it,
+    /// which is what a bus without a policy always does
+    OverwriteOldest,
+    /// The new item is rejected, the broadcast failing with it
+    RejectNewest,
+}
+
+/// Decides what broadcasting an item to a full bus does, see
+/// `Builder::build_with_overflow_policy`.
+///
+/// A bus with a policy is full once its slowest subscriber has as many items left to
+/// receive as the bus holds. Only broadcasts of single items are subject to the policy.
+pub trait OverflowPolicy<T>: Send + Sync {
+    /// Returns what to do with `newest`, given `oldest`, the item it would overwrite.
+    fn on_overflow(&self, oldest: &T, newest: &T) -> Overflow;
+}
+
+impl<T> fmt::Debug for dyn OverflowPolicy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverflowPolicy").finish()
+    }
+}
+
+/// Rejects the new items while the bus is full, e.g. for sampling streams whose
+/// subscribers would rather miss the latest samples than be lapped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectNewest;
+
+impl<T> OverflowPolicy<T> for RejectNewest {
+    fn on_overflow(&self, _: &T, _: &T) -> Overflow {
+        Overflow::RejectNewest
+    }
+}
+
+/// Keeps whichever of the oldest and the new item has the higher priority, as returned
+/// by the function, overwriting the oldest one on ties.
+#[derive(Clone, Copy)]
+pub struct ByPriority<F>(pub F);
+
+impl<T, P: Ord, F: Fn(&T) -> P + Send + Sync> OverflowPolicy<T> for ByPriority<F> {
+    fn on_overflow(&self, oldest: &T, newest: &T) -> Overflow {
+        if (self.0)(newest) < (self.0)(oldest) {
+            Overflow::RejectNewest
+        } else {
+            Overflow::OverwriteOldest
+        }
+    }
+}
+
+impl<F> fmt::Debug for ByPriority<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByPriority").finish()
+    }
+}
+
+/// Read indexes of the subscribers, tracked by buses with an overflow policy.
+#[derive(Debug, Default)]
+pub(crate) struct Cursors {
+    cursors: Mutex<Vec<Weak<AtomicCounter>>>,
+}
+
+impl Cursors {
+    /// Starts tracking a new subscriber reading from `ri`.
+    pub(crate) fn register(&self, ri: usize) -> Arc<AtomicCounter> {
+        let cursor = Arc::new(AtomicCounter::new(ri));
+        let mut cursors = self.cursors.lock().unwrap();
+        cursors.retain(|cursor| cursor.strong_count() > 0);
+        cursors.push(Arc::downgrade(&cursor));
+        cursor
+    }
+
+    /// Returns the most items a subscriber has left to receive.
+    pub(crate) fn max_pending(&self, wi: usize) -> usize {
+        self.cursors
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|cursor| wi.wrapping_sub(cursor.get()))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ByPriority;
+    use crate::flavors::arc_swap::Slot;
+    use crate::Builder;
+
+    #[test]
+    fn lower_priority_items_are_rejected() {
+        let (sender, receiver) = Builder::new(2)
+            .build_with_overflow_policy::<(u8, char), Slot<_>, _>(ByPriority(
+                |item: &(u8, char)| item.0,
+            ));
+        sender.broadcast((1, 'a')).unwrap();
+        sender.broadcast((2, 'b')).unwrap();
+        // The bus is full, 'c' has a lower priority than 'a'.
+        assert_eq!(sender.broadcast((0, 'c')).unwrap_err().0, (0, 'c'));
+        sender.broadcast((1, 'd')).unwrap();
+
+        assert_eq!(receiver.try_recv().unwrap().1, 'b');
+        // Receiving makes room, the next item is published whatever its priority.
+        sender.broadcast((0, 'e')).unwrap();
+        let received: Vec<_> = receiver.map(|item| item.1).collect::<Vec<_>>();
+        assert_eq!(received, vec!['d', 'e']);
+    }
+}
@@ -0,0 +1,223 @@
+//! Blocking strategies for the blocking receive operations.
+//!
+//! `Subscriber::recv` blocks the current thread, `Subscriber::recv_with_parker` lets
+//! embedders block differently, e.g. by switching fibers, and lets tests drive the
+//! wait/wake logic deterministically with a `FakeParker`.
+use crate::event::EventListener;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{self, Poll, Wake, Waker};
+use std::time::Instant;
+
+/// Notification a parked receiver waits for, completing once an item is published
+/// or the publisher is gone. Also a future, for parkers that poll it.
+pub struct Wakeup {
+    listener: EventListener,
+}
+
+impl Wakeup {
+    pub(crate) fn new(listener: EventListener) -> Self {
+        Self { listener }
+    }
+
+    /// Blocks the current thread until notified.
+    pub fn wait(self) {
+        self.listener.wait();
+    }
+
+    /// Blocks the current thread until notified or until the deadline,
+    /// returns true if notified.
+    pub fn wait_deadline(self, deadline: Instant) -> bool {
+        self.listener.wait_deadline(deadline)
+    }
+
+    /// Checks if the notification has arrived, without blocking.
+    pub fn is_notified(&mut self) -> bool {
+        struct Noop;
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = task::Context::from_waker(&waker);
+        Pin::new(&mut self.listener).poll(&mut cx).is_ready()
+    }
+}
+
+impl Future for Wakeup {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.listener).poll(cx)
+    }
+}
+
+impl fmt::Debug for Wakeup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Wakeup").finish()
+    }
+}
+
+/// Blocks a receiver until its wakeup is notified.
+pub trait Parker {
+    /// Blocks until the wakeup is notified.
+    fn park(&self, wakeup: Wakeup);
+
+    /// Blocks until the wakeup is notified or until the deadline, returns true if notified.
+    fn park_deadline(&self, wakeup: Wakeup, deadline: Instant) -> bool;
+}
+
+/// Parker blocking the current thread, used by `Subscriber::recv`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadParker;
+
+impl Parker for ThreadParker {
+    fn park(&self, wakeup: Wakeup) {
+        wakeup.wait();
+    }
+
+    fn park_deadline(&self, wakeup: Wakeup, deadline: Instant) -> bool {
+        wakeup.wait_deadline(deadline)
+    }
+}
+
+/// Deterministic parker for tests: instead of blocking, every park runs the next
+/// scripted step, which is expected to wake the receiver.
+///
+/// `park` panics if there is no step left or the step did not wake the receiver,
+/// since a real parker would block forever. `park_deadline` treats both as the
+/// deadline passing, without waiting for it.
+#[derive(Default)]
+pub struct FakeParker {
+    steps: RefCell<VecDeque<Box<dyn FnOnce()>>>,
+    parks: Cell<usize>,
+}
+
+impl FakeParker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step run on a park, after the previously added ones.
+    pub fn then<F: FnOnce() + 'static>(&self, step: F) -> &Self {
+        self.steps.borrow_mut().push_back(Box::new(step));
+        self
+    }
+
+    /// Returns how many times a receiver parked.
+    pub fn parks(&self) -> usize {
+        self.parks.get()
+    }
+
+    /// Runs the next step and checks if it woke the receiver.
+    fn step(&self, mut wakeup: Wakeup) -> Result<(), &'static str> {
+        self.parks.set(self.parks.get() + 1);
+        let step = self.steps.borrow_mut().pop_front();
+        match step {
+            Some(step) => step(),
+            None => return Err("parked with no step left"),
+        }
+        if wakeup.is_notified() {
+            Ok(())
+        } else {
+            Err("parked and the step did not wake the receiver")
+        }
+    }
+}
+
+impl Parker for FakeParker {
+    fn park(&self, wakeup: Wakeup) {
+        if let Err(error) = self.step(wakeup) {
+            panic!("{}, a real parker would block forever", error);
+        }
+    }
+
+    fn park_deadline(&self, wakeup: Wakeup, _deadline: Instant) -> bool {
+        self.step(wakeup).is_ok()
+    }
+}
+
+impl fmt::Debug for FakeParker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FakeParker")
+            .field("steps", &self.steps.borrow().len())
+            .field("parks", &self.parks.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FakeParker;
+    use crate::flavors::arc_swap::bounded;
+    use crate::ring_buffer::{RecvError, RecvTimeoutError};
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn recv_wakes_on_broadcast() {
+        let (sender, receiver) = bounded(2);
+        let sender = Rc::new(sender);
+        let parker = FakeParker::new();
+        let publisher = sender.clone();
+        parker.then(move || publisher.broadcast(1).unwrap());
+
+        assert_eq!(*receiver.recv_with_parker(&parker).unwrap(), 1);
+        assert_eq!(parker.parks(), 1);
+
+        // Items already published are received without parking.
+        sender.broadcast(2).unwrap();
+        assert_eq!(*receiver.recv_with_parker(&parker).unwrap(), 2);
+        assert_eq!(parker.parks(), 1);
+    }
+
+    #[test]
+    fn recv_wakes_on_close() {
+        let (sender, receiver) = bounded::<i32>(2);
+        let parker = FakeParker::new();
+        parker.then(move || drop(sender));
+
+        assert_eq!(receiver.recv_with_parker(&parker), Err(RecvError));
+    }
+
+    #[test]
+    fn recv_deadline_wakes_or_times_out() {
+        let (sender, receiver) = bounded(2);
+        let deadline = Instant::now() + Duration::from_secs(3600);
+        let parker = FakeParker::new();
+        // Nothing published, the deadline passes without waiting for it.
+        parker.then(|| {});
+        assert_eq!(
+            receiver.recv_deadline_with_parker(deadline, &parker),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert_eq!(parker.parks(), 1);
+
+        parker.then(move || sender.broadcast(1).unwrap());
+        assert_eq!(
+            receiver
+                .recv_deadline_with_parker(deadline, &parker)
+                .map(|item| *item),
+            Ok(1)
+        );
+        // The publisher was dropped along with the step.
+        assert_eq!(
+            receiver.recv_deadline_with_parker(deadline, &parker),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(parker.parks(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "did not wake")]
+    fn detects_missed_wakeups() {
+        let (_sender, receiver) = bounded::<i32>(2);
+        let parker = FakeParker::new();
+        parker.then(|| {});
+
+        let _ = receiver.recv_with_parker(&parker);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+
+/// View of the slots of the items a subscriber has yet to receive, returned by
+/// `Subscriber::as_slices`.
+///
+/// The items are borrowed in place with `SwapSlot::load_with`, without cloning a shared
+/// pointer each, e.g. to run vectorized computations over a batch of numbers. The
+/// publisher is not held back meanwhile: an item overwritten while the view is alive is
+/// seen as the newer one, which `commit` reports. Evicted items load nothing.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use bus_queue::SwapSlot;
+///
+/// let (tx, rx) = bounded(4);
+/// (1..=6).for_each(|x| tx.broadcast(x).unwrap());
+///
+/// let pending = rx.as_slices();
+/// let (older, newer) = pending.as_slices();
+/// let sum: i32 = older
+///     .iter()
+///     .chain(newer)
+///     .filter_map(|slot| slot.load_with(|x| *x))
+///     .sum();
+/// assert!(pending.commit());
+/// assert_eq!(sum, 3 + 4 + 5 + 6);
+/// assert!(rx.try_recv().is_err());
+/// ```
+pub struct PendingSlices<'a, T, S: SwapSlot<T>> {
+    subscriber: &'a Subscriber<T, S>,
+    /// Sequence of the first item of the view
+    start: usize,
+    /// Sequence past the last item of the view
+    end: usize,
+}
+
+impl<T, S: SwapSlot<T>> Subscriber<T, S> {
+    /// Returns a view of the slots of the retained items not received yet, which are
+    /// only received once the view is committed.
+    pub fn as_slices(&self) -> PendingSlices<'_, T, S> {
+        let end = self.buffer.write_index();
+        let oldest = end.wrapping_sub(self.buffer.len());
+        let ri = self.ri.get();
+        let start = if end.wrapping_sub(ri) > self.buffer.len() {
+            oldest.wrapping_add(self.skip_items.min(self.buffer.len()))
+        } else {
+            ri
+        };
+        PendingSlices {
+            subscriber: self,
+            start,
+            end,
+        }
+    }
+}
+
+impl<'a, T, S: SwapSlot<T>> PendingSlices<'a, T, S> {
+    /// Returns the slots of the items, oldest first, split in two where the
+    /// bus wraps around.
+    pub fn as_slices(&self) -> (&'a [S], &'a [S]) {
+        self.subscriber.buffer.slots(self.start, self.end)
+    }
+
+    /// Returns the sequence of the first item.
+    pub fn first_sequence(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the number of items.
+    pub fn len(&self) -> usize {
+        self.end.wrapping_sub(self.start)
+    }
+
+    /// Checks if there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Checks if none of the items was overwritten since the view was created.
+    pub fn is_intact(&self) -> bool {
+        self.subscriber
+            .buffer
+            .write_index()
+            .wrapping_sub(self.start)
+            <= self.subscriber.buffer.len()
+    }
+
+    /// Receives the items of the view, returning false if some of them were
+    /// overwritten while it was inspected.
+    pub fn commit(self) -> bool {
+        let subscriber = self.subscriber;
+        if self.start != subscriber.ri.get() {
+            subscriber.laps.inc();
+        }
+        if !self.is_empty() {
+            subscriber.check_ordering(self.end);
+        }
+        subscriber.ri.set(self.end);
+        subscriber.fences.notify();
+        subscriber.sync_cursor();
+        self.is_intact()
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for PendingSlices<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingSlices")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::bounded;
+    use crate::swap_slot::SwapSlot;
+
+    #[test]
+    fn overwritten_items_are_reported() {
+        let (tx, rx) = bounded(3);
+        (0..2).for_each(|x| tx.broadcast(x).unwrap());
+        let pending = rx.as_slices();
+        assert_eq!(pending.len(), 2);
+        (2..5).for_each(|x| tx.broadcast(x).unwrap());
+
+        let (older, newer) = pending.as_slices();
+        let items: Vec<_> = older
+            .iter()
+            .chain(newer)
+            .filter_map(|slot| slot.load_with(|x| *x))
+            .collect();
+        assert_eq!(items, vec![4, 1]);
+        assert!(!pending.commit());
+        assert_eq!(rx.as_slices().len(), 3);
+    }
+}
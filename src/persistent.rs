@@ -0,0 +1,551 @@
+//! Ring of byte records backed by a memory mapped file, surviving crashes of the
+//! processes publishing to and receiving from it.
+//!
+//! The file holds a header and `capacity` fixed size slots, so that like the bus it never
+//! grows. Every record is stored along with its sequence and a checksum of both, which
+//! subscribers validate, and the write index is kept in the header: a publisher opening
+//! the file again resumes the numbering, and subscribers can resume from the sequence
+//! they last received.
+//!
+//! Records are in the file as soon as they are broadcast and survive the crash of the
+//! process, surviving the crash of the machine takes `PersistentPublisher::flush`.
+//! There must be a single publisher to a file at a time.
+use memmap2::MmapRaw;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"BUSQPERS");
+const VERSION: u64 = 1;
+const HEADER_SIZE: usize = 64;
+/// Offsets of the header fields
+const CAPACITY: usize = 16;
+const SLOT_SIZE: usize = 24;
+const WRITE_INDEX: usize = 32;
+/// Sequence, length and checksum preceding the payload of every slot
+const SLOT_HEADER_SIZE: usize = 24;
+/// Sequence of a slot being written
+const WRITING: u64 = u64::MAX;
+
+/// Layout of a ring file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    capacity: u64,
+    /// Largest payload of a slot, a multiple of 8
+    slot_size: usize,
+}
+
+impl Layout {
+    fn stride(&self) -> usize {
+        SLOT_HEADER_SIZE + self.slot_size
+    }
+
+    /// Returns the size of the file, None if it does not fit in a usize.
+    fn file_len(&self) -> Option<usize> {
+        usize::try_from(self.capacity)
+            .ok()?
+            .checked_mul(SLOT_HEADER_SIZE.checked_add(self.slot_size)?)?
+            .checked_add(HEADER_SIZE)
+    }
+
+    /// Returns the offset of the slot holding the sequence.
+    fn slot(&self, seq: u64) -> usize {
+        HEADER_SIZE + (seq % self.capacity) as usize * self.stride()
+    }
+}
+
+/// Mapped ring file, only ever accessed through atomics since publisher and
+/// subscribers of other processes write and read it concurrently.
+struct Ring {
+    map: MmapRaw,
+    layout: Layout,
+}
+
+impl Ring {
+    /// Reads the layout from the header of the mapped file.
+    fn new(map: MmapRaw) -> io::Result<Self> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        if map.len() < HEADER_SIZE {
+            return Err(invalid("not a bus_queue ring file"));
+        }
+        let mut ring = Ring {
+            map,
+            layout: Layout {
+                capacity: 1,
+                slot_size: 0,
+            },
+        };
+        if ring.word(0).load(Ordering::Acquire) != MAGIC {
+            return Err(invalid("not a bus_queue ring file"));
+        }
+        if ring.word(8).load(Ordering::Acquire) != VERSION {
+            return Err(invalid("unsupported ring file version"));
+        }
+        let slot_size = usize::try_from(ring.word(SLOT_SIZE).load(Ordering::Acquire))
+            .map_err(|_| invalid("corrupt ring file header"))?;
+        let layout = Layout {
+            capacity: ring.word(CAPACITY).load(Ordering::Acquire),
+            slot_size,
+        };
+        if layout.capacity == 0 || slot_size % 8 != 0 || layout.file_len() != Some(ring.map.len()) {
+            return Err(invalid("corrupt ring file header"));
+        }
+        ring.layout = layout;
+        Ok(ring)
+    }
+
+    /// Returns the word of the map at the offset, a multiple of 8.
+    fn word(&self, offset: usize) -> &AtomicU64 {
+        assert!(offset + 8 <= self.map.len());
+        // The map is page aligned and lives as long as the ring. The pointer is the
+        // mutable one since the publisher stores through it.
+        unsafe { &*(self.map.as_mut_ptr().add(offset) as *const AtomicU64) }
+    }
+
+    /// Writes the record to its slot, marking the slot as being written meanwhile.
+    fn write(&self, seq: u64, payload: &[u8]) {
+        let slot = self.layout.slot(seq);
+        self.word(slot).store(WRITING, Ordering::Relaxed);
+        fence(Ordering::Release);
+        for (i, chunk) in payload.chunks(8).enumerate() {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            self.word(slot + SLOT_HEADER_SIZE + i * 8)
+                .store(u64::from_ne_bytes(bytes), Ordering::Relaxed);
+        }
+        self.word(slot + 8)
+            .store(payload.len() as u64, Ordering::Relaxed);
+        self.word(slot + 16)
+            .store(checksum(seq, payload), Ordering::Relaxed);
+        self.word(slot).store(seq, Ordering::Release);
+    }
+
+    /// Reads the record of the sequence, None if it fails validation.
+    ///
+    /// The sequence of the slot is checked again once the payload is read, failing
+    /// with the sequence found if the slot holds another record or was written to
+    /// meanwhile.
+    fn read(&self, seq: u64) -> Result<Option<Vec<u8>>, u64> {
+        let slot = self.layout.slot(seq);
+        let found = self.word(slot).load(Ordering::Acquire);
+        if found != seq {
+            return Err(found);
+        }
+        let len = self.word(slot + 8).load(Ordering::Relaxed);
+        let expected = self.word(slot + 16).load(Ordering::Relaxed);
+        let payload = match usize::try_from(len) {
+            Ok(len) if len <= self.layout.slot_size => {
+                let mut payload = Vec::with_capacity(len + 7);
+                for offset in (0..len).step_by(8) {
+                    let word = self.word(slot + SLOT_HEADER_SIZE + offset);
+                    payload.extend_from_slice(&word.load(Ordering::Relaxed).to_ne_bytes());
+                }
+                payload.truncate(len);
+                Some(payload)
+            }
+            _ => None,
+        };
+        fence(Ordering::Acquire);
+        let found = self.word(slot).load(Ordering::Relaxed);
+        if found != seq {
+            return Err(found);
+        }
+        Ok(payload.filter(|payload| checksum(seq, payload) == expected))
+    }
+}
+
+/// FNV-1a hash of the sequence and the payload of a record.
+fn checksum(seq: u64, payload: &[u8]) -> u64 {
+    seq.to_le_bytes()
+        .iter()
+        .chain(payload)
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Publisher writing records to a ring file.
+///
+/// ```rust
+/// use bus_queue::{PersistentPublisher, PersistentSubscriber};
+///
+/// let path = std::env::temp_dir().join(format!("bus_queue-doc-{}", std::process::id()));
+/// let mut tx = PersistentPublisher::open(&path, 16, 32).unwrap();
+/// tx.broadcast(b"first").unwrap();
+/// drop(tx);
+///
+/// // Restarted, the publisher carries on with the next sequence.
+/// let mut tx = PersistentPublisher::open(&path, 16, 32).unwrap();
+/// assert_eq!(tx.broadcast(b"second").unwrap(), 1);
+///
+/// let rx = PersistentSubscriber::open(&path).unwrap();
+/// assert_eq!(rx.try_recv().unwrap(), (0, b"first".to_vec()));
+/// assert_eq!(rx.try_recv().unwrap(), (1, b"second".to_vec()));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct PersistentPublisher {
+    ring: Ring,
+    /// Sequence of the next record
+    wi: u64,
+}
+
+impl PersistentPublisher {
+    /// Opens the ring file at the path, creating it with `capacity` slots holding
+    /// payloads of up to `slot_size` bytes if there is none. Fails if the file exists
+    /// with another capacity or slot size.
+    ///
+    /// A record broadcast just before a crash but not yet counted in the header is
+    /// recovered if it is whole.
+    pub fn open<P: AsRef<Path>>(path: P, capacity: usize, slot_size: usize) -> io::Result<Self> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "ring too large");
+        if capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a ring needs at least one slot",
+            ));
+        }
+        let layout = Layout {
+            capacity: capacity as u64,
+            slot_size: slot_size.checked_add(7).ok_or_else(too_large)? / 8 * 8,
+        };
+        let file_len = layout.file_len().ok_or_else(too_large)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let created = file.metadata()?.len() == 0;
+        let ring = if created {
+            file.set_len(file_len as u64)?;
+            let ring = Ring {
+                map: MmapRaw::map_raw(&file)?,
+                layout,
+            };
+            ring.word(CAPACITY)
+                .store(layout.capacity, Ordering::Relaxed);
+            ring.word(SLOT_SIZE)
+                .store(layout.slot_size as u64, Ordering::Relaxed);
+            ring.word(8).store(VERSION, Ordering::Relaxed);
+            // Written last, a file without it is not a ring.
+            ring.word(0).store(MAGIC, Ordering::Release);
+            ring.map.flush()?;
+            ring
+        } else {
+            Ring::new(MmapRaw::map_raw(&file)?)?
+        };
+        if ring.layout != layout {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "ring file has another capacity or slot size",
+            ));
+        }
+        let mut wi = ring.word(WRITE_INDEX).load(Ordering::Acquire);
+        if matches!(ring.read(wi), Ok(Some(_))) {
+            wi += 1;
+            ring.word(WRITE_INDEX).store(wi, Ordering::Release);
+        }
+        Ok(Self { ring, wi })
+    }
+
+    /// Writes the payload to the next slot, returning its sequence.
+    pub fn broadcast(&mut self, payload: &[u8]) -> Result<u64, PayloadTooLargeError> {
+        if payload.len() > self.ring.layout.slot_size {
+            return Err(PayloadTooLargeError {
+                len: payload.len(),
+                max: self.ring.layout.slot_size,
+            });
+        }
+        let seq = self.wi;
+        self.ring.write(seq, payload);
+        self.wi += 1;
+        self.ring
+            .word(WRITE_INDEX)
+            .store(self.wi, Ordering::Release);
+        Ok(seq)
+    }
+
+    /// Returns the sequence of the next record.
+    pub fn write_index(&self) -> u64 {
+        self.wi
+    }
+
+    /// Returns the number of slots.
+    pub fn capacity(&self) -> usize {
+        self.ring.layout.capacity as usize
+    }
+
+    /// Writes the records broadcast so far through to the disk, so that they survive
+    /// a crash of the machine as well.
+    pub fn flush(&self) -> io::Result<()> {
+        self.ring.map.flush()
+    }
+}
+
+impl fmt::Debug for PersistentPublisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentPublisher")
+            .field("capacity", &self.ring.layout.capacity)
+            .field("slot_size", &self.ring.layout.slot_size)
+            .field("wi", &self.wi)
+            .finish()
+    }
+}
+
+/// Subscriber reading the records of a ring file, possibly from another process.
+pub struct PersistentSubscriber {
+    ring: Ring,
+    /// Sequence of the next record to receive
+    ri: AtomicU64,
+}
+
+impl PersistentSubscriber {
+    /// Opens the ring file at the path, positioned at the oldest retained record.
+    ///
+    /// The file is mapped read-write even though the subscriber never writes to it:
+    /// atomic loads may be implemented with stores, faulting on read-only memory.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let ring = Ring::new(MmapRaw::map_raw(&file)?)?;
+        let wi = ring.word(WRITE_INDEX).load(Ordering::Acquire);
+        Ok(Self {
+            ri: AtomicU64::new(wi.saturating_sub(ring.layout.capacity)),
+            ring,
+        })
+    }
+
+    /// Receives the next record along with its sequence.
+    ///
+    /// Returns `Lagged` with the number of records lost if the publisher overwrote
+    /// the next ones, the next call returning the oldest retained record, and
+    /// `Corrupt` for a record failing validation, which is passed over.
+    pub fn try_recv(&self) -> Result<(u64, Vec<u8>), PersistentRecvError> {
+        let capacity = self.ring.layout.capacity;
+        loop {
+            let ri = self.ri.load(Ordering::Relaxed);
+            let wi = self.ring.word(WRITE_INDEX).load(Ordering::Acquire);
+            if ri >= wi {
+                return Err(PersistentRecvError::Empty);
+            }
+            let oldest = wi.saturating_sub(capacity);
+            if ri < oldest {
+                self.ri.store(oldest, Ordering::Relaxed);
+                return Err(PersistentRecvError::Lagged(oldest - ri));
+            }
+            let found = match self.ring.read(ri) {
+                Ok(payload) => {
+                    self.ri.store(ri + 1, Ordering::Relaxed);
+                    return payload
+                        .map(|payload| (ri, payload))
+                        .ok_or(PersistentRecvError::Corrupt(ri));
+                }
+                Err(found) => found,
+            };
+            let lapped = found != WRITING
+                && found > ri
+                && self.ring.layout.slot(found) == self.ring.layout.slot(ri);
+            if lapped && found < self.ring.word(WRITE_INDEX).load(Ordering::Acquire) {
+                // Overwritten since the write index was read, the lag is reported next.
+                continue;
+            }
+            // The slot is being overwritten by a record not counted yet, or was left
+            // half written or garbled by a crash: waiting would not resolve it.
+            self.ri.store(ri + 1, Ordering::Relaxed);
+            return Err(if lapped {
+                PersistentRecvError::Lagged(1)
+            } else {
+                PersistentRecvError::Corrupt(ri)
+            });
+        }
+    }
+
+    /// Returns the sequence of the next record to receive, to resume from with
+    /// `set_position` after a restart.
+    pub fn position(&self) -> u64 {
+        self.ri.load(Ordering::Relaxed)
+    }
+
+    /// Moves the subscriber to the sequence.
+    pub fn set_position(&self, seq: u64) {
+        self.ri.store(seq, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Debug for PersistentSubscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentSubscriber")
+            .field("capacity", &self.ring.layout.capacity)
+            .field("slot_size", &self.ring.layout.slot_size)
+            .field("ri", &self.position())
+            .finish()
+    }
+}
+
+/// Error returned by `PersistentPublisher::broadcast` for a payload larger than a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadTooLargeError {
+    pub len: usize,
+    /// Largest payload of a slot
+    pub max: usize,
+}
+
+impl fmt::Display for PayloadTooLargeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "payload of {} bytes does not fit in a slot of {}",
+            self.len, self.max
+        )
+    }
+}
+
+impl std::error::Error for PayloadTooLargeError {}
+
+/// Error returned by `PersistentSubscriber::try_recv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistentRecvError {
+    /// No record is available at the moment.
+    Empty,
+    /// The given number of records was overwritten before being received.
+    Lagged(u64),
+    /// The record of the given sequence failed validation, e.g. left half written by
+    /// a crash of the publisher or overwritten while being read.
+    Corrupt(u64),
+}
+
+impl fmt::Display for PersistentRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistentRecvError::Empty => write!(f, "ring empty"),
+            PersistentRecvError::Lagged(n) => write!(f, "ring lagged by {}", n),
+            PersistentRecvError::Corrupt(seq) => write!(f, "record {} is corrupt", seq),
+        }
+    }
+}
+
+impl std::error::Error for PersistentRecvError {}
+
+#[cfg(test)]
+mod test {
+    use super::{PersistentPublisher, PersistentRecvError, PersistentSubscriber, HEADER_SIZE};
+    use std::fs::{self, OpenOptions};
+    use std::io::{Seek, SeekFrom, Write};
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bus_queue-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn recovers_from_crashes() {
+        let path = temp_path("persistent-recovery");
+        let _ = fs::remove_file(&path);
+        let mut publisher = PersistentPublisher::open(&path, 4, 8).unwrap();
+        assert!(publisher.broadcast(&[0; 9]).is_err());
+        for i in 0..6u8 {
+            publisher.broadcast(&[i; 3]).unwrap();
+        }
+        drop(publisher);
+        assert!(PersistentPublisher::open(&path, 5, 8).is_err());
+
+        // Tear the payload of record 3, in the last slot.
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start((HEADER_SIZE + 3 * 32 + 24) as u64))
+            .unwrap();
+        file.write_all(&[9]).unwrap();
+
+        let subscriber = PersistentSubscriber::open(&path).unwrap();
+        assert_eq!(subscriber.try_recv(), Ok((2, vec![2; 3])));
+        assert_eq!(subscriber.try_recv(), Err(PersistentRecvError::Corrupt(3)));
+        assert_eq!(subscriber.try_recv(), Ok((4, vec![4; 3])));
+
+        // Crash before the last record was counted in the header.
+        file.seek(SeekFrom::Start(32)).unwrap();
+        file.write_all(&5u64.to_ne_bytes()).unwrap();
+        drop(file);
+        let mut publisher = PersistentPublisher::open(&path, 4, 8).unwrap();
+        assert_eq!(publisher.write_index(), 6);
+        for i in 6..10u8 {
+            publisher.broadcast(&[i; 3]).unwrap();
+        }
+        assert_eq!(subscriber.try_recv(), Err(PersistentRecvError::Lagged(1)));
+        assert_eq!(subscriber.try_recv(), Ok((6, vec![6; 3])));
+        subscriber.set_position(9);
+        assert_eq!(subscriber.try_recv(), Ok((9, vec![9; 3])));
+        assert_eq!(subscriber.try_recv(), Err(PersistentRecvError::Empty));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn passes_over_slots_left_writing() {
+        let path = temp_path("persistent-writing");
+        let _ = fs::remove_file(&path);
+        let mut publisher = PersistentPublisher::open(&path, 2, 8).unwrap();
+        publisher.broadcast(b"a").unwrap();
+        publisher.broadcast(b"b").unwrap();
+        drop(publisher);
+
+        // Crash while overwriting the first slot.
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(HEADER_SIZE as u64)).unwrap();
+        file.write_all(&u64::MAX.to_ne_bytes()).unwrap();
+        drop(file);
+
+        let subscriber = PersistentSubscriber::open(&path).unwrap();
+        assert_eq!(subscriber.try_recv(), Err(PersistentRecvError::Corrupt(0)));
+        assert_eq!(subscriber.try_recv(), Ok((1, b"b".to_vec())));
+        assert_eq!(subscriber.try_recv(), Err(PersistentRecvError::Empty));
+
+        let mut publisher = PersistentPublisher::open(&path, 2, 8).unwrap();
+        assert_eq!(publisher.write_index(), 2);
+        assert_eq!(publisher.broadcast(b"c"), Ok(2));
+        assert_eq!(subscriber.try_recv(), Ok((2, b"c".to_vec())));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_corrupt_headers() {
+        let path = temp_path("persistent-header");
+        let _ = fs::remove_file(&path);
+        assert!(PersistentPublisher::open(&path, usize::MAX, 8).is_err());
+        assert!(PersistentPublisher::open(&path, 2, usize::MAX).is_err());
+        let _ = fs::remove_file(&path);
+
+        drop(PersistentPublisher::open(&path, 2, 8).unwrap());
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(16)).unwrap();
+        file.write_all(&u64::MAX.to_ne_bytes()).unwrap();
+        assert!(PersistentSubscriber::open(&path).is_err());
+        assert!(PersistentPublisher::open(&path, 2, 8).is_err());
+
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(b"garbage!").unwrap();
+        assert!(PersistentSubscriber::open(&path).is_err());
+        assert!(PersistentPublisher::open(&path, 2, 8).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reopens_after_wraparound() {
+        let path = temp_path("persistent-wraparound");
+        let _ = fs::remove_file(&path);
+        let mut publisher = PersistentPublisher::open(&path, 3, 8).unwrap();
+        for i in 0..10u8 {
+            publisher.broadcast(&[i]).unwrap();
+        }
+        drop(publisher);
+
+        let mut publisher = PersistentPublisher::open(&path, 3, 8).unwrap();
+        assert_eq!(publisher.write_index(), 10);
+        assert_eq!(publisher.broadcast(&[10]), Ok(10));
+        let subscriber = PersistentSubscriber::open(&path).unwrap();
+        for i in 8..11u8 {
+            assert_eq!(subscriber.try_recv(), Ok((u64::from(i), vec![i])));
+        }
+        assert_eq!(subscriber.try_recv(), Err(PersistentRecvError::Empty));
+        fs::remove_file(&path).unwrap();
+    }
+}
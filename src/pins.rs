@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Item published with `Publisher::broadcast_pinned`.
+#[derive(Debug)]
+struct Pinned<I> {
+    /// Position of the item in the buffer
+    seq: usize,
+    /// Sequence handed out along with the item, see `Subscriber::try_recv_sequenced`
+    sequence: u64,
+    item: I,
+    /// Subscribers which have not received the item yet
+    remaining: usize,
+}
+
+/// Pinned items kept past their slot being overwritten, so that lapped subscribers
+/// still receive them, until `pin_for` more items are published or every subscriber
+/// there was when they were published received them.
+#[derive(Debug)]
+pub(crate) struct Pins<I> {
+    pin_for: usize,
+    pinned: Mutex<VecDeque<Pinned<I>>>,
+    /// Number of pinned items, saves locking when there are none
+    count: AtomicUsize,
+}
+
+impl<I: Clone> Pins<I> {
+    pub(crate) fn new(pin_for: usize) -> Self {
+        Self {
+            pin_for,
+            pinned: Mutex::new(VecDeque::new()),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn set_pin_for(&mut self, pin_for: usize) {
+        self.pin_for = pin_for;
+    }
+
+    /// Pins the item at `seq` for the `subscribers` there are.
+    pub(crate) fn pin(&self, seq: usize, sequence: u64, item: I, subscribers: usize) {
+        let mut pinned = self.pinned.lock().unwrap();
+        pinned.push_back(Pinned {
+            seq,
+            sequence,
+            item,
+            remaining: subscribers,
+        });
+        self.count.store(pinned.len(), Ordering::Release);
+    }
+
+    /// Unpins the items published more than `pin_for` items before `wi`.
+    pub(crate) fn expire(&self, wi: usize) {
+        if self.count.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        let mut pinned = self.pinned.lock().unwrap();
+        pinned.retain(|pin| wi.wrapping_sub(pin.seq) <= self.pin_for);
+        self.count.store(pinned.len(), Ordering::Release);
+    }
+
+    /// Takes the oldest pinned item at or after `ri` and before `oldest`, the oldest item
+    /// still retained in the buffer, on behalf of a subscriber lapped from `ri`.
+    pub(crate) fn take_lapped(&self, ri: usize, oldest: usize) -> Option<(usize, u64, I)> {
+        if self.count.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        let mut pinned = self.pinned.lock().unwrap();
+        let index = pinned
+            .iter()
+            .position(|pin| pin.seq.wrapping_sub(ri) < oldest.wrapping_sub(ri))?;
+        let pin = &pinned[index];
+        let taken = (pin.seq, pin.sequence, pin.item.clone());
+        self.received(&mut pinned, index);
+        Some(taken)
+    }
+
+    /// Records that a subscriber received the item at `seq` from the buffer, if pinned.
+    pub(crate) fn mark_received(&self, seq: usize) {
+        if self.count.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        let mut pinned = self.pinned.lock().unwrap();
+        if let Some(index) = pinned.iter().position(|pin| pin.seq == seq) {
+            self.received(&mut pinned, index);
+        }
+    }
+
+    /// Unpins the item at `index` once every subscriber received it.
+    fn received(&self, pinned: &mut VecDeque<Pinned<I>>, index: usize) {
+        let pin = &mut pinned[index];
+        pin.remaining = pin.remaining.saturating_sub(1);
+        if pin.remaining == 0 {
+            pinned.remove(index);
+            self.count.store(pinned.len(), Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Pins;
+
+    #[test]
+    fn pins_expire() {
+        let pins = Pins::new(4);
+        pins.pin(2, 2, "pinned", 2);
+        assert_eq!(pins.take_lapped(0, 2), None);
+        assert_eq!(pins.take_lapped(0, 3), Some((2, 2, "pinned")));
+        pins.expire(6);
+        assert_eq!(pins.take_lapped(0, 3), Some((2, 2, "pinned")));
+        // Both subscribers received it.
+        assert_eq!(pins.take_lapped(0, 3), None);
+
+        pins.pin(8, 8, "pinned", 2);
+        pins.expire(13);
+        assert_eq!(pins.take_lapped(0, 13), None);
+    }
+}
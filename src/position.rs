@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Position of a subscriber in its bus, see `Subscriber::save_position`.
+///
+/// Besides serde (behind the `serde` feature), positions have a stable 16 byte
+/// wire format: the bus id followed by the sequence, both little endian.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Id of the bus the position belongs to
+    pub bus_id: u64,
+    /// Sequence of the next item to receive
+    pub sequence: u64,
+}
+
+impl Position {
+    /// Encodes the position into its wire format.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.bus_id.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a position from its wire format.
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        let mut bus_id = [0; 8];
+        let mut sequence = [0; 8];
+        bus_id.copy_from_slice(&bytes[..8]);
+        sequence.copy_from_slice(&bytes[8..]);
+        Self {
+            bus_id: u64::from_le_bytes(bus_id),
+            sequence: u64::from_le_bytes(sequence),
+        }
+    }
+}
+
+/// Error returned by `Subscriber::restore`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RestoreError {
+    /// The position was saved from another bus, the subscriber was not moved.
+    OtherBus,
+    /// Some items after the position are no longer retained,
+    /// the subscriber was moved to the oldest retained item.
+    NotRetained,
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestoreError::OtherBus => write!(f, "position of another bus"),
+            RestoreError::NotRetained => write!(f, "position no longer retained"),
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {}
@@ -0,0 +1,280 @@
+#[cfg(feature = "async")]
+use crate::async_subscriber::AsyncSubscriber;
+#[cfg(feature = "blocking")]
+use crate::ring_buffer::RecvError;
+use crate::ring_buffer::TryRecvError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+#[cfg(feature = "async")]
+use futures_core::{
+    task::{self, Poll},
+    Stream,
+};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Item received through a projection, dereferencing to the projected part of the item,
+/// e.g. the payload of an enum variant.
+pub struct Projected<P, U, F> {
+    item: P,
+    project: Arc<F>,
+    part: PhantomData<fn() -> U>,
+}
+
+impl<P, U, F> Projected<P, U, F> {
+    /// Returns the whole item.
+    pub fn into_item(self) -> P {
+        self.item
+    }
+}
+
+impl<P, U, F> Deref for Projected<P, U, F>
+where
+    P: Deref,
+    F: Fn(&P::Target) -> Option<&U>,
+{
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        (self.project)(&self.item).expect("received items are projected")
+    }
+}
+
+impl<P: Clone, U, F> Clone for Projected<P, U, F> {
+    fn clone(&self) -> Self {
+        Self {
+            item: self.item.clone(),
+            project: self.project.clone(),
+            part: PhantomData,
+        }
+    }
+}
+
+impl<P, U: fmt::Debug, F> fmt::Debug for Projected<P, U, F>
+where
+    P: Deref,
+    F: Fn(&P::Target) -> Option<&U>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Receiver of the items a projection applies to, returned by `Subscriber::project`.
+///
+/// Several logical channels can share one bus by broadcasting an enum, each subscriber
+/// projecting the variant it is interested in and passing over the others.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+///
+/// enum Message {
+///     Control(&'static str),
+///     Tick(u64),
+/// }
+///
+/// let (tx, rx) = bounded(10);
+/// let ticks = rx.project(|message: &Message| match message {
+///     Message::Tick(tick) => Some(tick),
+///     _ => None,
+/// });
+/// tx.broadcast(Message::Tick(1)).unwrap();
+/// tx.broadcast(Message::Control("pause")).unwrap();
+/// tx.broadcast(Message::Tick(2)).unwrap();
+///
+/// let received: Vec<u64> = ticks.map(|tick| *tick).collect();
+/// assert_eq!(received, vec![1, 2]);
+/// ```
+pub struct Projection<T, S: SwapSlot<T>, U, F> {
+    subscriber: Subscriber<T, S>,
+    project: Arc<F>,
+    part: PhantomData<fn() -> U>,
+}
+
+impl<T, S: SwapSlot<T>> Subscriber<T, S> {
+    /// Turns the receiver into one receiving the part of the items the function
+    /// returns, passing over the items it returns None for, see `Projection`.
+    pub fn project<U, F>(self, project: F) -> Projection<T, S, U, F>
+    where
+        F: Fn(&T) -> Option<&U>,
+    {
+        Projection {
+            subscriber: self,
+            project: Arc::new(project),
+            part: PhantomData,
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>, U, F> Projection<T, S, U, F>
+where
+    F: Fn(&T) -> Option<&U>,
+{
+    /// Receives the next item the projection applies to, see `Subscriber::try_recv`.
+    pub fn try_recv(&self) -> Result<Projected<S::Shared, U, F>, TryRecvError> {
+        loop {
+            if let Some(projected) = self.projected(self.subscriber.try_recv()?) {
+                return Ok(projected);
+            }
+        }
+    }
+
+    /// Receives the next item the projection applies to, blocking until there is one,
+    /// see `Subscriber::recv`.
+    #[cfg(feature = "blocking")]
+    pub fn recv(&self) -> Result<Projected<S::Shared, U, F>, RecvError> {
+        loop {
+            if let Some(projected) = self.projected(self.subscriber.recv()?) {
+                return Ok(projected);
+            }
+        }
+    }
+
+    /// Returns the underlying receiver.
+    pub fn into_inner(self) -> Subscriber<T, S> {
+        self.subscriber
+    }
+
+    fn projected(&self, item: S::Shared) -> Option<Projected<S::Shared, U, F>> {
+        (self.project)(&item)?;
+        Some(Projected {
+            item,
+            project: self.project.clone(),
+            part: PhantomData,
+        })
+    }
+}
+
+impl<T, S: SwapSlot<T>, U, F> Iterator for Projection<T, S, U, F>
+where
+    F: Fn(&T) -> Option<&U>,
+{
+    type Item = Projected<S::Shared, U, F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_recv().ok()
+    }
+}
+
+impl<T, S: SwapSlot<T>, U, F> fmt::Debug for Projection<T, S, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Projection")
+            .field("subscriber", &self.subscriber)
+            .finish()
+    }
+}
+
+/// Stream of the items a projection applies to, returned by `AsyncSubscriber::project`.
+#[cfg(feature = "async")]
+pub struct AsyncProjection<T, S: SwapSlot<T>, U, F> {
+    subscriber: AsyncSubscriber<T, S>,
+    project: Arc<F>,
+    part: PhantomData<fn() -> U>,
+}
+
+#[cfg(feature = "async")]
+impl<T, S: SwapSlot<T>> AsyncSubscriber<T, S> {
+    /// Turns the stream into one yielding the part of the items the function returns,
+    /// passing over the items it returns None for, see `Subscriber::project`.
+    pub fn project<U, F>(self, project: F) -> AsyncProjection<T, S, U, F>
+    where
+        F: Fn(&T) -> Option<&U>,
+    {
+        AsyncProjection {
+            subscriber: self,
+            project: Arc::new(project),
+            part: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, S: SwapSlot<T>, U, F> AsyncProjection<T, S, U, F> {
+    /// Returns the underlying subscriber.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.subscriber
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, S: SwapSlot<T>, U, F> Unpin for AsyncProjection<T, S, U, F> {}
+
+#[cfg(feature = "async")]
+impl<T, S: SwapSlot<T>, U, F> Stream for AsyncProjection<T, S, U, F>
+where
+    F: Fn(&T) -> Option<&U>,
+{
+    type Item = Projected<S::Shared, U, F>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let item = match futures_core::ready!(Pin::new(&mut self.subscriber).poll_next(cx)) {
+                Some(item) => item,
+                None => return Poll::Ready(None),
+            };
+            if (self.project)(&item).is_some() {
+                return Poll::Ready(Some(Projected {
+                    item,
+                    project: self.project.clone(),
+                    part: PhantomData,
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, S: SwapSlot<T>, U, F> fmt::Debug for AsyncProjection<T, S, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncProjection").finish()
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod test {
+    use crate::flavors::arc_swap::async_bounded;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+
+    #[derive(Debug)]
+    enum Message {
+        Control(bool),
+        Data(String),
+    }
+
+    fn data(message: &Message) -> Option<&String> {
+        match message {
+            Message::Data(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn streams_pass_over_other_variants() {
+        let (mut publisher, subscriber) = async_bounded(10);
+        let control = subscriber
+            .clone()
+            .project(|message: &Message| match message {
+                Message::Control(on) => Some(on),
+                _ => None,
+            });
+        let data = subscriber.project(data);
+        block_on(async {
+            publisher.send(Message::Control(true)).await.unwrap();
+            publisher.send(Message::Data("a".into())).await.unwrap();
+            publisher.send(Message::Control(false)).await.unwrap();
+        });
+        drop(publisher);
+
+        let data: Vec<_> = block_on(data.collect());
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].as_str(), "a");
+        assert!(matches!(*data[0].clone().into_item(), Message::Data(_)));
+        let control: Vec<bool> = block_on(control.map(|on| *on).collect());
+        assert_eq!(control, vec![true, false]);
+    }
+}
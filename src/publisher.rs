@@ -1,11 +1,54 @@
-use crate::ring_buffer::{RingBuffer, SendError};
+use crate::fence::FenceToken;
+use crate::quota::Quota;
+use crate::ring_buffer::{ChannelState, RingBuffer, SendError};
+use crate::subscriber::Subscriber;
 use crate::swap_slot::SwapSlot;
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "blocking")]
+use std::time::Instant;
+
+/// Function run on every published object before it is stored,
+/// returning None drops the object.
+pub type Middleware<T> = Box<dyn Fn(T) -> Option<T> + Send + Sync>;
+
+/// Item of a bus with an error lane, carrying either data or an upstream error,
+/// see `Publisher::broadcast_err`.
+pub type Fallible<T, E> = Result<Arc<T>, Arc<E>>;
+
+/// What broadcasting does while the publisher is paused, see `Publisher::pause`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PausePolicy {
+    /// Broadcasts fail, returning the objects.
+    Reject,
+    /// Broadcasts succeed, only the latest object is kept and published on resume.
+    KeepLatest,
+}
+
+/// State of a paused publisher.
+struct Pause<T> {
+    policy: PausePolicy,
+    /// Latest object broadcast while paused with `PausePolicy::KeepLatest`
+    latest: Option<T>,
+}
 
-#[derive(Debug)]
 pub struct Publisher<T, S: SwapSlot<T>> {
     /// Shared reference to the channel
     pub(super) buffer: Arc<RingBuffer<T, S>>,
+    /// Middleware run in order on every published object
+    pub(super) middleware: Vec<Middleware<T>>,
+    /// Called with the number of subscribers reaped while broadcasting
+    pub(super) on_reaped: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    /// true while paused, saves locking `pause` on every broadcast
+    paused: AtomicBool,
+    pause: Mutex<Option<Pause<T>>>,
+    /// Items per second this publisher may broadcast, if limited
+    quota: Option<Quota>,
+    /// Origin the published items are tagged with, see `set_origin`
+    origin: usize,
 }
 
 impl<T, S: SwapSlot<T>> Publisher<T, S> {
@@ -14,7 +57,290 @@ impl<T, S: SwapSlot<T>> Publisher<T, S> {
     /// # Arguments
     /// * `object` - owned object to be published
     pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
-        self.buffer.broadcast(object)
+        self.try_broadcast(object)
+            .map_err(|error| SendError(error.into_inner()))
+    }
+
+    /// Publishes the object like `broadcast`, telling a broadcast over the quota set with
+    /// `Builder::write_quota` apart from one the bus refused.
+    pub fn try_broadcast(&self, object: T) -> Result<(), TryBroadcastError<T>> {
+        self.publish(object, |object| {
+            self.buffer.broadcast_from(object, self.origin)
+        })
+    }
+
+    /// Publishes the object returned by `f`, only calling it if there are subscribers to
+    /// publish to, saving the cost of building items nobody would see. Returns false if
+    /// it was not called, instead of failing like `broadcast` without subscribers.
+    pub fn broadcast_lazy<F: FnOnce() -> T>(&self, f: F) -> Result<bool, SendError<T>> {
+        if !self.buffer.accepts_broadcasts() {
+            return Ok(false);
+        }
+        self.broadcast(f()).map(|()| true)
+    }
+
+    /// Publishes the object like `broadcast`, pinning it for rare critical items, e.g. a
+    /// session reset, not to be lost among many others: subscribers lapped before receiving
+    /// it still receive it, until `Builder::pin_for` more items are published or every
+    /// subscriber there was when it was published received it.
+    pub fn broadcast_pinned(&self, object: T) -> Result<(), SendError<T>> {
+        self.publish(object, |object| {
+            self.buffer.broadcast_pinned(object, self.origin)
+        })
+        .map_err(|error| SendError(error.into_inner()))
+    }
+
+    /// Publishes the object like `broadcast` along with metadata, e.g. a trace id,
+    /// received with `Subscriber::try_recv_with_meta`, saving wrapping every item type.
+    ///
+    /// The metadata is dropped unless the bus was built with `Builder::metadata::<M>`,
+    /// or if the object is kept while paused.
+    pub fn broadcast_with_meta<M: Copy + Send + 'static>(
+        &self,
+        object: T,
+        meta: M,
+    ) -> Result<(), SendError<T>> {
+        self.publish(object, |object| {
+            self.buffer.broadcast_with_meta(object, self.origin, meta)
+        })
+        .map_err(|error| SendError(error.into_inner()))
+    }
+
+    /// Publishes the object through `send` unless the middleware, the pause
+    /// or the quota keep it from being published.
+    fn publish<F>(&self, object: T, send: F) -> Result<(), TryBroadcastError<T>>
+    where
+        F: FnOnce(T) -> Result<(), SendError<T>>,
+    {
+        let object = match self.run_middleware(object) {
+            Some(object) => object,
+            // Dropped by the middleware, there is nothing to publish.
+            None => return Ok(()),
+        };
+        if self.paused.load(Ordering::Acquire) {
+            if let Some(pause) = self.pause.lock().unwrap().as_mut() {
+                return match pause.policy {
+                    PausePolicy::Reject => Err(TryBroadcastError::Rejected(object)),
+                    PausePolicy::KeepLatest => {
+                        pause.latest = Some(object);
+                        Ok(())
+                    }
+                };
+            }
+        }
+        if let Some(retry_after) = self.quota_backoff() {
+            return Err(TryBroadcastError::QuotaExceeded {
+                object,
+                retry_after,
+            });
+        }
+        self.reap_if_due();
+        send(object)?;
+        self.charge_quota(1);
+        Ok(())
+    }
+
+    /// Publishes all of the values as a group, subscribers never see only some of them.
+    ///
+    /// Fails, returning every value, if they would not all fit in the queue at once.
+    /// Values dropped by the middleware are left out of the group.
+    pub fn broadcast_all_or_nothing(
+        &self,
+        objects: Vec<T>,
+    ) -> Result<(), TryBroadcastError<Vec<T>>> {
+        if self.paused.load(Ordering::Acquire) {
+            if let Some(pause) = self.pause.lock().unwrap().as_mut() {
+                if pause.policy == PausePolicy::Reject {
+                    return Err(TryBroadcastError::Rejected(objects));
+                }
+            }
+        }
+        let mut objects: Vec<T> = objects
+            .into_iter()
+            .filter_map(|object| self.run_middleware(object))
+            .collect();
+        if self.paused.load(Ordering::Acquire) {
+            if let Some(pause) = self.pause.lock().unwrap().as_mut() {
+                // Only the latest object of the group is kept.
+                if let Some(object) = objects.pop() {
+                    pause.latest = Some(object);
+                }
+                return Ok(());
+            }
+        }
+        if let Some(retry_after) = self.quota_backoff() {
+            return Err(TryBroadcastError::QuotaExceeded {
+                object: objects,
+                retry_after,
+            });
+        }
+        let len = objects.len();
+        self.reap_if_due();
+        self.buffer.broadcast_all_from(objects, self.origin)?;
+        self.charge_quota(len);
+        Ok(())
+    }
+
+    /// Pauses publishing, e.g. while the upstream source reconnects, broadcasts being
+    /// handled according to the policy until `resume` is called.
+    ///
+    /// Pausing again only changes the policy.
+    pub fn pause(&self, policy: PausePolicy) {
+        let mut pause = self.pause.lock().unwrap();
+        match pause.as_mut() {
+            Some(pause) => pause.policy = policy,
+            None => {
+                *pause = Some(Pause {
+                    policy,
+                    latest: None,
+                })
+            }
+        }
+        self.paused.store(true, Ordering::Release);
+    }
+
+    /// Resumes publishing, publishing the latest object kept while paused, if any.
+    pub fn resume(&self) -> Result<(), SendError<T>> {
+        let pause = self.pause.lock().unwrap().take();
+        self.paused.store(false, Ordering::Release);
+        match pause.and_then(|pause| pause.latest) {
+            Some(latest) => {
+                self.reap_if_due();
+                self.buffer.broadcast_from(latest, self.origin)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Tags the items broadcast from then on with the origin `id`, so that subscribers
+    /// can skip them with `Subscriber::exclude_origin`. The default origin is 0.
+    ///
+    /// Items are only tagged if the bus was built with `Builder::origin_tags`.
+    pub fn set_origin(&mut self, id: usize) {
+        self.origin = id;
+    }
+
+    /// Returns the origin the broadcast items are tagged with.
+    pub fn origin(&self) -> usize {
+        self.origin
+    }
+
+    /// Returns how long to wait before broadcasting fits the quota set with
+    /// `Builder::write_quota`, None if it does. Broadcasts over the quota fail,
+    /// with `TryBroadcastError::QuotaExceeded` from `try_broadcast`.
+    pub fn quota_backoff(&self) -> Option<Duration> {
+        self.quota.as_ref().and_then(Quota::backoff)
+    }
+
+    /// Takes `n` published items off the quota, if limited.
+    pub(crate) fn charge_quota(&self, n: usize) {
+        if let Some(quota) = &self.quota {
+            quota.charge(n);
+        }
+    }
+
+    /// Checks if publishing is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Blocks until `n` subscribers have polled the bus at least once, e.g. with
+    /// `Subscriber::try_recv`, so that the first items are not broadcast before
+    /// the subscribers are attached and lost to a small bus.
+    ///
+    /// Fails with the number of ready subscribers once the timeout runs out.
+    /// Dropped subscribers are no longer counted.
+    ///
+    /// ```rust
+    /// use bus_queue::flavors::arc_swap::bounded;
+    /// use std::time::Duration;
+    ///
+    /// let (tx, rx) = bounded(1);
+    /// let consumer = std::thread::spawn(move || rx.recv().map(|item| *item));
+    /// tx.start_barrier(1, Duration::from_secs(10)).unwrap();
+    /// tx.broadcast(1).unwrap();
+    /// assert_eq!(consumer.join().unwrap(), Ok(1));
+    /// ```
+    #[cfg(feature = "blocking")]
+    pub fn start_barrier(&self, n: usize, timeout: Duration) -> Result<(), StartBarrierError> {
+        // A timeout too large to be represented means waiting without deadline.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if self.buffer.ready_count() >= n {
+                return Ok(());
+            }
+            let listener = self.buffer.listen_ready();
+            if self.buffer.ready_count() >= n {
+                return Ok(());
+            }
+            let timed_out = match deadline {
+                Some(deadline) => !listener.wait_deadline(deadline),
+                None => {
+                    listener.wait();
+                    false
+                }
+            };
+            if timed_out {
+                let ready = self.buffer.ready_count();
+                if ready >= n {
+                    return Ok(());
+                }
+                return Err(StartBarrierError { ready, expected: n });
+            }
+        }
+    }
+
+    /// Marks the subscribers which have not received for the timeout set with
+    /// `Builder::reap_inactive` as stale, returning how many were marked.
+    ///
+    /// Stale subscribers are not counted by `sub_count` until they receive again.
+    /// Broadcasting reaps on its own once per timeout.
+    pub fn reap_inactive(&self) -> usize {
+        let reaped = self.buffer.reap_inactive();
+        if reaped > 0 {
+            if let Some(on_reaped) = &self.on_reaped {
+                on_reaped(reaped);
+            }
+        }
+        reaped
+    }
+
+    /// Sets a function called with the number of subscribers marked as stale whenever
+    /// some are, see `reap_inactive`.
+    pub fn on_reaped<F>(&mut self, on_reaped: F)
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.on_reaped = Some(Box::new(on_reaped));
+    }
+
+    /// Runs the middleware in order on the object, None if one of them dropped it.
+    pub(crate) fn run_middleware(&self, object: T) -> Option<T> {
+        self.middleware
+            .iter()
+            .try_fold(object, |object, middleware| middleware(object))
+    }
+
+    pub(crate) fn reap_if_due(&self) {
+        if self.buffer.is_reap_due() {
+            self.reap_inactive();
+        }
+    }
+
+    /// Adds a middleware run on every published object before it is stored,
+    /// after the previously added ones. The middleware can modify the object,
+    /// or drop it by returning None.
+    pub fn add_middleware<F>(&mut self, middleware: F)
+    where
+        F: Fn(T) -> Option<T> + Send + Sync + 'static,
+    {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    /// Returns a suggested size for the bus, see `RingBuffer::capacity_advice`.
+    /// None unless the bus was built with `Builder::track_capacity`.
+    pub fn capacity_advice(&self) -> Option<usize> {
+        self.buffer.capacity_advice()
     }
 
     /// Returns the length of the queue
@@ -27,20 +353,180 @@ impl<T, S: SwapSlot<T>> Publisher<T, S> {
         self.buffer.is_empty()
     }
 
-    /// Closes the Sender
+    /// Creates a new Receiver which only receives the items published from now on.
+    pub fn subscribe(&self) -> Subscriber<T, S> {
+        let subscriber = Subscriber::from(self.buffer.clone());
+        self.buffer.inc_sub_count();
+        subscriber.ri.set(self.buffer.write_index());
+        subscriber.sync_cursor();
+        subscriber
+    }
+
+    /// Creates a new Receiver positioned at the given sequence, the number of items
+    /// published before it. Sequences outside the retained items are clamped into them.
+    pub fn subscribe_at(&self, seq: usize) -> Subscriber<T, S> {
+        let subscriber = Subscriber::from(self.buffer.clone());
+        self.buffer.inc_sub_count();
+        subscriber.ri.set(self.buffer.clamp_retained(seq));
+        subscriber.sync_cursor();
+        subscriber
+    }
+
+    /// Returns the id of the bus, shared by all of its handles: random unless set with
+    /// `Builder::bus_id`.
+    pub fn bus_id(&self) -> u64 {
+        self.buffer.id()
+    }
+
+    /// Returns a token subscribers get past once they received every item published
+    /// so far, see `Subscriber::wait_for_token`.
+    pub fn fence_token(&self) -> FenceToken {
+        FenceToken {
+            bus_id: self.bus_id(),
+            sequence: self.buffer.write_index(),
+        }
+    }
+
+    /// Returns the number of Receivers
+    pub fn sub_count(&self) -> usize {
+        self.buffer.sub_count()
+    }
+
+    /// Closes the Sender, closing it again does nothing.
     pub fn close(&self) {
         self.buffer.close()
     }
+
+    /// Returns the state of the bus, `Closing` once closed until every subscriber is gone,
+    /// see `RingBuffer::state`.
+    pub fn state(&self) -> ChannelState {
+        self.buffer.state()
+    }
+
+    /// Checks if the bus's flavor is lock free, see `SwapSlot::IS_LOCK_FREE`.
+    pub fn is_lock_free(&self) -> bool {
+        S::IS_LOCK_FREE
+    }
+}
+
+impl<T: Clone, S: SwapSlot<T>> Publisher<T, S> {
+    /// Publishes a value which might be borrowed, only cloning it if it is.
+    pub fn broadcast_cow(&self, object: Cow<'_, T>) -> Result<(), SendError<T>> {
+        self.broadcast(object.into_owned())
+    }
 }
 
+impl<T, E, S: SwapSlot<Fallible<T, E>>> Publisher<Fallible<T, E>, S> {
+    /// Publishes a data item on a bus with an error lane.
+    pub fn broadcast_ok(&self, object: T) -> Result<(), SendError<Fallible<T, E>>> {
+        self.broadcast(Ok(Arc::new(object)))
+    }
+
+    /// Publishes an upstream error in order with the data, e.g. a parse error or
+    /// a feed disconnect, instead of reporting it through a side channel.
+    pub fn broadcast_err(&self, error: E) -> Result<(), SendError<Fallible<T, E>>> {
+        self.broadcast(Err(Arc::new(error)))
+    }
+}
+
+impl<T, S: SwapSlot<T, Shared = Arc<T>>> Publisher<T, S> {
+    /// Returns the strong count of each retained item, see `RingBuffer::slot_refcounts`.
+    pub fn slot_refcounts(&self) -> Vec<usize> {
+        self.buffer.slot_refcounts()
+    }
+}
+
+/// Error returned by `Publisher::start_barrier` when the subscribers were not ready in time.
+#[cfg(feature = "blocking")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartBarrierError {
+    /// Number of subscribers which polled the bus
+    pub ready: usize,
+    pub expected: usize,
+}
+
+#[cfg(feature = "blocking")]
+impl fmt::Display for StartBarrierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timed out with {} of {} subscribers ready",
+            self.ready, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl std::error::Error for StartBarrierError {}
+
+/// Error returned by `Publisher::try_broadcast` and `broadcast_all_or_nothing`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryBroadcastError<T> {
+    /// The bus refused the object for any of the reasons `broadcast` fails,
+    /// e.g. it has no subscribers or the publisher is paused.
+    Rejected(T),
+    /// The publisher is over the quota set with `Builder::write_quota`,
+    /// broadcasting fits it again after `retry_after`.
+    QuotaExceeded { object: T, retry_after: Duration },
+}
+
+impl<T> TryBroadcastError<T> {
+    /// Returns the object that was not published.
+    pub fn into_inner(self) -> T {
+        match self {
+            TryBroadcastError::Rejected(object)
+            | TryBroadcastError::QuotaExceeded { object, .. } => object,
+        }
+    }
+}
+
+impl<T> From<SendError<T>> for TryBroadcastError<T> {
+    fn from(error: SendError<T>) -> Self {
+        TryBroadcastError::Rejected(error.0)
+    }
+}
+
+impl<T> fmt::Display for TryBroadcastError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryBroadcastError::Rejected(_) => write!(f, "broadcast rejected"),
+            TryBroadcastError::QuotaExceeded { retry_after, .. } => {
+                write!(f, "write quota exceeded, retry after {:?}", retry_after)
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for TryBroadcastError<T> {}
+
 impl<T, S: SwapSlot<T>> From<Arc<RingBuffer<T, S>>> for Publisher<T, S> {
     fn from(arc_channel: Arc<RingBuffer<T, S>>) -> Self {
         Self {
+            quota: arc_channel.write_quota(),
             buffer: arc_channel,
+            middleware: Vec::new(),
+            on_reaped: None,
+            paused: AtomicBool::new(false),
+            pause: Mutex::new(None),
+            origin: 0,
         }
     }
 }
 
+impl<T, S: SwapSlot<T>> fmt::Debug for Publisher<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Publisher")
+            .field("bus_id", &self.bus_id())
+            .field("wi", &self.buffer.write_index())
+            .field("size", &self.buffer.len())
+            .field("sub_count", &self.buffer.sub_count())
+            .field("closed", &!self.buffer.is_available())
+            .field("middleware", &self.middleware.len())
+            .field("paused", &self.is_paused())
+            .finish()
+    }
+}
+
 /// Drop trait is used to let subscribers know that publisher is no longer available.
 impl<T, S: SwapSlot<T>> Drop for Publisher<T, S> {
     fn drop(&mut self) {
@@ -0,0 +1,77 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting a handle to a number of items per second,
+/// allowing bursts of up to a second's worth of items.
+#[derive(Debug)]
+pub(crate) struct Quota {
+    per_second: u32,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Items that can be handled right now, negative once overdrawn
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Quota {
+    pub(crate) fn new(per_second: u32) -> Self {
+        let per_second = per_second.max(1);
+        Self {
+            per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(per_second),
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Returns how long to wait before the next item fits the quota, None if it already does.
+    pub(crate) fn backoff(&self) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        if bucket.tokens >= 1.0 {
+            None
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Some(Duration::from_secs_f64(
+                missing / f64::from(self.per_second),
+            ))
+        }
+    }
+
+    /// Takes `n` items off the quota, overdrawing it if need be so that a batch
+    /// exceeding it is paid for with a longer backoff.
+    pub(crate) fn charge(&self, n: usize) {
+        if n > 0 {
+            self.bucket.lock().unwrap().tokens -= n as f64;
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        let capacity = f64::from(self.per_second);
+        bucket.tokens = (bucket.tokens + elapsed * capacity).min(capacity);
+        bucket.refilled = now;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Quota;
+
+    #[test]
+    fn quota_allows_bursts_then_backs_off() {
+        let quota = Quota::new(2);
+        assert_eq!(quota.backoff(), None);
+        quota.charge(1);
+        assert_eq!(quota.backoff(), None);
+        quota.charge(2);
+        // Overdrawn by one item, two more are needed at two per second.
+        let backoff = quota.backoff().unwrap();
+        assert!(backoff.as_millis() > 900 && backoff.as_millis() <= 1000);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::atomic_counter::AtomicCounter;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Activity of a subscriber, tracked by the reaper.
+#[derive(Debug)]
+pub(crate) struct Activity {
+    /// Nanoseconds since the reaper's base the subscriber last received at
+    last_active: AtomicU64,
+    /// true if the subscriber is excluded from the subscriber count
+    stale: AtomicBool,
+}
+
+impl Activity {
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(Ordering::Acquire)
+    }
+}
+
+/// Marks subscribers that have not received for a while as stale, enabled
+/// through `Builder::reap_inactive`. Stale subscribers are excluded from the
+/// subscriber count until they receive again.
+#[derive(Debug)]
+pub(crate) struct Reaper {
+    timeout: Duration,
+    base: Instant,
+    /// Nanoseconds since base of the last reaping
+    last_reap: AtomicU64,
+    subscribers: Mutex<Vec<Weak<Activity>>>,
+}
+
+impl Reaper {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            base: Instant::now(),
+            last_reap: AtomicU64::new(0),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.base.elapsed().as_nanos() as u64
+    }
+
+    /// Starts tracking a new subscriber.
+    pub fn register(&self) -> Arc<Activity> {
+        let activity = Arc::new(Activity {
+            last_active: AtomicU64::new(self.now()),
+            stale: AtomicBool::new(false),
+        });
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| subscriber.strong_count() > 0);
+        subscribers.push(Arc::downgrade(&activity));
+        activity
+    }
+
+    /// Records that the subscriber received, counting it again if it was stale.
+    pub fn touch(&self, activity: &Activity, sub_count: &AtomicCounter) {
+        activity.last_active.store(self.now(), Ordering::Release);
+        if activity.is_stale()
+            && activity
+                .stale
+                .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            sub_count.inc();
+        }
+    }
+
+    /// Stops tracking a dropped subscriber, uncounting it unless it is stale.
+    pub fn unregister(&self, activity: &Activity, sub_count: &AtomicCounter) {
+        if !activity.stale.swap(true, Ordering::AcqRel) {
+            sub_count.dec();
+        }
+    }
+
+    /// Checks if a timeout has passed since the last reaping.
+    pub fn is_due(&self) -> bool {
+        let last_reap = self.last_reap.load(Ordering::Acquire);
+        self.now().saturating_sub(last_reap) >= self.timeout.as_nanos() as u64
+    }
+
+    /// Marks the subscribers inactive for longer than the timeout as stale,
+    /// returning how many were marked.
+    pub fn reap(&self, sub_count: &AtomicCounter) -> usize {
+        let now = self.now();
+        self.last_reap.store(now, Ordering::Release);
+        let timeout = self.timeout.as_nanos() as u64;
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| subscriber.strong_count() > 0);
+        let mut reaped = 0;
+        for activity in subscribers.iter().filter_map(Weak::upgrade) {
+            let last_active = activity.last_active.load(Ordering::Acquire);
+            if now.saturating_sub(last_active) > timeout
+                && activity
+                    .stale
+                    .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+            {
+                sub_count.dec();
+                reaped += 1;
+            }
+        }
+        reaped
+    }
+}
@@ -1,414 +1,2906 @@
-use crate::atomic_counter::AtomicCounter;
-use std::sync::{atomic::AtomicBool, atomic::Ordering, Arc};
+use crate::advisor::Advisor;
+use crate::atomic_counter::{AtomicCounter, SequenceCounter};
+use crate::codec::{Codec, Decoded};
+use crate::event::Event;
+#[cfg(feature = "blocking")]
+use crate::event::EventListener;
+#[cfg(feature = "fault-injection")]
+use crate::fault_injection::Faults;
+use crate::metadata::{Metadata, NewSidecar, Sidecar};
+use crate::overflow::{Cursors, Overflow, OverflowPolicy};
+use crate::pins::Pins;
+use crate::quota::Quota;
+use crate::reaper::{Activity, Reaper};
+use crate::sequence::SequenceSource;
+use crate::tiered::ColdRing;
+use std::sync::{atomic, atomic::AtomicBool, atomic::Ordering};
 // Use std mpsc's error types as our own
 use crate::swap_slot::SwapSlot;
-use std::fmt::Debug;
-pub use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+#[cfg(feature = "blocking")]
+pub use std::sync::mpsc::RecvTimeoutError;
+pub use std::sync::mpsc::{RecvError, SendError, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Error returned by `RingBuffer::try_from_capacity` for a capacity of 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroCapacityError;
+
+impl fmt::Display for ZeroCapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bus capacity must be greater than 0")
+    }
+}
+
+impl std::error::Error for ZeroCapacityError {}
+
+/// State of a bus, see `Publisher::state` and `Subscriber::state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelState {
+    /// Items can be published.
+    Open,
+    /// Closed to new items, the retained ones are still to be received.
+    Closing,
+    /// Closed, with nothing left to receive.
+    Closed,
+}
+
+/// Publication times of the items, enabled through `Builder::timestamps`.
+#[derive(Debug)]
+pub(crate) struct Timestamps {
+    /// Creation time of the buffer
+    base: Instant,
+    /// Nanoseconds since base each slot's item was published at
+    nanos: Vec<AtomicU64>,
+}
+
+/// Sequences stamped on the items, enabled through `Builder::sequence_source`.
+#[derive(Debug)]
+pub(crate) struct Stamps {
+    source: Arc<dyn SequenceSource>,
+    /// Sequence of each slot's item
+    sequences: Vec<AtomicU64>,
+}
+
+/// Events the async subscribers are spread over, enabled through `Builder::fair_wakeups`.
+#[derive(Debug)]
+pub(crate) struct FairWakeups {
+    lanes: Vec<Event>,
+    /// Lane of the next async subscriber created
+    next_lane: AtomicUsize,
+    /// Lane woken first by the next notification
+    rotation: AtomicUsize,
+}
+
+/// Byte budget of a buffer created through `bounded_bytes`.
+pub(crate) struct ByteBound<T> {
+    /// Maximum number of payload bytes retained at once
+    max_bytes: usize,
+    /// Computes the payload size of an item
+    size_of: Box<dyn Fn(&T) -> usize + Send + Sync>,
+    /// Payload size of the item held in each slot
+    sizes: Vec<AtomicUsize>,
+    /// Sum of the payload sizes of all retained items
+    total: AtomicUsize,
+    /// Index of the oldest item that has not been evicted
+    tail: AtomicCounter,
+}
+
+impl<T> Debug for ByteBound<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteBound")
+            .field("max_bytes", &self.max_bytes)
+            .field("total", &self.total.load(Ordering::Relaxed))
+            .field("tail", &self.tail)
+            .finish()
+    }
+}
 
 #[derive(Debug)]
 pub struct RingBuffer<T, S: SwapSlot<T>> {
+    /// Id of the bus, see `Publisher::bus_id`
+    id: u64,
     /// Circular buffer
     buffer: Vec<S>,
     /// Size of the buffer
     size: usize,
     /// Write index pointer
-    wi: AtomicCounter,
+    wi: SequenceCounter,
+    /// Number of slots past the one at wi being stored by `broadcast_all`
+    reserved: AtomicCounter,
     /// Number of subscribers
     sub_count: AtomicCounter,
     /// true if this sender is still available
     is_available: AtomicBool,
+    /// Optional byte budget evicting the oldest items on broadcast
+    byte_bound: Option<ByteBound<T>>,
+    /// Number of subscribers asking the publisher to slow down
+    slowdown_requests: AtomicCounter,
+    /// Notified when a subscriber stops asking the publisher to slow down
+    slowdown_released: Event,
+    /// Number of threads blocked in recv
+    waiting: AtomicCounter,
+    /// Wakes threads blocked in recv
+    recv_event: Event,
+    /// Notified once, when the buffer is closed
+    close_event: Event,
+    /// Number of async subscribers waiting on their publisher's event
+    listeners: AtomicCounter,
+    /// Number of subscribers which polled at least once, see `Publisher::start_barrier`
+    ready: AtomicCounter,
+    /// Notified when a subscriber polls for the first time
+    ready_event: Event,
+    /// true if broadcasting without subscribers stores the item instead of failing
+    allow_no_subscribers: bool,
+    /// Optional publication times of the items
+    timestamps: Option<Timestamps>,
+    /// Optional reaper of inactive subscribers
+    reaper: Option<Reaper>,
+    /// Optional custom sequences of the items
+    stamps: Option<Stamps>,
+    /// Optional tracker of the lag and publish rate high-water marks
+    advisor: Option<Advisor>,
+    /// true if an async subscriber is marked as critical
+    has_critical: AtomicBool,
+    /// Wakes the critical async subscriber ahead of the others
+    critical_event: Event,
+    /// Optional lanes woken in rotating order
+    fair_wakeups: Option<FairWakeups>,
+    /// Optional transform of the stored items
+    codec: Option<Decoded<T, S>>,
+    /// Items per second each subscriber may receive, if limited
+    read_quota: Option<u32>,
+    /// Items per second each publisher may broadcast, if limited
+    write_quota: Option<u32>,
+    /// Origin of each slot's item, enabled through `Builder::origin_tags`
+    origins: Option<Vec<AtomicUsize>>,
+    /// Items broadcast with `broadcast_pinned` kept past being overwritten
+    pins: Pins<S::Shared>,
+    /// Metadata of each slot's item, enabled through `Builder::metadata`
+    meta: Option<Box<dyn Sidecar>>,
+    /// Longer history retained behind the buffer, set up with `with_cold_tier`
+    cold: Option<ColdRing<S::Shared>>,
+    /// What broadcasting to a full bus does, overwriting the oldest item if None
+    overflow: Option<Box<dyn OverflowPolicy<T>>>,
+    /// Read indexes of the subscribers, tracked along with an overflow policy
+    cursors: Option<Cursors>,
+    /// Optional faults injected at given sequences
+    #[cfg(feature = "fault-injection")]
+    faults: Option<Arc<Faults>>,
     ph: std::marker::PhantomData<T>,
 }
 
+/// Returns a random id for a new bus, so that the ids of the buses of other processes,
+/// e.g. in a position saved before a restart, are unlikely to match either.
+fn new_bus_id() -> u64 {
+    static CREATED: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(CREATED.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+/// What a receive makes of the item it lands on, see `RingBuffer::receive_within`.
+pub(crate) trait Receive<T, S: SwapSlot<T>> {
+    type Output;
+
+    /// Reads the item at `seq` from its slot, None if it was lapped meanwhile.
+    fn read(&mut self, buffer: &RingBuffer<T, S>, seq: usize) -> Option<Self::Output>;
+
+    /// Takes an item kept outside of the slots, pinned or by the cold tier.
+    fn take(&mut self, item: S::Shared) -> Self::Output;
+}
+
+/// Receives the shared pointer of the item.
+pub(crate) struct Shared;
+
+impl<T, S: SwapSlot<T>> Receive<T, S> for Shared {
+    type Output = S::Shared;
+
+    fn read(&mut self, buffer: &RingBuffer<T, S>, seq: usize) -> Option<S::Shared> {
+        let item = buffer.load(seq);
+        if buffer.is_readable(seq) {
+            item
+        } else {
+            None
+        }
+    }
+
+    fn take(&mut self, item: S::Shared) -> S::Shared {
+        item
+    }
+}
+
+/// Runs the function on the item borrowed from its slot, without cloning its
+/// shared pointer. Only receives a single item.
+pub(crate) struct With<F>(pub(crate) Option<F>);
+
+impl<T, S: SwapSlot<T>, R, F: FnOnce(&T) -> R> Receive<T, S> for With<F> {
+    type Output = R;
+
+    fn read(&mut self, buffer: &RingBuffer<T, S>, seq: usize) -> Option<R> {
+        if buffer.codec.is_some() {
+            // Borrowing the slot would hand out the encoded item.
+            let item = buffer.load(seq)?;
+            if buffer.is_readable(seq) {
+                Some(Receive::<T, S>::take(self, item))
+            } else {
+                None
+            }
+        } else {
+            // Only run f once the item is known not to be lapped, as it cannot run twice.
+            let f = &mut self.0;
+            buffer.buffer[buffer.slot(seq)]
+                .load_with(|item| {
+                    if buffer.is_readable(seq) {
+                        f.take().map(|f| f(item))
+                    } else {
+                        None
+                    }
+                })
+                .flatten()
+        }
+    }
+
+    fn take(&mut self, item: S::Shared) -> R {
+        let f = self.0.take().expect("only receives a single item");
+        f(&item)
+    }
+}
+
 impl<T, S: SwapSlot<T>> RingBuffer<T, S> {
+    /// Creates a buffer holding the last `size` items.
+    ///
+    /// A size of 0 holds the latest item only, like a size of 1: every broadcast
+    /// overwrites the item before it. Use `try_from_capacity` to reject it instead.
     pub fn new(size: usize) -> Self {
-        let size = size + 1;
+        let size = size.max(1) + 1;
         let mut buffer = Vec::with_capacity(size);
         for _i in 0..size {
             buffer.push(S::none())
         }
         Self {
+            id: new_bus_id(),
             buffer,
             size,
-            wi: AtomicCounter::new(0),
+            wi: SequenceCounter::new(),
+            reserved: AtomicCounter::new(0),
             sub_count: AtomicCounter::new(1),
             is_available: AtomicBool::new(true),
+            byte_bound: None,
+            slowdown_requests: AtomicCounter::new(0),
+            slowdown_released: Event::new(),
+            waiting: AtomicCounter::new(0),
+            recv_event: Event::new(),
+            close_event: Event::new(),
+            listeners: AtomicCounter::new(0),
+            ready: AtomicCounter::new(0),
+            ready_event: Event::new(),
+            allow_no_subscribers: false,
+            timestamps: None,
+            reaper: None,
+            stamps: None,
+            advisor: None,
+            has_critical: AtomicBool::new(false),
+            critical_event: Event::new(),
+            fair_wakeups: None,
+            codec: None,
+            read_quota: None,
+            write_quota: None,
+            origins: None,
+            pins: Pins::new(size - 1),
+            meta: None,
+            cold: None,
+            overflow: None,
+            cursors: None,
+            #[cfg(feature = "fault-injection")]
+            faults: None,
             ph: std::marker::PhantomData,
         }
     }
-    /// Publishes values to the circular buffer at wi % size
-    ///
-    /// # Arguments
-    /// * `object` - owned object to be published
-    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
-        if self.sub_count.get() == 0 {
-            return Err(SendError(object));
-        }
-        self.buffer[self.wi.get() % self.size].store(object);
-        self.wi.inc();
-        Ok(())
-    }
 
-    /// Receives some atomic reference to an object if queue is not empty, or None if it is. Never
-    /// Blocks
-    pub fn try_recv(&self, ri: &AtomicCounter, skip_items: usize) -> Result<Arc<T>, TryRecvError> {
-        if ri.get() == self.wi.get() {
-            if self.is_available() {
-                return Err(TryRecvError::Empty);
-            } else {
-                return Err(TryRecvError::Disconnected);
-            }
+    /// Creates a buffer holding the last `size` items, failing for a size of 0.
+    pub fn try_from_capacity(size: usize) -> Result<Self, ZeroCapacityError> {
+        if size == 0 {
+            return Err(ZeroCapacityError);
         }
+        Ok(Self::new(size))
+    }
 
-        // Reader has not read enough to keep up with (writer - buffer size) so
-        // set the reader pointer to be (writer - buffer size)
-        loop {
-            let local_ri = ri.get();
-
-            let val = self.buffer[local_ri % self.size].load();
-            if self.wi.get().wrapping_sub(local_ri) >= self.size {
-                ri.set(
-                    self.wi
-                        .get()
-                        .wrapping_sub(self.size)
-                        .wrapping_add(1 + skip_items),
-                );
-            } else {
-                ri.inc();
-                // NOTE: unwrap is safe to use, because the reader would never read a slot that
-                // hasn't been written to.
-                return Ok(val.unwrap());
-            }
+    /// Creates a buffer holding the last `hot` items, backed by a cold tier retaining
+    /// the last `cold` items for the readers lapped on the hot ring or joining late.
+    ///
+    /// Reads hit the small, cache hot ring whenever the reader keeps up with it.
+    pub fn with_cold_tier(hot: usize, cold: usize) -> Self {
+        let mut buffer = Self::new(hot);
+        if cold > hot {
+            buffer.cold = Some(ColdRing::new(cold));
         }
+        buffer
     }
 
-    /// Closes the channel
-    pub fn close(&self) {
-        self.is_available.store(false, Ordering::Relaxed);
+    /// Creates a buffer which, on top of holding at most `size` items,
+    /// evicts the oldest items on broadcast so that the sum of their
+    /// `size_of` stays within `max_bytes`.
+    ///
+    /// An item larger than `max_bytes` evicts everything else and is kept on its own.
+    pub fn with_max_bytes<F>(size: usize, max_bytes: usize, size_of: F) -> Self
+    where
+        F: Fn(&T) -> usize + Send + Sync + 'static,
+    {
+        let mut buffer = Self::new(size);
+        buffer.byte_bound = Some(ByteBound {
+            max_bytes,
+            size_of: Box::new(size_of),
+            sizes: (0..buffer.size).map(|_| AtomicUsize::new(0)).collect(),
+            total: AtomicUsize::new(0),
+            tail: AtomicCounter::new(0),
+        });
+        buffer
     }
-    /// Returns true if the sender is available, otherwise false
-    pub fn is_available(&self) -> bool {
-        self.is_available.load(Ordering::Relaxed)
+
+    /// Makes broadcasting without subscribers store the item, retained for the
+    /// subscribers created later, instead of failing.
+    pub fn set_allow_no_subscribers(&mut self, allow: bool) {
+        self.allow_no_subscribers = allow;
     }
 
-    /// Returns the length of the queue
-    pub fn len(&self) -> usize {
-        self.size - 1
+    /// Makes the buffer record the time every item is published at.
+    pub fn set_timestamps(&mut self, enabled: bool) {
+        self.timestamps = if enabled {
+            Some(Timestamps {
+                base: Instant::now(),
+                nanos: (0..self.size).map(|_| AtomicU64::new(0)).collect(),
+            })
+        } else {
+            None
+        };
     }
 
-    /// Checks if nothings has been published yet
-    pub fn is_empty(&self) -> bool {
-        self.wi.get() == 0
+    /// Makes subscribers which have not received for `timeout` stale, excluding
+    /// them from the subscriber count until they receive again.
+    pub fn set_reap_inactive(&mut self, timeout: Option<Duration>) {
+        self.reaper = timeout.map(Reaper::new);
     }
 
-    /// Checks if subscriber has read all published items
-    pub fn is_sub_empty(&self, ri: usize) -> bool {
-        self.wi.get() == ri
+    /// Makes the buffer stamp every item with a sequence taken from the source.
+    pub fn set_sequence_source(&mut self, source: Option<Arc<dyn SequenceSource>>) {
+        self.stamps = source.map(|source| Stamps {
+            source,
+            sequences: (0..self.size).map(|_| AtomicU64::new(0)).collect(),
+        });
     }
 
-    /// Increment the number of subs
-    pub fn inc_sub_count(&self) {
-        self.sub_count.inc();
+    /// Makes the buffer encode the items it stores and decode the ones it hands out.
+    pub fn set_codec(&mut self, codec: Option<Box<dyn Codec<T>>>) {
+        self.codec = codec.map(|codec| Decoded::new(codec, self.size));
     }
 
-    /// Decrement the number of subs
-    pub fn dec_sub_count(&self) {
-        self.sub_count.dec();
+    /// Makes broadcasting to a full buffer follow the policy instead of always
+    /// overwriting the oldest item, tracking the read index of every subscriber.
+    pub fn set_overflow_policy(&mut self, policy: Option<Box<dyn OverflowPolicy<T>>>) {
+        self.cursors = policy.as_ref().map(|_| Cursors::default());
+        self.overflow = policy;
     }
-}
 
-/// Drop trait is used to let subscribers know that publisher is no longer available.
-impl<T, S: SwapSlot<T>> Drop for RingBuffer<T, S> {
-    fn drop(&mut self) {
-        self.close();
+    /// Sets the id of the bus, random by default.
+    pub fn set_id(&mut self, id: u64) {
+        self.id = id;
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::SwapSlot;
-    use crate::flavors::arc_swap::bounded;
-    use crate::ring_buffer::TryRecvError;
+    /// Returns the id of the bus.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
 
-    #[test]
-    fn subcount() {
-        let (sender, receiver) = bounded::<()>(1);
-        let receiver2 = receiver.clone();
-        assert_eq!(sender.buffer.sub_count.get(), 2);
-        assert_eq!(receiver.buffer.sub_count.get(), 2);
-        assert_eq!(receiver2.buffer.sub_count.get(), 2);
-        drop(receiver2);
+    /// Makes the buffer tag every item with the origin of its publisher.
+    pub fn set_origin_tags(&mut self, enabled: bool) {
+        self.origins = if enabled {
+            Some((0..self.size).map(|_| AtomicUsize::new(0)).collect())
+        } else {
+            None
+        };
+    }
 
-        assert_eq!(sender.buffer.sub_count.get(), 1);
-        assert_eq!(receiver.buffer.sub_count.get(), 1);
+    /// Keeps the items broadcast with `broadcast_pinned` for lapped subscribers until
+    /// `publishes` more items are published.
+    pub fn set_pin_for(&mut self, publishes: usize) {
+        self.pins.set_pin_for(publishes);
     }
 
-    #[test]
-    fn bounded_channel() {
-        let (sender, receiver) = bounded::<i32>(1);
-        let receiver2 = receiver.clone();
-        sender.broadcast(123).unwrap();
-        assert_eq!(*receiver.try_recv().unwrap(), 123);
-        assert_eq!(*receiver2.try_recv().unwrap(), 123);
+    /// Makes the buffer store metadata of type M alongside the items, see `broadcast_with_meta`.
+    pub fn set_metadata<M: Copy + Send + 'static>(&mut self) {
+        self.set_sidecar(|size| Box::new(Metadata::<M>::new(size)));
     }
 
-    #[test]
-    fn bounded_channel_no_subs() {
-        let (sender, receiver) = bounded(1);
-        drop(receiver);
-        let err = sender.broadcast(123);
-        assert!(err.is_err());
+    /// Makes the buffer store the metadata created by `new` for its size.
+    pub(crate) fn set_sidecar(&mut self, new: NewSidecar) {
+        self.meta = Some(new(self.size));
     }
 
-    #[test]
-    fn bounded_channel_no_sender() {
-        let (sender, receiver) = bounded::<()>(1);
-        drop(sender);
-        assert_eq!(receiver.is_sender_available(), false);
+    fn metadata<M: Copy + Send + 'static>(&self) -> Option<&Metadata<M>> {
+        self.meta.as_ref()?.as_any().downcast_ref()
     }
 
-    #[test]
-    fn bounded_channel_size() {
-        let (sender, receiver) = bounded::<()>(3);
-        assert_eq!(sender.len(), 3);
-        assert_eq!(receiver.len(), 3);
+    /// Returns the metadata of type M the item at `seq` was published with, None if it
+    /// was published without any, or if the item is no longer retained.
+    pub fn meta_of<M: Copy + Send + 'static>(&self, seq: usize) -> Option<M> {
+        let meta = self.metadata::<M>()?.get(self.slot(seq));
+        // The metadata might belong to an item stored since.
+        if self.clamp_sequence(seq) != seq || seq == self.wi.get() {
+            return None;
+        }
+        meta
     }
 
-    #[test]
-    fn bounded_within_size() {
-        let (sender, receiver) = bounded(3);
-        assert_eq!(sender.len(), 3);
+    /// Returns the origin the item at `seq` was tagged with, 0 if untagged, or None
+    /// if it is no longer retained.
+    pub fn origin_of(&self, seq: usize) -> Option<usize> {
+        let origin = match &self.origins {
+            Some(origins) => origins[self.slot(seq)].load(Ordering::Acquire),
+            None => 0,
+        };
+        // The tag might belong to an item stored since.
+        if self.clamp_sequence(seq) != seq || seq == self.wi.get() {
+            return None;
+        }
+        Some(origin)
+    }
 
-        for i in 0..3 {
-            sender.broadcast(i).unwrap();
+    /// Loads the item at `seq`, decoded if there is a codec.
+    fn load(&self, seq: usize) -> Option<S::Shared> {
+        let index = self.slot(seq);
+        match &self.codec {
+            Some(codec) => codec.load(seq, index, &self.buffer[index]),
+            None => self.buffer[index].load(),
         }
+    }
 
-        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
-        assert_eq!(values, (0..=2).collect::<Vec<i32>>());
+    /// Returns the index of the slot holding the item at `seq`, taken from the full
+    /// 64 bit sequence so that wrapping usize sequences keep to consecutive slots.
+    #[inline]
+    fn slot(&self, seq: usize) -> usize {
+        (self.wi.widen(seq) % self.size as u64) as usize
     }
 
-    #[test]
-    fn bounded_overflow() {
-        let (sender, receiver) = bounded(3);
-        assert_eq!(sender.len(), 3);
+    /// Limits the items per second each subscriber may receive and each publisher
+    /// may broadcast, for the handles created from then on.
+    pub fn set_quotas(&mut self, read: Option<u32>, write: Option<u32>) {
+        self.read_quota = read;
+        self.write_quota = write;
+    }
 
-        for i in 0..4 {
-            sender.broadcast(i).unwrap();
-        }
+    /// Returns the quota of a new subscriber, if limited.
+    pub(crate) fn read_quota(&self) -> Option<Quota> {
+        self.read_quota.map(Quota::new)
+    }
 
-        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
-        assert_eq!(values, (1..=3).collect::<Vec<i32>>());
+    /// Returns the quota of a new publisher, if limited.
+    pub(crate) fn write_quota(&self) -> Option<Quota> {
+        self.write_quota.map(Quota::new)
     }
 
-    #[test]
-    fn bounded_overflow_with_reads() {
-        let (sender, receiver) = bounded(3);
-        assert_eq!(sender.len(), 3);
+    /// Makes the buffer track how far behind subscribers get and how fast items are
+    /// published, enabling `capacity_advice`.
+    pub fn set_track_capacity(&mut self, enabled: bool) {
+        self.advisor = if enabled { Some(Advisor::new()) } else { None };
+    }
 
-        for i in 0..3 {
-            sender.broadcast(i).unwrap();
-        }
+    /// Returns a capacity large enough for the subscribers observed so far not to
+    /// be lapped, at the highest publish rate observed so far.
+    /// None unless capacity tracking is enabled.
+    ///
+    /// Only subscribers which receive are observed, a subscriber that stopped
+    /// receiving altogether does not make the advice grow.
+    pub fn capacity_advice(&self) -> Option<usize> {
+        self.advisor.as_ref().map(Advisor::advice)
+    }
 
-        assert_eq!(*receiver.try_recv().unwrap(), 0);
-        assert_eq!(*receiver.try_recv().unwrap(), 1);
+    /// Spreads the async subscribers over `lanes` events, notified in rotating order so
+    /// that no subscriber is persistently woken last. Less than two lanes disables it.
+    pub fn set_fair_wakeups(&mut self, lanes: usize) {
+        self.fair_wakeups = if lanes > 1 {
+            Some(FairWakeups {
+                lanes: (0..lanes).map(|_| Event::new()).collect(),
+                next_lane: AtomicUsize::new(0),
+                rotation: AtomicUsize::new(0),
+            })
+        } else {
+            None
+        };
+    }
 
-        // "Cycle" buffer around twice
-        for i in 3..10 {
-            sender.broadcast(i).unwrap();
+    /// Touches the memory of every slot and registers the calling thread with the flavor's
+    /// bookkeeping, e.g. `arc_swap`'s debt list, which the first load of a thread otherwise
+    /// does, so that the first publishes and receives do not take the page faults.
+    ///
+    /// Best called from the threads about to publish or receive.
+    pub fn prefault(&self) {
+        for slot in &self.buffer {
+            drop(slot.load());
         }
+    }
 
-        // Should be reading from the last element in the buffer
-        let index = (receiver.buffer.wi.get() - receiver.buffer.size + 1) % receiver.buffer.size;
+    /// Makes the buffer inject the faults at their sequences.
+    #[cfg(feature = "fault-injection")]
+    pub fn set_faults(&mut self, faults: Option<Arc<Faults>>) {
+        self.faults = faults;
+    }
 
-        assert_eq!(*SwapSlot::load(&receiver.buffer.buffer[index]).unwrap(), 7);
-        assert_eq!(*receiver.try_recv().unwrap(), 7);
+    /// Starts tracking the read index of a new subscriber, if there is an overflow policy
+    pub(crate) fn register_cursor(&self, ri: usize) -> Option<Arc<AtomicCounter>> {
+        self.cursors.as_ref().map(|cursors| cursors.register(ri))
+    }
 
-        // Cloned receiver start reading where the original receiver left off
-        let receiver2 = receiver.clone();
-        assert_eq!(*receiver2.try_recv().unwrap(), 8);
-        assert_eq!(*receiver2.try_recv().unwrap(), 9);
-        assert_eq!(receiver2.try_recv(), Err(TryRecvError::Empty));
+    /// Checks if the buffer is full and its overflow policy rejects the object.
+    fn rejects_overflow(&self, object: &T) -> bool {
+        let (policy, cursors) = match (&self.overflow, &self.cursors) {
+            (Some(policy), Some(cursors)) => (policy, cursors),
+            _ => return false,
+        };
+        let wi = self.wi.get();
+        let capacity = self.size - 1;
+        if cursors.max_pending(wi) < capacity {
+            return false;
+        }
+        let oldest = wi.wrapping_sub(capacity);
+        self.buffer[self.slot(oldest)]
+            .load_with(|oldest| policy.on_overflow(oldest, object) == Overflow::RejectNewest)
+            .unwrap_or(false)
+    }
 
-        sender.broadcast(10).unwrap();
+    /// Starts tracking the activity of a new subscriber, if reaping is enabled
+    pub(crate) fn register_activity(&self) -> Option<Arc<Activity>> {
+        self.reaper.as_ref().map(Reaper::register)
+    }
 
-        // Test reader has moved forward in the buffer
-        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
-        assert_eq!(values, (8..=10).collect::<Vec<i32>>());
+    /// Records that a subscriber received
+    pub(crate) fn touch(&self, activity: &Activity) {
+        if let Some(reaper) = &self.reaper {
+            reaper.touch(activity, &self.sub_count);
+        }
     }
 
-    #[test]
-    fn read_before_writer_increments() {
-        let (sender, receiver) = bounded(3);
-        assert_eq!(sender.len(), 3);
+    /// Decrements the number of subs for a dropped subscriber whose activity is tracked
+    pub(crate) fn unregister_activity(&self, activity: &Activity) {
+        if let Some(reaper) = &self.reaper {
+            reaper.unregister(activity, &self.sub_count);
+        }
+    }
 
-        for i in 0..3 {
-            sender.broadcast(i).unwrap();
+    /// Checks if reaping is enabled and a timeout has passed since the last reaping
+    pub fn is_reap_due(&self) -> bool {
+        matches!(&self.reaper, Some(reaper) if reaper.is_due())
+    }
+
+    /// Marks the inactive subscribers as stale, returning how many were marked
+    pub fn reap_inactive(&self) -> usize {
+        match &self.reaper {
+            Some(reaper) => reaper.reap(&self.sub_count),
+            None => 0,
         }
-        assert_eq!(sender.buffer.wi.get(), 3);
-        assert_eq!(receiver.ri.get(), 0);
+    }
 
-        // Inserts the value 3, but does not increment the index.
-        SwapSlot::store(
-            &sender.buffer.buffer[sender.buffer.wi.get() % sender.buffer.size],
-            3,
-        );
-        // Receiver still expects the oldest value in buffer to be returned.
-        assert_eq!(*receiver.try_recv().unwrap(), 0);
-        // reset receiver index
-        receiver.ri.set(0);
+    /// Publishes values to the circular buffer at wi % size
+    ///
+    /// # Arguments
+    /// * `object` - owned object to be published
+    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        self.broadcast_from(object, 0)
+    }
 
-        // sender index is incremented
-        sender.buffer.wi.inc();
-        assert_eq!(*receiver.try_recv().unwrap(), 1);
+    /// Publishes the object like `broadcast`, tagged with `origin` if origin tags are enabled.
+    pub fn broadcast_from(&self, object: T, origin: usize) -> Result<(), SendError<T>> {
+        self.publish(object, origin, |_| {})
+    }
 
-        // reset receiver index
-        receiver.ri.set(0);
+    /// Publishes the object like `broadcast_from`, pinning it so that the subscribers
+    /// lapped before receiving it still receive it, see `set_pin_for`.
+    pub fn broadcast_pinned(&self, object: T, origin: usize) -> Result<(), SendError<T>> {
+        self.publish(object, origin, |wi| {
+            if let Some(item) = self.load(wi) {
+                self.pins
+                    .pin(wi, self.sequence_of(wi), item, self.sub_count.get());
+            }
+        })
+    }
 
-        // Inserts the value 4, but does not increment the index.
-        SwapSlot::store(
-            &sender.buffer.buffer[sender.buffer.wi.get() % sender.buffer.size],
-            4,
-        );
-        // Receiver still expects the oldest value in buffer to be returned.
-        assert_eq!(*receiver.try_recv().unwrap(), 1);
+    /// Publishes the object like `broadcast_from` along with its metadata, which is
+    /// dropped unless the buffer stores metadata of type M, see `set_metadata`.
+    pub fn broadcast_with_meta<M: Copy + Send + 'static>(
+        &self,
+        object: T,
+        origin: usize,
+        meta: M,
+    ) -> Result<(), SendError<T>> {
+        self.publish(object, origin, |wi| {
+            if let Some(metadata) = self.metadata::<M>() {
+                metadata.set(self.slot(wi), meta);
+            }
+        })
     }
 
-    #[test]
-    fn writer_overflows_pass_usize_max_less_then_size() {
-        let (sender, receiver) = bounded(3);
-        // set Sender wi index to usize::MAX - 3
-        sender.buffer.wi.set(usize::max_value() - 3);
-        // fill buffer so that reader can read oldest value in buffer (1,2,3)
-        for i in 1..4 {
-            sender.broadcast(i).unwrap();
+    /// Publishes the object, running `stored` with its sequence once it is stored.
+    fn publish<F: FnOnce(usize)>(
+        &self,
+        object: T,
+        origin: usize,
+        stored: F,
+    ) -> Result<(), SendError<T>> {
+        if !self.accepts_broadcasts() {
+            return Err(SendError(object));
         }
-        assert_eq!(*receiver.try_recv().unwrap(), 1);
-        assert_eq!(*receiver.try_recv().unwrap(), 2);
-
+        #[cfg(feature = "fault-injection")]
+        {
+            if matches!(&self.faults, Some(faults) if faults.is_disconnect(self.wi.get())) {
+                self.close();
+                return Err(SendError(object));
+            }
+        }
+        if self.rejects_overflow(&object) {
+            return Err(SendError(object));
+        }
+        let wi = self.wi.get();
+        self.store(wi, object, origin);
+        stored(wi);
+        self.wi.inc();
+        self.pins.expire(self.wi.get());
+        self.wake_waiters();
+        Ok(())
+    }
+
+    /// Publishes all of the values at once, subscribers either see none or all of them.
+    ///
+    /// Fails if the values would not all be retained, because there are more of them
+    /// than the buffer holds or, for a byte bounded buffer, they exceed its budget.
+    pub fn broadcast_all(&self, objects: Vec<T>) -> Result<(), SendError<Vec<T>>> {
+        self.broadcast_all_from(objects, 0)
+    }
+
+    /// Publishes the values like `broadcast_all`, all tagged with `origin`.
+    pub fn broadcast_all_from(
+        &self,
+        objects: Vec<T>,
+        origin: usize,
+    ) -> Result<(), SendError<Vec<T>>> {
+        if !self.accepts_broadcasts() {
+            return Err(SendError(objects));
+        }
+        if objects.len() > self.size - 1 {
+            return Err(SendError(objects));
+        }
+        if let Some(bound) = &self.byte_bound {
+            let bytes = objects
+                .iter()
+                .map(|object| (bound.size_of)(object))
+                .sum::<usize>();
+            if bytes > bound.max_bytes {
+                return Err(SendError(objects));
+            }
+        }
+        if objects.is_empty() {
+            return Ok(());
+        }
+        let wi = self.wi.get();
+        let len = objects.len();
+        // Every slot but the one at wi still holds a retained item,
+        // reserving them makes the readers treat those items as overwritten.
+        self.reserved.set(len - 1);
+        for (i, object) in objects.into_iter().enumerate() {
+            self.store(wi.wrapping_add(i), object, origin);
+        }
+        self.wi.set(wi.wrapping_add(len));
+        self.reserved.set(0);
+        self.pins.expire(self.wi.get());
+        self.wake_waiters();
+        Ok(())
+    }
+
+    /// Claims up to `n` slots past the write index, returning how many were claimed.
+    /// None are if there are no subscribers to publish to.
+    ///
+    /// Has to be followed by `publish_claimed`, with no broadcast in between.
+    pub fn claim(&self, n: usize) -> usize {
+        if !self.accepts_broadcasts() {
+            return 0;
+        }
+        let n = std::cmp::min(n, self.size - 1);
+        // Like for broadcast_all, the retained items in the claimed slots are overwritten.
+        self.reserved.set(n.saturating_sub(1));
+        n
+    }
+
+    /// Stores the object in the claimed slot at `offset` past the write index.
+    pub fn store_claimed(&self, offset: usize, object: T) {
+        self.store(self.wi.get().wrapping_add(offset), object, 0);
+    }
+
+    /// Publishes the first `written` claimed slots and releases the claim.
+    pub fn publish_claimed(&self, written: usize) {
+        self.wi.set(self.wi.get().wrapping_add(written));
+        self.reserved.set(0);
+        self.pins.expire(self.wi.get());
+        self.wake_waiters();
+    }
+
+    /// Stores the object at the given sequence tagged with `origin`, without publishing it.
+    fn store(&self, seq: usize, object: T, origin: usize) {
+        #[cfg(feature = "fault-injection")]
+        {
+            if let Some(faults) = &self.faults {
+                faults.delay_store(seq);
+                if faults.is_spurious_wakeup(seq) {
+                    self.recv_event.notify(usize::MAX);
+                    self.notify_critical();
+                    self.notify_lanes();
+                }
+            }
+        }
+        let object = match &self.codec {
+            Some(codec) => {
+                codec.forget(self.slot(seq));
+                codec.encode(object)
+            }
+            None => object,
+        };
+        if let Some(bound) = &self.byte_bound {
+            self.evict(bound, seq, (bound.size_of)(&object));
+        }
+        self.buffer[self.slot(seq)].store(object);
+        if let Some(timestamps) = &self.timestamps {
+            let nanos = timestamps.base.elapsed().as_nanos() as u64;
+            timestamps.nanos[self.slot(seq)].store(nanos, Ordering::Release);
+        }
+        if let Some(stamps) = &self.stamps {
+            let sequence = stamps.source.next_sequence();
+            stamps.sequences[self.slot(seq)].store(sequence, Ordering::Release);
+        }
+        if let Some(origins) = &self.origins {
+            origins[self.slot(seq)].store(origin, Ordering::Release);
+        }
+        if let Some(meta) = &self.meta {
+            meta.clear(self.slot(seq));
+        }
+        if let Some(cold) = &self.cold {
+            if let Some(item) = self.buffer[self.slot(seq)].load() {
+                cold.store(seq, self.wi.widen(seq), self.sequence_of(seq), item);
+            }
+        }
+        if let Some(advisor) = &self.advisor {
+            advisor.record_publish();
+        }
+    }
+
+    /// Returns the sequence past the last slot being stored into, wi unless `broadcast_all`
+    /// is in progress. Items that far behind it might have been overwritten.
+    fn frontier(&self) -> usize {
+        let reserved = self.reserved.get();
+        self.wi.get().wrapping_add(reserved)
+    }
+
+    /// Wakes the threads blocked in recv which have not been woken yet, if any.
+    fn wake_waiters(&self) {
+        // Pairs with the fence in register_waiter, either the waiter sees the new
+        // write index or the publisher sees the waiter.
+        atomic::fence(Ordering::SeqCst);
+        let waiting = self.waiting.get();
+        if waiting > 0 {
+            self.recv_event.notify(waiting);
+        }
+    }
+
+    /// Registers a thread about to block in recv, the returned listener is notified on the
+    /// next broadcast or on close. Has to be paired with a call to unregister_waiter.
+    #[cfg(feature = "blocking")]
+    pub fn register_waiter(&self) -> EventListener {
+        self.waiting.inc();
+        let listener = self.recv_event.listen();
+        atomic::fence(Ordering::SeqCst);
+        listener
+    }
+
+    /// Unregisters a thread which is no longer blocked in recv
+    #[cfg(feature = "blocking")]
+    pub fn unregister_waiter(&self) {
+        self.waiting.dec();
+    }
+
+    /// Evicts the oldest items until an item of `item_size` bytes fits within the budget,
+    /// and accounts for it in the slot it is about to be stored in.
+    fn evict(&self, bound: &ByteBound<T>, wi: usize, item_size: usize) {
+        let mut total = bound.total.load(Ordering::Acquire);
+        loop {
+            let tail = bound.tail.get();
+            let retained = wi.wrapping_sub(tail);
+            let over_budget = total.saturating_add(item_size) > bound.max_bytes;
+            if retained == 0 || (retained < self.size - 1 && !over_budget) {
+                break;
+            }
+            // The tail is moved first, so a reader that loads a cleared slot
+            // is guaranteed to notice it has been evicted.
+            bound.tail.inc();
+            total -= bound.sizes[self.slot(tail)].swap(0, Ordering::AcqRel);
+            self.buffer[self.slot(tail)].clear();
+        }
+        bound.sizes[self.slot(wi)].store(item_size, Ordering::Release);
+        bound.total.store(total + item_size, Ordering::Release);
+    }
+
+    /// Returns the index of the oldest retained item if the reader at `ri`
+    /// is pointing to an item evicted by the byte budget.
+    fn evicted_tail(&self, ri: usize) -> Option<usize> {
+        let bound = self.byte_bound.as_ref()?;
+        let wi = self.wi.get();
+        let tail = bound.tail.get();
+        if wi.wrapping_sub(ri) > wi.wrapping_sub(tail) {
+            Some(tail)
+        } else {
+            None
+        }
+    }
+
+    /// Receives some atomic reference to an object if queue is not empty, or None if it is. Never
+    /// Blocks
+    pub fn try_recv(
+        &self,
+        ri: &AtomicCounter,
+        skip_items: usize,
+    ) -> Result<S::Shared, TryRecvError> {
+        self.try_recv_sequenced(ri, skip_items)
+            .map(|(_, item)| item)
+    }
+
+    /// Receives an item like `try_recv`, along with its sequence: the one stamped from
+    /// the sequence source if there is one, its position in the buffer otherwise.
+    pub fn try_recv_sequenced(
+        &self,
+        ri: &AtomicCounter,
+        skip_items: usize,
+    ) -> Result<(u64, S::Shared), TryRecvError> {
+        let mut unbounded = usize::MAX;
+        self.try_recv_within(ri, skip_items, |_| true, &mut unbounded)
+    }
+
+    /// Receives like `try_recv_sequenced`, taking one from `budget` for every slot loaded.
+    /// Returns `Empty` once the budget runs out, even though items are pending.
+    ///
+    /// Items not accepted by their position are skipped.
+    pub fn try_recv_within<A: Fn(usize) -> bool>(
+        &self,
+        ri: &AtomicCounter,
+        skip_items: usize,
+        accept: A,
+        budget: &mut usize,
+    ) -> Result<(u64, S::Shared), TryRecvError> {
+        self.receive_within(ri, skip_items, accept, budget, &mut Shared)
+    }
+
+    /// Receives like `try_recv_within`, the item being read by `receive`.
+    pub(crate) fn receive_within<A, V>(
+        &self,
+        ri: &AtomicCounter,
+        skip_items: usize,
+        accept: A,
+        budget: &mut usize,
+        receive: &mut V,
+    ) -> Result<(u64, V::Output), TryRecvError>
+    where
+        A: Fn(usize) -> bool,
+        V: Receive<T, S>,
+    {
+        if let Some(advisor) = &self.advisor {
+            advisor.record_lag(self.wi.get().wrapping_sub(ri.get()));
+        }
+        loop {
+            let local_ri = ri.get();
+            if local_ri == self.wi.get() {
+                if self.is_available() {
+                    return Err(TryRecvError::Empty);
+                } else {
+                    return Err(TryRecvError::Disconnected);
+                }
+            }
+            if *budget == 0 {
+                return Err(TryRecvError::Empty);
+            }
+            *budget -= 1;
+
+            if !accept(local_ri) {
+                if self.is_readable(local_ri) {
+                    ri.inc();
+                } else {
+                    self.catch_up(ri, local_ri, skip_items);
+                }
+                continue;
+            }
+            let sequence = self.sequence_of(local_ri);
+            if let Some(item) = receive.read(self, local_ri) {
+                ri.inc();
+                return Ok((sequence, item));
+            }
+            if let Some((sequence, item)) = self.load_cold(local_ri) {
+                ri.inc();
+                return Ok((sequence, receive.take(item)));
+            }
+            self.catch_up(ri, local_ri, skip_items);
+        }
+    }
+
+    /// Returns the sequence and the item at `seq` from the cold tier, if it retains it.
+    fn load_cold(&self, seq: usize) -> Option<(u64, S::Shared)> {
+        self.cold.as_ref()?.load(seq, self.wi.widen(seq))
+    }
+
+    /// Returns the sequence of the item at `seq`: the one stamped from the sequence
+    /// source if there is one, `seq` otherwise.
+    fn sequence_of(&self, seq: usize) -> u64 {
+        match &self.stamps {
+            Some(stamps) => stamps.sequences[self.slot(seq)].load(Ordering::Acquire),
+            None => seq as u64,
+        }
+    }
+
+    /// Takes the oldest pinned item a reader at `ri` was lapped past without receiving,
+    /// along with its position and sequence.
+    pub fn take_lapped_pin(&self, ri: usize) -> Option<(usize, u64, S::Shared)> {
+        let oldest = self.clamp_retained(ri);
+        if oldest == ri {
+            return None;
+        }
+        self.pins.take_lapped(ri, oldest)
+    }
+
+    /// Records that a reader received the item at `seq`, unpinning it once all have.
+    pub fn mark_pin_received(&self, seq: usize) {
+        self.pins.mark_received(seq);
+    }
+
+    /// Checks if the item at `local_ri` can still be received, i.e. it has been neither
+    /// overwritten, evicted nor missed because of an injected fault.
+    fn is_readable(&self, local_ri: usize) -> bool {
+        let reserved = self.reserved.get();
+        let wi = self.wi.get();
+        wi.wrapping_add(reserved).wrapping_sub(local_ri) < self.size
+            && !self.is_lapped_by_fault(local_ri)
+            && self.evicted_tail(local_ri).is_none()
+    }
+
+    /// Moves a reader that can no longer receive the item at `local_ri` to the oldest
+    /// item it can, skipping `skip_items` more if it was lapped.
+    fn catch_up(&self, ri: &AtomicCounter, local_ri: usize, skip_items: usize) {
+        if let Some(cold) = &self.cold {
+            let retained = cold.capacity();
+            let wi = self.wi.get();
+            if wi.wrapping_sub(local_ri) > retained {
+                ri.set(
+                    wi.wrapping_sub(retained)
+                        .wrapping_add(skip_items.min(retained)),
+                );
+            } else {
+                // Overwritten in the cold tier by a group being published.
+                ri.set(local_ri.wrapping_add(1));
+            }
+            return;
+        }
+        // Reader has not read enough to keep up with (writer - buffer size) so
+        // set the reader pointer to be (writer - buffer size)
+        let reserved = self.reserved.get();
+        let wi = self.wi.get();
+        if wi.wrapping_add(reserved).wrapping_sub(local_ri) >= self.size {
+            if reserved + skip_items >= self.size {
+                ri.set(wi);
+            } else {
+                ri.set(
+                    wi.wrapping_add(reserved)
+                        .wrapping_sub(self.size)
+                        .wrapping_add(1 + skip_items),
+                );
+            }
+        } else if self.is_lapped_by_fault(local_ri) {
+            ri.set(local_ri.wrapping_add(1));
+        } else if let Some(tail) = self.evicted_tail(local_ri) {
+            let retained = self.wi.get().wrapping_sub(tail);
+            ri.set(tail.wrapping_add(skip_items.min(retained.saturating_sub(1))));
+        }
+    }
+
+    /// Checks if the item at `seq` is to be missed because of an injected fault.
+    #[cfg(feature = "fault-injection")]
+    fn is_lapped_by_fault(&self, seq: usize) -> bool {
+        matches!(&self.faults, Some(faults) if faults.is_lapped(seq))
+    }
+
+    #[cfg(not(feature = "fault-injection"))]
+    #[inline]
+    fn is_lapped_by_fault(&self, _: usize) -> bool {
+        false
+    }
+
+    /// Returns the slots of the items from `start` up to `end`, which must be retained,
+    /// as up to two contiguous slices, oldest first.
+    pub(crate) fn slots(&self, start: usize, end: usize) -> (&[S], &[S]) {
+        if start == end {
+            return (&[], &[]);
+        }
+        let (first, last) = (self.slot(start), self.slot(end));
+        if first < last {
+            (&self.buffer[first..last], &[])
+        } else {
+            (&self.buffer[first..], &self.buffer[..last])
+        }
+    }
+
+    /// Receives every retained item the reader has not read yet, advancing the reader
+    /// index once. Items are returned along with their sequence.
+    pub fn drain(&self, ri: &AtomicCounter, skip_items: usize) -> Vec<(usize, S::Shared)> {
+        let wi = self.wi.get();
+        let mut start = ri.get();
+        if let Some(advisor) = &self.advisor {
+            advisor.record_lag(wi.wrapping_sub(start));
+        }
+        if let Some(cold) = &self.cold {
+            let retained = cold.capacity();
+            if wi.wrapping_sub(start) > retained {
+                start = wi
+                    .wrapping_sub(retained)
+                    .wrapping_add(skip_items.min(retained));
+            }
+        } else if wi.wrapping_sub(start) >= self.size {
+            start = wi.wrapping_sub(self.size).wrapping_add(1 + skip_items);
+        } else if let Some(tail) = self.evicted_tail(start) {
+            let retained = wi.wrapping_sub(tail);
+            start = tail.wrapping_add(skip_items.min(retained.saturating_sub(1)));
+        }
+        let loaded: Vec<_> = (0..wi.wrapping_sub(start))
+            .map(|i| start.wrapping_add(i))
+            .map(|seq| (seq, self.load(seq)))
+            .collect();
+        ri.set(wi);
+
+        // Drop the items which might have been overwritten or evicted while loading.
+        let frontier = self.frontier();
+        let wi = self.wi.get();
+        let oldest = match &self.byte_bound {
+            Some(bound) => wi.wrapping_sub(bound.tail.get()),
+            None => self.size - 1,
+        };
+        loaded
+            .into_iter()
+            .filter_map(|(seq, item)| {
+                if wi.wrapping_sub(seq) <= oldest
+                    && frontier.wrapping_sub(seq) < self.size
+                    && !self.is_lapped_by_fault(seq)
+                {
+                    item.map(|item| (seq, item))
+                } else {
+                    self.load_cold(seq).map(|(_, item)| (seq, item))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the retained items published at or after `since`, oldest first.
+    /// Always empty unless timestamps are enabled.
+    pub fn retained_since(&self, since: Instant) -> Vec<S::Shared> {
+        let timestamps = match &self.timestamps {
+            Some(timestamps) => timestamps,
+            None => return Vec::new(),
+        };
+        let since = since.saturating_duration_since(timestamps.base).as_nanos() as u64;
+        let wi = self.wi.get();
+        let start = self.clamp_sequence(0);
+        let loaded: Vec<_> = (0..wi.wrapping_sub(start))
+            .map(|i| start.wrapping_add(i))
+            .map(|seq| {
+                let nanos = timestamps.nanos[self.slot(seq)].load(Ordering::Acquire);
+                (seq, nanos, self.load(seq))
+            })
+            .collect();
+
+        // Drop the items which might have been overwritten or evicted while loading.
+        loaded
+            .into_iter()
+            .filter(|(seq, nanos, _)| self.clamp_sequence(*seq) == *seq && *nanos >= since)
+            .filter_map(|(_, _, item)| item)
+            .collect()
+    }
+
+    /// Returns the nanoseconds since the item at `seq` was published, if timestamps are
+    /// enabled and the item is still retained.
+    pub fn latency_of(&self, seq: usize) -> Option<u64> {
+        let timestamps = self.timestamps.as_ref()?;
+        let published = timestamps.nanos[self.slot(seq)].load(Ordering::Acquire);
+        let now = timestamps.base.elapsed().as_nanos() as u64;
+        // The timestamp might belong to an item stored since.
+        if self.clamp_sequence(seq) != seq || seq == self.wi.get() {
+            return None;
+        }
+        Some(now.saturating_sub(published))
+    }
+
+    /// Closes the channel, closing it again does nothing.
+    pub fn close(&self) {
+        if self.is_available.swap(false, Ordering::Relaxed) {
+            self.recv_event.notify(usize::MAX);
+            self.close_event.notify(usize::MAX);
+        }
+    }
+
+    /// Returns the state of the channel: `Closing` once closed while there are
+    /// subscribers left to receive the retained items, `Closed` once they are all gone.
+    pub fn state(&self) -> ChannelState {
+        if self.is_available() {
+            ChannelState::Open
+        } else if self.sub_count.get() > 0 {
+            ChannelState::Closing
+        } else {
+            ChannelState::Closed
+        }
+    }
+
+    /// Returns a listener notified when the buffer is closed. Closing before the
+    /// listener is created is not notified, is_available has to be checked after.
+    #[cfg(feature = "blocking")]
+    pub fn listen_close(&self) -> EventListener {
+        self.close_event.listen()
+    }
+    /// Returns true if the sender is available, otherwise false
+    pub fn is_available(&self) -> bool {
+        self.is_available.load(Ordering::Relaxed)
+    }
+
+    /// Returns the length of the queue
+    pub fn len(&self) -> usize {
+        self.size - 1
+    }
+
+    /// Checks if nothings has been published yet
+    pub fn is_empty(&self) -> bool {
+        self.wi.get() == 0
+    }
+
+    /// Returns the number of published items ahead of the subscriber
+    pub fn pending(&self, ri: usize) -> usize {
+        self.wi.get().wrapping_sub(ri)
+    }
+
+    /// Checks if subscriber has read all published items
+    pub fn is_sub_empty(&self, ri: usize) -> bool {
+        self.wi.get() == ri
+    }
+
+    /// Returns the number of subs
+    pub fn sub_count(&self) -> usize {
+        self.sub_count.get()
+    }
+
+    /// Checks if a broadcast would be stored, i.e. there are subscribers or
+    /// broadcasting without subscribers is allowed.
+    pub fn accepts_broadcasts(&self) -> bool {
+        self.sub_count.get() > 0 || self.allow_no_subscribers
+    }
+
+    /// Returns the write index, the sequence of the next published item
+    pub fn write_index(&self) -> usize {
+        self.wi.get()
+    }
+
+    /// Checks if the buffer evicts items to stay within a byte budget
+    pub fn is_byte_bounded(&self) -> bool {
+        self.byte_bound.is_some()
+    }
+
+    /// Clamps a sequence into the window of retained items like `clamp_sequence`,
+    /// including the ones only retained by the cold tier.
+    pub fn clamp_retained(&self, seq: usize) -> usize {
+        let cold = match &self.cold {
+            Some(cold) => cold,
+            None => return self.clamp_sequence(seq),
+        };
+        let wi = self.wi.get();
+        let retained = std::cmp::min(wi, cold.capacity());
+        if seq > wi {
+            wi
+        } else if wi - seq > retained {
+            wi - retained
+        } else {
+            seq
+        }
+    }
+
+    /// Clamps a sequence into the window of retained items, from the oldest one to the write index.
+    pub fn clamp_sequence(&self, seq: usize) -> usize {
+        let reserved = self.reserved.get();
+        let wi = self.wi.get();
+        let mut retained = std::cmp::min(wi, self.size - 1 - reserved);
+        if let Some(bound) = &self.byte_bound {
+            retained = std::cmp::min(retained, wi.wrapping_sub(bound.tail.get()));
+        }
+        if seq > wi {
+            wi
+        } else if wi - seq > retained {
+            wi - retained
+        } else {
+            seq
+        }
+    }
+
+    /// Moves the write index, making the items in between appear published or unpublished.
+    /// Only meant for tests.
+    #[cfg(feature = "test-controls")]
+    pub fn set_write_index(&self, wi: usize) {
+        self.wi.set(wi);
+    }
+
+    /// Stores an item in the slot at the write index without publishing it.
+    /// Only meant for tests.
+    #[cfg(feature = "test-controls")]
+    pub fn store_unpublished(&self, object: T) {
+        self.buffer[self.slot(self.wi.get())].store(object);
+    }
+
+    /// Increment the number of subs
+    pub fn inc_sub_count(&self) {
+        self.sub_count.inc();
+    }
+
+    /// Decrement the number of subs
+    pub fn dec_sub_count(&self) {
+        self.sub_count.dec();
+    }
+
+    /// Returns the number of async subscribers waiting for an item
+    pub fn listeners(&self) -> usize {
+        self.listeners.get()
+    }
+
+    /// Increment the number of async subscribers waiting for an item
+    pub fn inc_listeners(&self) {
+        self.listeners.inc();
+    }
+
+    /// Decrement the number of async subscribers waiting for an item
+    pub fn dec_listeners(&self) {
+        self.listeners.dec();
+    }
+
+    /// Marks the caller as the critical subscriber, returns false if there already is one
+    pub fn claim_critical(&self) -> bool {
+        self.has_critical
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Unmarks the critical subscriber
+    pub fn release_critical(&self) {
+        self.has_critical.store(false, Ordering::Release);
+    }
+
+    /// Returns a listener notified before the other async subscribers are
+    #[cfg(feature = "blocking")]
+    pub fn listen_critical(&self) -> EventListener {
+        self.critical_event.listen()
+    }
+
+    /// Wakes the critical subscriber, if it is waiting
+    pub fn notify_critical(&self) {
+        self.critical_event.notify(usize::MAX);
+    }
+
+    /// Returns the lane of a new async subscriber, if fair wakeups are enabled
+    pub fn assign_lane(&self) -> Option<usize> {
+        let fair = self.fair_wakeups.as_ref()?;
+        Some(fair.next_lane.fetch_add(1, Ordering::Relaxed) % fair.lanes.len())
+    }
+
+    /// Returns a listener notified along with the other subscribers in the lane
+    #[cfg(feature = "blocking")]
+    pub fn listen_lane(&self, lane: usize) -> Option<EventListener> {
+        let fair = self.fair_wakeups.as_ref()?;
+        Some(fair.lanes[lane].listen())
+    }
+
+    /// Wakes the subscribers waiting in lanes, starting from the next lane in rotation
+    pub fn notify_lanes(&self) {
+        if let Some(fair) = &self.fair_wakeups {
+            let first = fair.rotation.fetch_add(1, Ordering::Relaxed);
+            for i in 0..fair.lanes.len() {
+                fair.lanes[first.wrapping_add(i) % fair.lanes.len()].notify(usize::MAX);
+            }
+        }
+    }
+
+    /// Returns the number of subscribers asking the publisher to slow down
+    pub fn slowdown_requests(&self) -> usize {
+        self.slowdown_requests.get()
+    }
+
+    /// Increment the number of subscribers asking the publisher to slow down
+    pub fn request_slowdown(&self) {
+        self.slowdown_requests.inc();
+    }
+
+    /// Decrement the number of subscribers asking the publisher to slow down
+    pub fn release_slowdown(&self) {
+        self.slowdown_requests.dec();
+        self.slowdown_released.notify(usize::MAX);
+    }
+
+    /// Returns a listener notified when a slowdown request is released
+    #[cfg(feature = "blocking")]
+    pub fn listen_slowdown(&self) -> EventListener {
+        self.slowdown_released.listen()
+    }
+
+    /// Counts a subscriber which polled for the first time.
+    pub(crate) fn mark_ready(&self) {
+        self.ready.inc();
+        self.ready_event.notify(usize::MAX);
+    }
+
+    /// Stops counting a dropped subscriber which polled.
+    pub(crate) fn unmark_ready(&self) {
+        self.ready.dec();
+    }
+
+    /// Returns the number of subscribers which polled at least once.
+    pub fn ready_count(&self) -> usize {
+        self.ready.get()
+    }
+
+    /// Returns a listener notified when a subscriber polls for the first time
+    #[cfg(feature = "blocking")]
+    pub fn listen_ready(&self) -> EventListener {
+        self.ready_event.listen()
+    }
+}
+
+impl<T, S: SwapSlot<T, Shared = Arc<T>>> RingBuffer<T, S> {
+    /// Returns the strong count of each retained item from the oldest to the newest,
+    /// 1 meaning only the buffer holds it.
+    ///
+    /// Items held long after being overwritten are not counted, but counts well
+    /// above the number of subscribers point at consumers holding on to items.
+    pub fn slot_refcounts(&self) -> Vec<usize> {
+        let wi = self.wi.get();
+        let start = self.clamp_sequence(0);
+        (0..wi.wrapping_sub(start))
+            .filter_map(|i| self.buffer[self.slot(start.wrapping_add(i))].load())
+            // Leaves out the pointer just loaded.
+            .map(|item| Arc::strong_count(&item) - 1)
+            .collect()
+    }
+}
+
+/// Drop trait is used to let subscribers know that publisher is no longer available.
+impl<T, S: SwapSlot<T>> Drop for RingBuffer<T, S> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SwapSlot;
+    use crate::flavors::arc_swap::{bounded, bounded_bytes};
+    use crate::ring_buffer::{SendError, TryRecvError};
+    use crate::TryBroadcastError;
+
+    #[test]
+    fn subcount() {
+        let (sender, receiver) = bounded::<()>(1);
+        let receiver2 = receiver.clone();
+        assert_eq!(sender.buffer.sub_count.get(), 2);
+        assert_eq!(receiver.buffer.sub_count.get(), 2);
+        assert_eq!(receiver2.buffer.sub_count.get(), 2);
+        drop(receiver2);
+
+        assert_eq!(sender.buffer.sub_count.get(), 1);
+        assert_eq!(receiver.buffer.sub_count.get(), 1);
+    }
+
+    #[test]
+    fn bounded_channel() {
+        let (sender, receiver) = bounded::<i32>(1);
+        let receiver2 = receiver.clone();
+        sender.broadcast(123).unwrap();
+        assert_eq!(*receiver.try_recv().unwrap(), 123);
+        assert_eq!(*receiver2.try_recv().unwrap(), 123);
+    }
+
+    #[test]
+    fn bounded_channel_no_subs() {
+        let (sender, receiver) = bounded(1);
+        drop(receiver);
+        let err = sender.broadcast(123);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn bounded_channel_no_sender() {
+        let (sender, receiver) = bounded::<()>(1);
+        drop(sender);
+        assert_eq!(receiver.is_sender_available(), false);
+    }
+
+    #[test]
+    fn bounded_channel_size() {
+        let (sender, receiver) = bounded::<()>(3);
+        assert_eq!(sender.len(), 3);
+        assert_eq!(receiver.len(), 3);
+    }
+
+    #[test]
+    fn bounded_within_size() {
+        let (sender, receiver) = bounded(3);
+        assert_eq!(sender.len(), 3);
+
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+
+        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, (0..=2).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn bounded_overflow() {
+        let (sender, receiver) = bounded(3);
+        assert_eq!(sender.len(), 3);
+
+        for i in 0..4 {
+            sender.broadcast(i).unwrap();
+        }
+
+        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, (1..=3).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn bounded_overflow_with_reads() {
+        let (sender, receiver) = bounded(3);
+        assert_eq!(sender.len(), 3);
+
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+
+        // "Cycle" buffer around twice
+        for i in 3..10 {
+            sender.broadcast(i).unwrap();
+        }
+
+        // Should be reading from the last element in the buffer
+        let index = (receiver.buffer.wi.get() - receiver.buffer.size + 1) % receiver.buffer.size;
+
+        assert_eq!(*SwapSlot::load(&receiver.buffer.buffer[index]).unwrap(), 7);
+        assert_eq!(*receiver.try_recv().unwrap(), 7);
+
+        // Cloned receiver start reading where the original receiver left off
+        let receiver2 = receiver.clone();
+        assert_eq!(*receiver2.try_recv().unwrap(), 8);
+        assert_eq!(*receiver2.try_recv().unwrap(), 9);
+        assert_eq!(receiver2.try_recv(), Err(TryRecvError::Empty));
+
+        sender.broadcast(10).unwrap();
+
+        // Test reader has moved forward in the buffer
+        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, (8..=10).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn read_before_writer_increments() {
+        let (sender, receiver) = bounded(3);
+        assert_eq!(sender.len(), 3);
+
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(sender.buffer.wi.get(), 3);
+        assert_eq!(receiver.ri.get(), 0);
+
+        // Inserts the value 3, but does not increment the index.
+        SwapSlot::store(
+            &sender.buffer.buffer[sender.buffer.wi.get() % sender.buffer.size],
+            3,
+        );
+        // Receiver still expects the oldest value in buffer to be returned.
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        // reset receiver index
+        receiver.ri.set(0);
+        #[cfg(feature = "ordering-checks")]
+        receiver.ordering.reset();
+
+        // sender index is incremented
+        sender.buffer.wi.inc();
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+
+        // reset receiver index
+        receiver.ri.set(0);
+        #[cfg(feature = "ordering-checks")]
+        receiver.ordering.reset();
+
+        // Inserts the value 4, but does not increment the index.
+        SwapSlot::store(
+            &sender.buffer.buffer[sender.buffer.wi.get() % sender.buffer.size],
+            4,
+        );
+        // Receiver still expects the oldest value in buffer to be returned.
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+    }
+
+    #[test]
+    fn writer_overflows_pass_usize_max_less_then_size() {
+        let (sender, receiver) = bounded(3);
+        // set Sender wi index to usize::MAX - 3
+        sender.buffer.wi.set(usize::max_value() - 3);
+        // fill buffer so that reader can read oldest value in buffer (1,2,3)
+        for i in 1..4 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+        assert_eq!(*receiver.try_recv().unwrap(), 2);
+
+        // wi should be at usize::max_value()
+        assert_eq!(sender.buffer.wi.get(), usize::max_value());
+        // ri should be at usize::max_value() -1
+        assert_eq!(receiver.ri.get(), usize::max_value() - 1);
+
+        // broadcast 2 more items (4,5) so wi is at 1
+        for i in 4..6 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(sender.buffer.wi.get(), 1);
+        // receiver should be able to receive 3
+        assert_eq!(*receiver.try_recv().unwrap(), 3);
+        // ri should be at usize::max_value()
+        assert_eq!(receiver.ri.get(), usize::max_value());
+    }
+
+    #[test]
+    fn writer_overflows_pass_usize_max_more_then_size() {
+        let (sender, receiver) = bounded(3);
+        // set Sender wi index to usize::MAX - 3
+        sender.buffer.wi.set(usize::max_value() - 3);
+        // fill buffer so that reader can read oldest value in buffer (1,2,3)
+        for i in 1..4 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+        assert_eq!(*receiver.try_recv().unwrap(), 2);
+
         // wi should be at usize::max_value()
         assert_eq!(sender.buffer.wi.get(), usize::max_value());
         // ri should be at usize::max_value() -1
         assert_eq!(receiver.ri.get(), usize::max_value() - 1);
 
-        // broadcast 2 more items (4,5) so wi is at 1
-        for i in 4..6 {
+        // broadcast 6 more items (4,5,6,7,8,9) so wi is at 5
+        for i in 4..10 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(sender.buffer.wi.get(), 5);
+
+        // before calling try_recv() ri should be at usize::max_value() - 1
+        assert_eq!(receiver.ri.get(), usize::max_value() - 1);
+        // receiver should be able to receive 7
+        assert_eq!(*receiver.try_recv().unwrap(), 7);
+        // ri should be updated to 3
+        assert_eq!(receiver.ri.get(), 3);
+    }
+
+    #[test]
+    fn slots_stay_consecutive_past_u32_max() {
+        // Three slots, u32::MAX + 1 is not a multiple of them, so a 32 bit sequence
+        // wrapping would reuse the slot of a pending item.
+        let (sender, receiver) = bounded(2);
+        let start = u64::from(u32::MAX) - 4;
+        sender.buffer.wi.set_wide(start);
+        receiver.ri.set(start as usize);
+        for i in 0..6 {
+            sender.broadcast(2 * i).unwrap();
+            sender.broadcast(2 * i + 1).unwrap();
+            assert_eq!(*receiver.try_recv().unwrap(), 2 * i);
+            assert_eq!(*receiver.try_recv().unwrap(), 2 * i + 1);
+        }
+        assert_eq!(sender.buffer.wi.get_wide(), start + 12);
+    }
+
+    #[test]
+    fn test_arc() {
+        use std::sync::Arc;
+        // make a sender with 2 receiver clones
+        let (sender, receiver) = bounded(1);
+        let receiver2 = receiver.clone();
+
+        // Broadcast an item.
+        // It is stored through an Arc inside the buffer
+        // it's reference count is 1.
+        sender.broadcast(1).unwrap();
+
+        // Pick up the item through one receiver
+        let arc1 = receiver.try_recv().unwrap();
+        assert_eq!(*arc1, 1);
+        // it's reference count jumps to 2.
+        assert_eq!(Arc::strong_count(&arc1), 2);
+
+        // Pick up the same item through the second receiver
+        let arc2 = receiver2.try_recv().unwrap();
+        // it's reference count jumps to 3.
+        assert_eq!(Arc::strong_count(&arc2), 3);
+        // the first received Arc ref count also jumps to 3.
+        assert_eq!(Arc::strong_count(&arc1), 3);
+
+        // Broadcast another item.
+        // Since the internal buffer is actually bigger by 1 then the size
+        // parameter sent the the bounded function, the item we published first
+        // is still inside the buffer and it's reference counts is unchanged.
+        sender.broadcast(2).unwrap();
+        assert_eq!(Arc::strong_count(&arc1), 3);
+        assert_eq!(Arc::strong_count(&arc2), 3);
+
+        // By broadcasting another item, we have overwritten the first item
+        // in the buffer and it's ref should drop by one.
+        sender.broadcast(3).unwrap();
+        assert_eq!(Arc::strong_count(&arc1), 2);
+        assert_eq!(Arc::strong_count(&arc2), 2);
+    }
+
+    #[test]
+    fn test_slot_refcounts() {
+        let (sender, receiver) = bounded(2);
+        assert!(sender.slot_refcounts().is_empty());
+        sender.broadcast(1).unwrap();
+        sender.broadcast(2).unwrap();
+        let held = receiver.try_recv().unwrap();
+        assert_eq!(sender.slot_refcounts(), vec![2, 1]);
+
+        sender.broadcast(3).unwrap();
+        assert_eq!(sender.slot_refcounts(), vec![1, 1]);
+        drop(held);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let (sender, receiver) = bounded(1);
+        assert!(sender.is_empty());
+        assert!(receiver.is_empty());
+        assert!(sender.buffer.is_empty());
+        sender.broadcast(1).unwrap();
+        assert!(!sender.is_empty());
+        assert!(!receiver.is_empty());
+        assert!(!sender.buffer.is_empty());
+    }
+
+    #[test]
+    fn test_has_pending() {
+        let (sender, receiver) = bounded(1);
+        assert!(!receiver.has_pending());
+        sender.broadcast(1).unwrap();
+        assert!(receiver.has_pending());
+        receiver.try_recv().unwrap();
+        assert!(!receiver.has_pending());
+        drop(sender);
+        assert!(receiver.has_pending());
+    }
+
+    #[test]
+    fn test_sender_eq() {
+        let (sender1, _) = bounded::<i32>(1);
+        let (sender2, _) = bounded::<i32>(1);
+        assert!(!sender1.eq(&sender2));
+        assert!(sender1.eq(&sender1));
+        assert!(sender2.eq(&sender2));
+    }
+
+    #[test]
+    fn test_set_skip_items() {
+        let (sender, receiver1) = bounded(3);
+        let mut receiver2 = receiver1.clone();
+        let mut receiver3 = receiver1.clone();
+        let mut receiver4 = receiver1.clone();
+        receiver2.set_skip_items(1);
+        receiver3.set_skip_items(2);
+        receiver4.set_skip_items(3);
+
+        for i in 0..6 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(*receiver1.try_recv().unwrap(), 3);
+        assert_eq!(*receiver2.try_recv().unwrap(), 4);
+        assert_eq!(*receiver3.try_recv().unwrap(), 5);
+        assert_eq!(*receiver4.try_recv().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_skip_mode() {
+        use crate::{SkipItemsError, SkipMode};
+        let (_sender, mut receiver) = bounded::<i32>(3);
+        assert_eq!(receiver.set_skip_items(5), 2);
+        assert_eq!(
+            receiver.set_skip_items_with(3, SkipMode::Strict),
+            Err(SkipItemsError {
+                requested: 3,
+                max: 2
+            })
+        );
+        // A rejected number leaves the previous one.
+        assert_eq!(receiver.skip_items(), 2);
+        assert_eq!(receiver.set_skip_items_with(1, SkipMode::Strict), Ok(1));
+    }
+
+    #[test]
+    fn bounded_bytes_evicts_oldest() {
+        let (sender, receiver) = bounded_bytes(10, 10, |v: &Vec<u8>| v.len());
+
+        sender.broadcast(vec![1; 4]).unwrap();
+        sender.broadcast(vec![2; 4]).unwrap();
+        // Publishing 4 more bytes exceeds the budget, evicting the first item.
+        sender.broadcast(vec![3; 4]).unwrap();
+
+        let values = receiver.into_iter().map(|v| v[0]).collect::<Vec<_>>();
+        assert_eq!(values, vec![2, 3]);
+    }
+
+    #[test]
+    fn bounded_bytes_releases_evicted_items() {
+        use std::sync::Arc;
+        let (sender, receiver) = bounded_bytes(10, 2, |_: &i32| 1);
+
+        sender.broadcast(1).unwrap();
+        let arc1 = receiver.try_recv().unwrap();
+        assert_eq!(Arc::strong_count(&arc1), 2);
+
+        sender.broadcast(2).unwrap();
+        sender.broadcast(3).unwrap();
+        // The first item was evicted, only the receiver holds it now.
+        assert_eq!(Arc::strong_count(&arc1), 1);
+    }
+
+    #[test]
+    fn bounded_bytes_oversized_item() {
+        let (sender, receiver) = bounded_bytes(10, 4, |v: &Vec<u8>| v.len());
+
+        sender.broadcast(vec![1; 2]).unwrap();
+        sender.broadcast(vec![2; 8]).unwrap();
+
+        let values = receiver.into_iter().map(|v| v[0]).collect::<Vec<_>>();
+        assert_eq!(values, vec![2]);
+    }
+
+    #[test]
+    fn bounded_bytes_respects_size() {
+        let (sender, receiver) = bounded_bytes(3, 100, |_: &i32| 1);
+
+        for i in 0..5 {
+            sender.broadcast(i).unwrap();
+        }
+
+        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, (2..5).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn test_set_sample_every() {
+        let (sender, receiver1) = bounded(10);
+        let mut receiver2 = receiver1.clone();
+        let mut receiver3 = receiver1.clone();
+        receiver2.set_sample_every(3);
+        receiver3.set_sample_every(3);
+
+        for i in 0..5 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(*receiver2.try_recv().unwrap(), 0);
+        assert_eq!(*receiver2.try_recv().unwrap(), 3);
+        assert_eq!(receiver2.try_recv(), Err(TryRecvError::Empty));
+
+        for i in 5..10 {
+            sender.broadcast(i).unwrap();
+        }
+        let values = receiver2.map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![6, 9]);
+        // Sampling is deterministic across clones.
+        let values = receiver3.map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 3, 6, 9]);
+        assert_eq!(receiver1.count(), 10);
+    }
+
+    #[test]
+    fn test_set_sample_every_overflow() {
+        let (sender, mut receiver) = bounded(3);
+        receiver.set_sample_every(2);
+
+        for i in 0..8 {
+            sender.broadcast(i).unwrap();
+        }
+        // Items 0 to 4 were overwritten, the oldest retained sampled item is 6.
+        let values = receiver.map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![6]);
+    }
+
+    #[test]
+    fn test_split_round_robin() {
+        let (sender, receiver) = bounded(10);
+        let workers = receiver.split_round_robin(3);
+        assert_eq!(workers.len(), 3);
+        assert_eq!(sender.sub_count(), 3);
+
+        for i in 0..8 {
+            sender.broadcast(i).unwrap();
+        }
+        let mut workers = workers.into_iter();
+        assert_eq!(*workers.next().unwrap().drain()[1], 3);
+        let values = workers.next().unwrap().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![1, 4, 7]);
+        let values = workers.next().unwrap().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![2, 5]);
+    }
+
+    #[test]
+    fn test_subscribe_at() {
+        let (sender, _receiver) = bounded(3);
+        for i in 0..2 {
+            sender.broadcast(i).unwrap();
+        }
+        let values = sender.subscribe_at(1).map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![1]);
+
+        for i in 2..6 {
+            sender.broadcast(i).unwrap();
+        }
+        // Sequences past the retained window are clamped into it.
+        let values = sender.subscribe_at(0).map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![3, 4, 5]);
+        assert!(sender.subscribe_at(100).is_empty());
+    }
+
+    #[test]
+    fn test_subscribe_at_bounded_bytes() {
+        let (sender, _receiver) = bounded_bytes(10, 2, |_: &i32| 1);
+        for i in 0..4 {
+            sender.broadcast(i).unwrap();
+        }
+
+        let values = sender.subscribe_at(0).map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_lap_count() {
+        let (sender, receiver) = bounded(4);
+        assert!(!receiver.is_lagging());
+
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+        assert!(receiver.is_lagging());
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        assert_eq!(receiver.lap_count(), 0);
+
+        for i in 3..10 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(*receiver.try_recv().unwrap(), 6);
+        assert_eq!(receiver.lap_count(), 1);
+
+        for i in 10..20 {
+            sender.broadcast(i).unwrap();
+        }
+        receiver.drain();
+        assert_eq!(receiver.lap_count(), 2);
+        assert!(!receiver.is_lagging());
+    }
+
+    #[test]
+    fn test_allow_no_subscribers() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+
+        let (sender, receiver) = Builder::new(2).build::<i32, Slot<i32>>();
+        drop(receiver);
+        assert_eq!(sender.broadcast(1), Err(SendError(1)));
+
+        let (sender, receiver) = Builder::new(2)
+            .allow_no_subscribers(true)
+            .build::<i32, Slot<i32>>();
+        drop(receiver);
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+        let values = sender.subscribe_at(0).map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_save_restore_position() {
+        use crate::{Position, RestoreError};
+
+        let (sender, mut receiver) = bounded(3);
+        sender.broadcast(0).unwrap();
+        let position = receiver.save_position();
+        let position = Position::from_bytes(position.to_bytes());
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+
+        assert_eq!(receiver.restore(position), Ok(()));
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+
+        for i in 1..5 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(receiver.restore(position), Err(RestoreError::NotRetained));
+        assert_eq!(*receiver.try_recv().unwrap(), 2);
+
+        let (_, mut other) = bounded::<i32>(3);
+        assert_eq!(other.restore(position), Err(RestoreError::OtherBus));
+    }
+
+    #[test]
+    fn test_retained_since() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        use std::time::{Duration, Instant};
+
+        let (sender, receiver) = bounded(3);
+        sender.broadcast(0).unwrap();
+        assert!(receiver.retained_since(Instant::now()).is_empty());
+
+        let (sender, receiver) = Builder::new(3).timestamps(true).build::<i32, Slot<i32>>();
+        let start = Instant::now();
+        for i in 0..2 {
+            sender.broadcast(i).unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        let since = Instant::now();
+        for i in 2..5 {
+            sender.broadcast(i).unwrap();
+        }
+
+        let values = receiver
+            .retained_since(since)
+            .into_iter()
+            .map(|v| *v)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![2, 3, 4]);
+        // Only the retained items are returned, and the receiver is not moved.
+        assert_eq!(receiver.retained_since(start).len(), 3);
+        assert_eq!(*receiver.try_recv().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_broadcast_all_or_nothing() {
+        let (sender, receiver) = bounded(4);
+        sender.broadcast(0).unwrap();
+        sender.broadcast_all_or_nothing(vec![1, 2, 3]).unwrap();
+        let values = receiver.drain().into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 1, 2, 3]);
+
+        // Groups larger than the queue are refused whole.
+        assert_eq!(
+            sender.broadcast_all_or_nothing(vec![4, 5, 6, 7, 8]),
+            Err(TryBroadcastError::Rejected(vec![4, 5, 6, 7, 8]))
+        );
+        sender.broadcast_all_or_nothing(vec![4, 5, 6, 7]).unwrap();
+        let values = receiver.map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_broadcast_all_or_nothing_concurrent() {
+        let (sender, receiver) = bounded::<usize>(8);
+        // Keeps broadcasting from failing once the reader is done.
+        let _receiver = receiver.clone();
+        let reader = std::thread::spawn(move || {
+            for _ in 0..10_000 {
+                if let Ok(group) = receiver.try_recv() {
+                    // Items of a group are never received before the whole group is published.
+                    assert!(receiver.buffer.write_index() >= (*group + 1) * 4);
+                }
+            }
+        });
+        let mut group = 0;
+        while !reader.is_finished() {
+            sender.broadcast_all_or_nothing(vec![group; 4]).unwrap();
+            group += 1;
+        }
+        reader.join().unwrap();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_start_barrier() {
+        use crate::StartBarrierError;
+        use std::time::Duration;
+
+        let (sender, receiver) = bounded::<i32>(1);
+        let other = receiver.clone();
+        let _ = receiver.try_recv();
+        assert_eq!(
+            sender.start_barrier(2, Duration::from_millis(10)),
+            Err(StartBarrierError {
+                ready: 1,
+                expected: 2
+            })
+        );
+        let waiter = std::thread::spawn(move || {
+            sender.start_barrier(2, Duration::MAX).unwrap();
+            sender
+        });
+        let _ = other.try_recv();
+        let sender = waiter.join().unwrap();
+        assert_eq!(sender.buffer.ready_count(), 2);
+        drop(other);
+        assert_eq!(sender.buffer.ready_count(), 1);
+    }
+
+    #[test]
+    fn test_reap_inactive() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let (mut sender, active) = Builder::new(3)
+            .reap_inactive(Duration::from_millis(5))
+            .build::<i32, Slot<i32>>();
+        let inactive = sender.subscribe();
+        let reaped = Arc::new(AtomicUsize::new(0));
+        let counter = reaped.clone();
+        sender.on_reaped(move |n| {
+            counter.fetch_add(n, Ordering::SeqCst);
+        });
+        assert_eq!(sender.reap_inactive(), 0);
+
+        std::thread::sleep(Duration::from_millis(10));
+        active.drain();
+        // Broadcasting reaps once the timeout has passed.
+        sender.broadcast(1).unwrap();
+        assert_eq!(reaped.load(Ordering::SeqCst), 1);
+        assert!(inactive.is_stale());
+        assert_eq!(sender.sub_count(), 1);
+
+        // Receiving again counts the subscriber again.
+        assert_eq!(*inactive.try_recv().unwrap(), 1);
+        assert!(!inactive.is_stale());
+        assert_eq!(sender.sub_count(), 2);
+        drop(inactive);
+        drop(active);
+        assert_eq!(sender.sub_count(), 0);
+    }
+
+    #[test]
+    fn test_capacity_advice() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+
+        let (sender, _receiver) = bounded::<i32>(4);
+        assert_eq!(sender.capacity_advice(), None);
+
+        let (sender, receiver) = Builder::new(4)
+            .track_capacity(true)
+            .build::<i32, Slot<i32>>();
+        assert_eq!(sender.capacity_advice(), Some(1));
+        for i in 0..10 {
+            sender.broadcast(i).unwrap();
+        }
+        // The receiver was lapped, 10 items behind.
+        assert_eq!(*receiver.try_recv().unwrap(), 6);
+        assert_eq!(sender.capacity_advice(), Some(13));
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        use crate::PausePolicy;
+
+        let (sender, receiver) = bounded(4);
+        sender.pause(PausePolicy::Reject);
+        assert!(sender.is_paused());
+        assert_eq!(sender.broadcast(1), Err(SendError(1)));
+        assert_eq!(
+            sender.broadcast_all_or_nothing(vec![1, 2]),
+            Err(TryBroadcastError::Rejected(vec![1, 2]))
+        );
+        sender.resume().unwrap();
+        assert!(receiver.is_empty());
+
+        sender.pause(PausePolicy::KeepLatest);
+        sender.broadcast(1).unwrap();
+        sender.broadcast_all_or_nothing(vec![2, 3]).unwrap();
+        sender.broadcast(4).unwrap();
+        assert!(receiver.is_empty());
+        sender.resume().unwrap();
+        sender.broadcast(5).unwrap();
+        assert_eq!(
+            receiver.into_iter().map(|x| *x).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+    }
+
+    #[test]
+    fn test_error_lane() {
+        use crate::Fallible;
+
+        let (sender, receiver) = bounded::<Fallible<i32, String>>(4);
+        sender.broadcast_ok(1).unwrap();
+        sender.broadcast_err("disconnected".to_string()).unwrap();
+        sender.broadcast_ok(2).unwrap();
+        assert_eq!(*receiver.try_recv_fallible().unwrap().unwrap(), 1);
+        assert_eq!(
+            *receiver.try_recv_fallible().unwrap().unwrap_err(),
+            "disconnected"
+        );
+        assert_eq!(*receiver.try_recv_fallible().unwrap().unwrap(), 2);
+
+        drop(receiver);
+        let refused = sender.broadcast_err("lost".to_string()).unwrap_err().0;
+        assert_eq!(*refused.unwrap_err(), "lost");
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let (sender, mut receiver) = bounded(4);
+        assert_eq!(receiver.size_hint(), (0, None));
+        for i in 0..6 {
+            sender.broadcast(i).unwrap();
+        }
+        // Only the retained items are counted.
+        assert_eq!(receiver.size_hint(), (4, None));
+        receiver.set_skip_items(1);
+        assert_eq!(receiver.size_hint(), (0, None));
+
+        let receiver = receiver.into_remaining().unwrap_err();
+        drop(sender);
+        let mut remaining = receiver.into_remaining().unwrap();
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(*remaining.next().unwrap(), 3);
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining.map(|x| *x).collect::<Vec<_>>(), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_size_hint_excluded_origins() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        let (mut sender, mut receiver) =
+            Builder::new(4).origin_tags(true).build::<i32, Slot<i32>>();
+        sender.set_origin(7);
+        receiver.exclude_origin(7);
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+        drop(sender);
+        let remaining = receiver.into_remaining().unwrap();
+        assert_eq!(remaining.len(), 0);
+        assert_eq!(remaining.count(), 0);
+    }
+
+    #[test]
+    fn test_remaining_lapped_pins() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        let (sender, receiver) = Builder::new(2).pin_for(6).build::<i32, Slot<i32>>();
+        sender.broadcast_pinned(0).unwrap();
+        (1..4).for_each(|i| sender.broadcast(i).unwrap());
+        drop(sender);
+        // The lapped pinned item is counted along with the retained ones.
+        let remaining = receiver.into_remaining().unwrap();
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(remaining.map(|x| *x).collect::<Vec<_>>(), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn test_size_hint_sampled() {
+        let (sender, mut receiver) = bounded(8);
+        receiver.set_sample_every(3);
+        for i in 0..8 {
+            sender.broadcast(i).unwrap();
+        }
+        drop(sender);
+        let remaining = receiver.into_remaining().unwrap();
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(remaining.map(|x| *x).collect::<Vec<_>>(), vec![0, 3, 6]);
+    }
+
+    #[test]
+    fn test_broadcast_cow() {
+        use std::borrow::Cow;
+
+        let (sender, receiver) = bounded(4);
+        let borrowed = "borrowed".to_string();
+        sender.broadcast_cow(Cow::Borrowed(&borrowed)).unwrap();
+        sender
+            .broadcast_cow(Cow::Owned("owned".to_string()))
+            .unwrap();
+        assert_eq!(*receiver.try_recv().unwrap(), "borrowed");
+        assert_eq!(*receiver.try_recv().unwrap(), "owned");
+    }
+
+    #[test]
+    fn test_latency_histogram() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        use std::time::Duration;
+
+        let (sender, mut receiver) = Builder::new(4).timestamps(true).build::<i32, Slot<i32>>();
+        assert_eq!(receiver.latency_histogram(), None);
+        receiver.record_latency(true);
+        sender.broadcast(1).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        sender.broadcast(2).unwrap();
+        receiver.try_recv().unwrap();
+        receiver.drain();
+
+        let histogram = receiver.latency_histogram().unwrap();
+        assert_eq!(histogram.count(), 2);
+        assert!(histogram.max() >= Duration::from_millis(5));
+        assert!(histogram.percentile(50.0) < Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_try_recv_with() {
+        let (sender, receiver1) = bounded(3);
+        let mut receiver2 = receiver1.clone();
+        receiver2.set_sample_every(2);
+
+        for i in 0..8 {
+            sender.broadcast((i, i * 10)).unwrap();
+        }
+        // Items 0 to 4 were overwritten.
+        assert_eq!(receiver1.try_recv_with(|item| item.1), Ok(50));
+        assert_eq!(receiver1.lap_count(), 1);
+        assert_eq!(receiver1.try_recv_with(|item| item.1), Ok(60));
+        assert_eq!(receiver2.try_recv_with(|item| item.0), Ok(6));
+        assert_eq!(
+            receiver2.try_recv_with(|item| item.0),
+            Err(TryRecvError::Empty)
+        );
+        assert_eq!(*receiver1.try_recv().unwrap(), (7, 70));
+        drop(sender);
+        assert_eq!(
+            receiver1.try_recv_with(|item| item.0),
+            Err(TryRecvError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_quotas() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        use std::time::Duration;
+        let (sender, receiver) = Builder::new(10)
+            .read_quota(2)
+            .write_quota(3)
+            .build::<i32, Slot<i32>>();
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(sender.broadcast(3), Err(SendError(3)));
+        assert!(sender.quota_backoff().is_some());
+        match sender.try_broadcast(3) {
+            Err(TryBroadcastError::QuotaExceeded {
+                object,
+                retry_after,
+            }) => {
+                assert_eq!(object, 3);
+                assert!(retry_after > Duration::ZERO);
+            }
+            other => panic!("not over the quota: {:?}", other),
+        }
+
+        assert_eq!(receiver.quota_backoff(), None);
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        assert!(receiver.drain().is_empty());
+        assert!(receiver.quota_backoff().is_some());
+        // Each receiver has its own quota.
+        let other = sender.subscribe_at(0);
+        assert_eq!(*other.try_recv().unwrap(), 0);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_recv_waits_for_quota() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::parker::{Parker, ThreadParker, Wakeup};
+        use crate::Builder;
+        use std::cell::Cell;
+        use std::time::Instant;
+
+        #[derive(Default)]
+        struct CountingParker(Cell<usize>);
+
+        impl Parker for CountingParker {
+            fn park(&self, wakeup: Wakeup) {
+                self.0.set(self.0.get() + 1);
+                ThreadParker.park(wakeup);
+            }
+
+            fn park_deadline(&self, wakeup: Wakeup, deadline: Instant) -> bool {
+                self.0.set(self.0.get() + 1);
+                ThreadParker.park_deadline(wakeup, deadline)
+            }
+        }
+
+        let (sender, receiver) = Builder::new(128).read_quota(100).build::<i32, Slot<i32>>();
+        for i in 0..101 {
             sender.broadcast(i).unwrap();
         }
-        assert_eq!(sender.buffer.wi.get(), 1);
-        // receiver should be able to receive 3
-        assert_eq!(*receiver.try_recv().unwrap(), 3);
-        // ri should be at usize::max_value()
-        assert_eq!(receiver.ri.get(), usize::max_value());
+        for i in 0..100 {
+            assert_eq!(*receiver.try_recv().unwrap(), i);
+        }
+        // Parked until the quota refills instead of spinning.
+        let parker = CountingParker::default();
+        assert_eq!(*receiver.recv_with_parker(&parker).unwrap(), 100);
+        assert!(parker.0.get() < 5);
     }
 
+    #[cfg(feature = "blocking")]
     #[test]
-    fn writer_overflows_pass_usize_max_more_then_size() {
+    fn test_recv_parks_while_sampled_out() {
+        use crate::parker::FakeParker;
+        use std::rc::Rc;
+        let (sender, mut receiver) = bounded::<u32>(8);
+        receiver.set_sample_every(3);
+        sender.broadcast(0).unwrap();
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        sender.broadcast(1).unwrap();
+
+        // Items pending but sampled out, the receiver parks until a sampled one is published.
+        let parker = FakeParker::new();
+        let sender = Rc::new(sender);
+        let publisher = sender.clone();
+        parker.then(move || publisher.broadcast(2).unwrap());
+        parker.then(move || sender.broadcast(3).unwrap());
+        assert_eq!(receiver.recv_with_parker(&parker).map(|v| *v), Ok(3));
+        assert_eq!(parker.parks(), 2);
+        assert_eq!(receiver.buffer.waiting.get(), 0);
+    }
+
+    #[test]
+    fn test_exclude_origin() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::{Builder, Publisher};
+        let (mut sender1, mut receiver1) =
+            Builder::new(10).origin_tags(true).build::<i32, Slot<i32>>();
+        let mut sender2 = Publisher::from(sender1.buffer.clone());
+        let mut receiver2 = receiver1.clone();
+        sender1.set_origin(1);
+        sender2.set_origin(2);
+        receiver1.exclude_origin(1);
+        receiver2.exclude_origin(2);
+        for i in 0..3 {
+            sender1.broadcast(i).unwrap();
+            sender2.broadcast(i * 10).unwrap();
+        }
+        sender1.broadcast_all_or_nothing(vec![3, 4]).unwrap();
+
+        let received: Vec<_> = (0..3).map(|_| *receiver1.try_recv().unwrap()).collect();
+        assert_eq!(received, vec![0, 10, 20]);
+        assert_eq!(receiver1.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(receiver2.try_recv_with(|item| *item), Ok(0));
+        let received: Vec<_> = receiver2.drain().iter().map(|item| **item).collect();
+        assert_eq!(received, vec![1, 2, 3, 4]);
+        assert_eq!(sender1.buffer.origin_of(0), Some(1));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_recv_status() {
+        use crate::flavors::arc_swap::async_bounded;
+        use crate::RecvStatus;
+        use futures::executor::block_on;
+        use futures::{SinkExt, StreamExt};
         let (sender, receiver) = bounded(3);
-        // set Sender wi index to usize::MAX - 3
-        sender.buffer.wi.set(usize::max_value() - 3);
-        // fill buffer so that reader can read oldest value in buffer (1,2,3)
-        for i in 1..4 {
+        sender.broadcast(0).unwrap();
+        assert_eq!(receiver.try_recv_status().unwrap().skipped(), 0);
+        for i in 1..6 {
+            sender.broadcast(i).unwrap();
+        }
+        let status = receiver.try_recv_status().unwrap();
+        assert_eq!(status.skipped(), 2);
+        assert_eq!(*status.into_item(), 3);
+        assert!(matches!(
+            receiver.try_recv_status(),
+            Ok(RecvStatus::Fresh(_))
+        ));
+
+        let (mut sender, receiver) = async_bounded(2);
+        let mut receiver = receiver.with_status();
+        for i in 0..4 {
+            block_on(sender.send(i)).unwrap();
+        }
+        let status = block_on(receiver.next()).unwrap();
+        assert_eq!((*status.clone().into_item(), status.skipped()), (2, 2));
+    }
+
+    #[test]
+    fn test_recv_lagged() {
+        use crate::TryRecvLaggedError;
+        let (sender, receiver) = bounded(3);
+        sender.broadcast(0).unwrap();
+        assert_eq!(receiver.try_recv_lagged().map(|v| *v), Ok(0));
+        for i in 1..7 {
             sender.broadcast(i).unwrap();
         }
+        assert_eq!(
+            receiver.try_recv_lagged(),
+            Err(TryRecvLaggedError::Lagged(3))
+        );
+        // The held back item is not lost to further laps.
+        for i in 7..10 {
+            sender.broadcast(i).unwrap();
+        }
+        assert_eq!(receiver.try_recv_lagged().map(|v| *v), Ok(4));
+        assert_eq!(
+            receiver.try_recv_lagged(),
+            Err(TryRecvLaggedError::Lagged(2))
+        );
+        assert_eq!(receiver.try_recv_lagged().map(|v| *v), Ok(7));
+        drop(sender);
+        assert_eq!(receiver.try_recv_lagged().map(|v| *v), Ok(8));
+        assert_eq!(receiver.try_recv_lagged().map(|v| *v), Ok(9));
+        assert_eq!(
+            receiver.try_recv_lagged(),
+            Err(TryRecvLaggedError::Disconnected)
+        );
+    }
+
+    #[cfg(feature = "rwlock")]
+    #[test]
+    fn test_is_lock_free() {
+        use crate::flavors::{arc_swap, rw_lock};
+        use crate::CachePadded;
+        const _: () = assert!(<CachePadded<arc_swap::Slot<u8>> as SwapSlot<u8>>::IS_LOCK_FREE);
+        assert!(bounded::<u8>(1).0.is_lock_free());
+        assert!(!rw_lock::bounded::<u8>(1).0.is_lock_free());
+        const _: () = assert!(!<arc_swap::Slot<u8> as SwapSlot<u8>>::LOAD_ALLOCATES);
+    }
+
+    #[test]
+    fn test_prefault() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        let (sender, receiver) = Builder::new(4).prefault(true).build::<i32, Slot<i32>>();
+        // Prefaulting a bus in use leaves its items alone.
+        sender.broadcast(1).unwrap();
+        sender.buffer.prefault();
         assert_eq!(*receiver.try_recv().unwrap(), 1);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        use super::{RingBuffer, ZeroCapacityError};
+        use crate::flavors::arc_swap::Slot;
+        let (sender, receiver) = bounded(0);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        sender.broadcast(1).unwrap();
+        sender.broadcast(2).unwrap();
+        assert_eq!(receiver.len(), 1);
         assert_eq!(*receiver.try_recv().unwrap(), 2);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
 
-        // wi should be at usize::max_value()
-        assert_eq!(sender.buffer.wi.get(), usize::max_value());
-        // ri should be at usize::max_value() -1
-        assert_eq!(receiver.ri.get(), usize::max_value() - 1);
+        assert_eq!(
+            RingBuffer::<i32, Slot<i32>>::try_from_capacity(0).err(),
+            Some(ZeroCapacityError)
+        );
+        assert!(RingBuffer::<i32, Slot<i32>>::try_from_capacity(1).is_ok());
+    }
 
-        // broadcast 6 more items (4,5,6,7,8,9) so wi is at 5
-        for i in 4..10 {
+    #[test]
+    fn test_broadcast_pinned() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::Builder;
+        let (sender, mut receiver1) = Builder::new(2).pin_for(6).build::<i32, Slot<i32>>();
+        let receiver2 = receiver1.clone();
+        let receiver3 = receiver1.clone();
+        sender.broadcast(0).unwrap();
+        sender.broadcast_pinned(1).unwrap();
+        (2..6).for_each(|i| sender.broadcast(i).unwrap());
+
+        // The lapped receivers still receive the pinned item first.
+        let received: Vec<_> = receiver1.by_ref().map(|item| *item).collect();
+        assert_eq!(received, vec![1, 4, 5]);
+        assert_eq!(receiver1.lap_count(), 2);
+        let received: Vec<_> = receiver2.drain().iter().map(|item| **item).collect();
+        assert_eq!(received, vec![1, 4, 5]);
+        // Unpinned once published 6 items ago.
+        (6..8).for_each(|i| sender.broadcast(i).unwrap());
+        let received: Vec<_> = receiver3.map(|item| *item).collect();
+        assert_eq!(received, vec![6, 7]);
+    }
+
+    #[test]
+    fn test_try_recv_with_pins_and_origins() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::{Builder, Publisher};
+        let (sender, receiver) = Builder::new(2).pin_for(6).build::<i32, Slot<i32>>();
+        sender.broadcast_pinned(0).unwrap();
+        (1..4).for_each(|i| sender.broadcast(i).unwrap());
+        // The lapped receiver still gets the pinned item first.
+        assert_eq!(receiver.try_recv_with(|item| *item), Ok(0));
+        assert_eq!(receiver.try_recv_with(|item| *item), Ok(2));
+
+        let (mut sender1, mut receiver) =
+            Builder::new(4).origin_tags(true).build::<i32, Slot<i32>>();
+        let mut sender2 = Publisher::from(sender1.buffer.clone());
+        sender1.set_origin(1);
+        sender2.set_origin(2);
+        receiver.exclude_origin(1);
+        sender1.broadcast(0).unwrap();
+        sender2.broadcast(1).unwrap();
+        // Passing over an excluded item is not a lap.
+        assert_eq!(receiver.try_recv_with(|item| *item), Ok(1));
+        assert_eq!(receiver.lap_count(), 0);
+    }
+
+    #[test]
+    fn test_broadcast_lazy() {
+        let (sender, receiver) = bounded(2);
+        assert_eq!(sender.broadcast_lazy(|| 1), Ok(true));
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+        drop(receiver);
+        assert_eq!(sender.broadcast_lazy(|| unreachable!()), Ok(false));
+    }
+
+    #[test]
+    fn test_state() {
+        use crate::ChannelState;
+        let (sender, receiver) = bounded(2);
+        sender.broadcast(1).unwrap();
+        assert_eq!(sender.state(), ChannelState::Open);
+        assert_eq!(receiver.state(), ChannelState::Open);
+        sender.close();
+        sender.close();
+        assert_eq!(sender.state(), ChannelState::Closing);
+        assert_eq!(receiver.state(), ChannelState::Closing);
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+        assert_eq!(receiver.state(), ChannelState::Closed);
+        drop(receiver);
+        assert_eq!(sender.state(), ChannelState::Closed);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_fence_token() {
+        use std::sync::Arc;
+        use std::thread;
+        let (sender, receiver) = bounded(10);
+        let receiver = Arc::new(receiver);
+        sender.broadcast(1).unwrap();
+        let token = sender.fence_token();
+        sender.broadcast(2).unwrap();
+        assert!(!receiver.is_past(token));
+
+        let consumer = receiver.clone();
+        let handle = thread::spawn(move || *consumer.recv().unwrap());
+        receiver.wait_for_token(token);
+        assert!(receiver.is_past(token));
+        assert_eq!(handle.join().unwrap(), 1);
+        // Items published after the token are not waited for.
+        assert_eq!(*receiver.try_recv().unwrap(), 2);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_fence_token_wakeups() {
+        use std::sync::Arc;
+        use std::thread;
+        let (sender, receiver) = bounded(4);
+        let receiver = Arc::new(receiver);
+        let consumer = receiver.clone();
+        let handle =
+            thread::spawn(move || (0..500).map(|_| *consumer.recv().unwrap()).sum::<i32>());
+        // Every wait ends with a receive, none is missed without rechecking.
+        for i in 0..500 {
             sender.broadcast(i).unwrap();
+            receiver.wait_for_token(sender.fence_token());
         }
-        assert_eq!(sender.buffer.wi.get(), 5);
+        assert_eq!(handle.join().unwrap(), (0..500).sum::<i32>());
+    }
 
-        // before calling try_recv() ri should be at usize::max_value() - 1
-        assert_eq!(receiver.ri.get(), usize::max_value() - 1);
-        // receiver should be able to receive 7
-        assert_eq!(*receiver.try_recv().unwrap(), 7);
-        // ri should be updated to 3
-        assert_eq!(receiver.ri.get(), 3);
+    #[test]
+    fn test_bounded_tiered() {
+        use crate::flavors::arc_swap::bounded_tiered;
+        let (sender, receiver) = bounded_tiered(2, 6);
+        let lapped = receiver.clone();
+        (0..5).for_each(|i| sender.broadcast(i).unwrap());
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        assert_eq!(receiver.try_recv_with(|item| *item), Ok(1));
+        let received: Vec<_> = receiver.map(|item| *item).collect();
+        assert_eq!(received, vec![2, 3, 4]);
+
+        (5..9).for_each(|i| sender.broadcast(i).unwrap());
+        // Lapped past the cold tier as well.
+        let received: Vec<_> = lapped.drain().iter().map(|item| **item).collect();
+        assert_eq!(received, vec![3, 4, 5, 6, 7, 8]);
+        assert_eq!(lapped.lap_count(), 1);
+        assert_eq!(*sender.subscribe_at(0).try_recv().unwrap(), 3);
     }
 
     #[test]
-    fn test_arc() {
-        use std::sync::Arc;
-        // make a sender with 2 receiver clones
-        let (sender, receiver) = bounded(1);
+    fn test_sequence_source() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::{Builder, Counter};
+
+        let (sender, receiver) = bounded(2);
+        for i in 0..3 {
+            sender.broadcast(i).unwrap();
+        }
+        // Without a source, the sequence is the position in the bus.
+        let (sequence, value) = receiver.try_recv_sequenced().unwrap();
+        assert_eq!((sequence, *value), (1, 1));
+
+        let (sender, mut receiver) = Builder::new(4)
+            .sequence_source(Counter::new(1000))
+            .build::<i32, Slot<i32>>();
+        receiver.set_sample_every(2);
+        for i in 0..4 {
+            sender.broadcast(i).unwrap();
+        }
+        let (sequence, value) = receiver.try_recv_sequenced().unwrap();
+        assert_eq!((sequence, *value), (1000, 0));
+        let (sequence, value) = receiver.try_recv_sequenced().unwrap();
+        assert_eq!((sequence, *value), (1002, 2));
+    }
+
+    #[test]
+    fn custom_shared_pointer() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        // A single threaded slot handing out Rc pointers.
+        struct RcSlot<T>(RefCell<Option<Rc<T>>>);
+
+        impl<T> SwapSlot<T> for RcSlot<T> {
+            type Shared = Rc<T>;
+
+            fn store(&self, item: T) {
+                *self.0.borrow_mut() = Some(Rc::new(item));
+            }
+
+            fn load(&self) -> Option<Rc<T>> {
+                self.0.borrow().clone()
+            }
+
+            fn clear(&self) {
+                *self.0.borrow_mut() = None;
+            }
+
+            fn none() -> Self {
+                RcSlot(RefCell::new(None))
+            }
+        }
+
+        let (sender, receiver) = crate::bounded::<i32, RcSlot<i32>>(2);
+        sender.broadcast(1).unwrap();
+        let rc: Rc<i32> = receiver.try_recv().unwrap();
+        assert_eq!(*rc, 1);
+        assert_eq!(Rc::strong_count(&rc), 2);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn recv_blocks_until_broadcast() {
+        use std::thread;
+        use std::time::Duration;
+        let (sender, receiver) = bounded(3);
         let receiver2 = receiver.clone();
 
-        // Broadcast an item.
-        // It is stored through an Arc inside the buffer
-        // it's reference count is 1.
+        let handles: Vec<_> = vec![receiver, receiver2]
+            .into_iter()
+            .map(|receiver| thread::spawn(move || *receiver.recv().unwrap()))
+            .collect();
+        // Wait for both threads to block.
+        while sender.buffer.waiting.get() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
         sender.broadcast(1).unwrap();
 
-        // Pick up the item through one receiver
-        let arc1 = receiver.try_recv().unwrap();
-        assert_eq!(*arc1, 1);
-        // it's reference count jumps to 2.
-        assert_eq!(Arc::strong_count(&arc1), 2);
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 1);
+        }
+        assert_eq!(sender.buffer.waiting.get(), 0);
+    }
 
-        // Pick up the same item through the second receiver
-        let arc2 = receiver2.try_recv().unwrap();
-        // it's reference count jumps to 3.
-        assert_eq!(Arc::strong_count(&arc2), 3);
-        // the first received Arc ref count also jumps to 3.
-        assert_eq!(Arc::strong_count(&arc1), 3);
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn recv_disconnected() {
+        use crate::ring_buffer::RecvError;
+        use std::thread;
+        let (sender, receiver) = bounded(3);
+        sender.broadcast(1).unwrap();
 
-        // Broadcast another item.
-        // Since the internal buffer is actually bigger by 1 then the size
-        // parameter sent the the bounded function, the item we published first
-        // is still inside the buffer and it's reference counts is unchanged.
-        sender.broadcast(2).unwrap();
-        assert_eq!(Arc::strong_count(&arc1), 3);
-        assert_eq!(Arc::strong_count(&arc2), 3);
+        let handle = thread::spawn(move || {
+            let first = receiver.recv().map(|v| *v);
+            (first, receiver.recv().map(|v| *v))
+        });
+        drop(sender);
 
-        // By broadcasting another item, we have overwritten the first item
-        // in the buffer and it's ref should drop by one.
-        sender.broadcast(3).unwrap();
-        assert_eq!(Arc::strong_count(&arc1), 2);
-        assert_eq!(Arc::strong_count(&arc2), 2);
+        // Items published before the disconnect are still received.
+        assert_eq!(handle.join().unwrap(), (Ok(1), Err(RecvError)));
     }
 
+    #[cfg(feature = "blocking")]
     #[test]
-    fn test_is_empty() {
-        let (sender, receiver) = bounded(1);
-        assert!(sender.is_empty());
-        assert!(receiver.is_empty());
-        assert!(sender.buffer.is_empty());
+    fn test_recv_timeout() {
+        use crate::parker::FakeParker;
+        use crate::ring_buffer::RecvTimeoutError;
+        use std::rc::Rc;
+        use std::time::{Duration, Instant};
+        let (sender, receiver) = bounded(3);
+        let parker = FakeParker::new();
+
+        let start = Instant::now();
+        parker.then(|| {});
+        assert_eq!(
+            receiver.recv_timeout_with_parker(Duration::from_millis(20), &parker),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert_eq!(parker.parks(), 1);
+        assert_eq!(receiver.buffer.waiting.get(), 0);
+        // A deadline already passed does not block.
+        assert_eq!(
+            receiver.recv_deadline(start),
+            Err(RecvTimeoutError::Timeout)
+        );
+        sender.broadcast(0).unwrap();
+        assert_eq!(receiver.recv_deadline(start).map(|v| *v), Ok(0));
+
+        let sender = Rc::new(sender);
+        let publisher = sender.clone();
+        parker.then(move || publisher.broadcast(1).unwrap());
+        parker.then(move || drop(sender));
+        let timeout = Duration::from_secs(10);
+        assert_eq!(
+            receiver
+                .recv_timeout_with_parker(timeout, &parker)
+                .map(|v| *v),
+            Ok(1)
+        );
+        assert_eq!(
+            receiver.recv_timeout_with_parker(timeout, &parker),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(parker.parks(), 3);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_recv_timeout_parks_while_excluded() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::parker::FakeParker;
+        use crate::ring_buffer::RecvTimeoutError;
+        use crate::{Builder, Publisher};
+        use std::time::Duration;
+        let (mut sender1, mut receiver) =
+            Builder::new(8).origin_tags(true).build::<i32, Slot<i32>>();
+        let mut sender2 = Publisher::from(sender1.buffer.clone());
+        sender1.set_origin(1);
+        sender2.set_origin(2);
+        receiver.exclude_origin(1);
+        sender1.broadcast(0).unwrap();
+        let parker = FakeParker::new();
+        let timeout = Duration::from_secs(10);
+
+        // Only an excluded item pending, the receiver parks until the deadline.
+        parker.then(|| {});
+        assert_eq!(
+            receiver.recv_timeout_with_parker(timeout, &parker),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert_eq!(parker.parks(), 1);
+
+        parker.then(move || sender2.broadcast(1).unwrap());
+        assert_eq!(
+            receiver
+                .recv_timeout_with_parker(timeout, &parker)
+                .map(|v| *v),
+            Ok(1)
+        );
+        assert_eq!(parker.parks(), 2);
+        assert_eq!(receiver.buffer.waiting.get(), 0);
+    }
+
+    #[test]
+    fn test_drain() {
+        let (sender, receiver) = bounded(3);
+        assert!(receiver.drain().is_empty());
+
+        sender.broadcast(0).unwrap();
         sender.broadcast(1).unwrap();
-        assert!(!sender.is_empty());
-        assert!(!receiver.is_empty());
-        assert!(!sender.buffer.is_empty());
+        let values = receiver.drain().into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 1]);
+        assert!(receiver.is_empty());
+
+        // Only the retained items are drained after an overflow.
+        for i in 2..7 {
+            sender.broadcast(i).unwrap();
+        }
+        let values = receiver.drain().into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![4, 5, 6]);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
     }
 
     #[test]
-    fn test_sender_eq() {
-        let (sender1, _) = bounded::<i32>(1);
-        let (sender2, _) = bounded::<i32>(1);
-        assert!(!sender1.eq(&sender2));
-        assert!(sender1.eq(&sender1));
-        assert!(sender2.eq(&sender2));
+    fn test_drain_bounded_bytes() {
+        let (sender, receiver) = bounded_bytes(10, 2, |_: &i32| 1);
+        for i in 0..4 {
+            sender.broadcast(i).unwrap();
+        }
+
+        let values = receiver.drain().into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![2, 3]);
     }
 
     #[test]
-    fn test_set_skip_items() {
-        let (sender, receiver1) = bounded(3);
-        let mut receiver2 = receiver1.clone();
-        let mut receiver3 = receiver1.clone();
-        let mut receiver4 = receiver1.clone();
-        receiver2.set_skip_items(1);
-        receiver3.set_skip_items(2);
-        receiver4.set_skip_items(3);
+    fn test_middleware() {
+        let (mut sender, receiver) = bounded(10);
+        // Drop odd numbers and scale the rest, in that order.
+        sender.add_middleware(|i: i32| if i % 2 == 0 { Some(i) } else { None });
+        sender.add_middleware(|i| Some(i * 10));
 
-        for i in 0..6 {
+        for i in 0..5 {
             sender.broadcast(i).unwrap();
         }
-        assert_eq!(*receiver1.try_recv().unwrap(), 3);
-        assert_eq!(*receiver2.try_recv().unwrap(), 4);
-        assert_eq!(*receiver3.try_recv().unwrap(), 5);
-        assert_eq!(*receiver4.try_recv().unwrap(), 5);
+
+        let values = receiver.into_iter().map(|v| *v).collect::<Vec<_>>();
+        assert_eq!(values, vec![0, 20, 40]);
+    }
+
+    #[test]
+    fn test_bus_id() {
+        let (sender1, receiver1) = bounded::<i32>(1);
+        let (sender2, _) = bounded::<i32>(1);
+        assert_eq!(sender1.bus_id(), receiver1.bus_id());
+        assert_eq!(receiver1.bus_id(), receiver1.clone().bus_id());
+        assert_ne!(sender1.bus_id(), sender2.bus_id());
+    }
+
+    #[test]
+    fn test_restore_on_replacement_bus() {
+        use crate::flavors::arc_swap::Slot;
+        use crate::{Builder, Position, RestoreError};
+        let (sender, receiver) = Builder::new(4).bus_id(7).build::<i32, Slot<i32>>();
+        (0..3).for_each(|i| sender.broadcast(i).unwrap());
+        assert_eq!(*receiver.try_recv().unwrap(), 0);
+        let saved = receiver.save_position().to_bytes();
+        drop((sender, receiver));
+
+        // Buses get ids of their own, even allocated where the dropped one was.
+        let (_, mut stranger) = bounded::<i32>(4);
+        let position = Position::from_bytes(saved);
+        assert_eq!(stranger.restore(position), Err(RestoreError::OtherBus));
+
+        // The bus replacing it, e.g. after a restart, accepts its positions.
+        let (replacement, mut receiver) = Builder::new(4).bus_id(7).build::<i32, Slot<i32>>();
+        (0..3).for_each(|i| replacement.broadcast(i).unwrap());
+        assert_eq!(receiver.restore(position), Ok(()));
+        assert_eq!(*receiver.try_recv().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_debug() {
+        let (sender, receiver) = bounded::<i32>(2);
+        sender.broadcast(1).unwrap();
+        let id = format!("bus_id: {}", sender.bus_id());
+
+        let sender = format!("{:?}", sender);
+        assert!(sender.starts_with("Publisher"));
+        assert!(sender.contains(&id));
+        assert!(sender.contains("wi: 1, size: 2, sub_count: 1, closed: false"));
+
+        let receiver = format!("{:?}", receiver);
+        assert!(receiver.starts_with("Subscriber"));
+        assert!(receiver.contains(&id));
+        assert!(receiver.contains("ri: 0, wi: 1, size: 2, sub_count: 1, closed: false"));
     }
 }
@@ -0,0 +1,31 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the sequence numbers stamped on published items, set through
+/// `Builder::sequence_source` and received with `Subscriber::try_recv_sequenced`.
+///
+/// Without a source, items carry their position in the bus.
+pub trait SequenceSource: Debug + Send + Sync {
+    /// Returns the sequence of the item about to be published.
+    fn next_sequence(&self) -> u64;
+}
+
+/// Gap-free counter, starting from a given sequence.
+#[derive(Debug, Default)]
+pub struct Counter {
+    next: AtomicU64,
+}
+
+impl Counter {
+    pub fn new(first: u64) -> Self {
+        Self {
+            next: AtomicU64::new(first),
+        }
+    }
+}
+
+impl SequenceSource for Counter {
+    fn next_sequence(&self) -> u64 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+}
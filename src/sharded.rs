@@ -0,0 +1,173 @@
+use crate::atomic_counter::AtomicCounter;
+use crate::publisher::Publisher;
+use crate::ring_buffer::{SendError, TryRecvError};
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Set of buses behind a single handle, each item being routed to one of them by
+/// the hash of its key, so that several threads can publish at once.
+///
+/// Publishing to different shards happens in parallel, publishing to the same one is
+/// serialized. Items are only ordered within a shard: items sharing a key stay in order.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::ShardedBus;
+///
+/// let bus = ShardedBus::new(4, 10, |item: &(u64, &str)| item.0);
+/// let all = bus.subscribe_all();
+/// let shard = bus.subscribe_shard(bus.shard_of(&(7, "")));
+/// bus.broadcast((7, "seven")).unwrap();
+///
+/// assert_eq!(shard.try_recv().unwrap().1, "seven");
+/// assert_eq!(all.try_recv().unwrap().1, "seven");
+/// ```
+pub struct ShardedBus<T, S: SwapSlot<T>> {
+    shards: Vec<Mutex<Publisher<T, S>>>,
+    hash: Box<dyn Fn(&T) -> u64 + Send + Sync>,
+}
+
+impl<T, S: SwapSlot<T>> ShardedBus<T, S> {
+    /// Creates `shards` buses retaining up to `size` items each, items being routed
+    /// by the given hash of their key.
+    ///
+    /// # Panics
+    ///
+    /// If `shards` is 0.
+    pub fn new<F>(shards: usize, size: usize, hash: F) -> Self
+    where
+        F: Fn(&T) -> u64 + Send + Sync + 'static,
+    {
+        assert!(shards > 0, "a sharded bus needs at least one shard");
+        Self {
+            shards: (0..shards)
+                .map(|_| Mutex::new(crate::bounded::<T, S>(size).0))
+                .collect(),
+            hash: Box::new(hash),
+        }
+    }
+
+    /// Returns the number of shards.
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the shard the item is routed to.
+    pub fn shard_of(&self, object: &T) -> usize {
+        ((self.hash)(object) % self.shards.len() as u64) as usize
+    }
+
+    /// Publishes the object to its shard, failing if the shard has no subscribers.
+    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        let shard = self.shard_of(&object);
+        self.shards[shard].lock().unwrap().broadcast(object)
+    }
+
+    /// Creates a receiver of the items published to the shard from now on.
+    ///
+    /// # Panics
+    ///
+    /// If there is no such shard.
+    pub fn subscribe_shard(&self, shard: usize) -> Subscriber<T, S> {
+        self.shards[shard].lock().unwrap().subscribe()
+    }
+
+    /// Creates a receiver of the items published to every shard from now on.
+    pub fn subscribe_all(&self) -> ShardedSubscriber<T, S> {
+        ShardedSubscriber {
+            shards: (0..self.shards.len())
+                .map(|shard| self.subscribe_shard(shard))
+                .collect(),
+            next: AtomicCounter::new(0),
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for ShardedBus<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedBus")
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
+
+/// Receiver of every shard of a `ShardedBus`, returned by `ShardedBus::subscribe_all`.
+pub struct ShardedSubscriber<T, S: SwapSlot<T>> {
+    shards: Vec<Subscriber<T, S>>,
+    /// Shard tried first by the next receive, so that no shard starves the others
+    next: AtomicCounter,
+}
+
+impl<T, S: SwapSlot<T>> ShardedSubscriber<T, S> {
+    /// Receives the next item of one of the shards, taking turns between them.
+    /// Returns `Disconnected` once every shard is closed and drained.
+    pub fn try_recv(&self) -> Result<S::Shared, TryRecvError> {
+        let first = self.next.get();
+        self.next.inc();
+        let mut disconnected = 0;
+        for i in 0..self.shards.len() {
+            match self.shards[first.wrapping_add(i) % self.shards.len()].try_recv() {
+                Ok(item) => return Ok(item),
+                Err(TryRecvError::Disconnected) => disconnected += 1,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        if disconnected == self.shards.len() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Returns the receivers of each shard.
+    pub fn into_shards(self) -> Vec<Subscriber<T, S>> {
+        self.shards
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for ShardedSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedSubscriber")
+            .field("shards", &self.shards)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::ShardedBus;
+    use std::sync::mpsc::TryRecvError;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn items_are_routed_by_key() {
+        let bus = Arc::new(ShardedBus::new(2, 100, |item: &u64| *item));
+        let even = bus.subscribe_shard(0);
+        let all = bus.subscribe_all();
+        let publishers: Vec<_> = (0..2)
+            .map(|parity| {
+                let bus = bus.clone();
+                thread::spawn(move || {
+                    for i in 0..10 {
+                        bus.broadcast(i * 2 + parity).unwrap();
+                    }
+                })
+            })
+            .collect();
+        publishers
+            .into_iter()
+            .for_each(|publisher| publisher.join().unwrap());
+
+        let received: Vec<_> = even.map(|item| *item).collect();
+        assert_eq!(received, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+        let mut received: Vec<_> = (0..20).map(|_| *all.try_recv().unwrap()).collect();
+        received.sort_unstable();
+        assert_eq!(received, (0..20).collect::<Vec<_>>());
+        assert_eq!(all.try_recv(), Err(TryRecvError::Empty));
+
+        drop(bus);
+        assert_eq!(all.try_recv(), Err(TryRecvError::Disconnected));
+    }
+}
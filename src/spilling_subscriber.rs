@@ -0,0 +1,211 @@
+use crate::ring_buffer::TryRecvError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Error returned by `SpillingSubscriber::try_recv`.
+#[derive(Debug)]
+pub enum SpillRecvError {
+    /// No item is available at the moment.
+    Empty,
+    /// The publisher is gone and every item, spilled or not, has been received.
+    Disconnected,
+    /// Spilling or replaying items failed.
+    Io(io::Error),
+}
+
+impl From<TryRecvError> for SpillRecvError {
+    fn from(error: TryRecvError) -> Self {
+        match error {
+            TryRecvError::Empty => SpillRecvError::Empty,
+            TryRecvError::Disconnected => SpillRecvError::Disconnected,
+        }
+    }
+}
+
+impl From<io::Error> for SpillRecvError {
+    fn from(error: io::Error) -> Self {
+        SpillRecvError::Io(error)
+    }
+}
+
+impl From<bincode::Error> for SpillRecvError {
+    fn from(error: bincode::Error) -> Self {
+        match *error {
+            bincode::ErrorKind::Io(error) => SpillRecvError::Io(error),
+            error => SpillRecvError::Io(io::Error::new(io::ErrorKind::InvalidData, error)),
+        }
+    }
+}
+
+impl fmt::Display for SpillRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpillRecvError::Empty => write!(f, "receiving on an empty channel"),
+            SpillRecvError::Disconnected => write!(f, "receiving on a closed channel"),
+            SpillRecvError::Io(error) => write!(f, "spilling failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SpillRecvError {}
+
+/// Subscriber wrapper which, instead of being lapped, spills the pending items to a
+/// temporary file and replays them before receiving from the bus again.
+///
+/// Lagging is checked on every receive, so the subscriber has to keep receiving,
+/// only slower than the publisher publishes. Items overwritten in between two
+/// receives are still lost.
+pub struct SpillingSubscriber<T, S: SwapSlot<T, Shared = Arc<T>>> {
+    subscriber: Subscriber<T, S>,
+    /// Number of pending items from which they are spilled
+    high_water: usize,
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    /// Number of items in the file which have not been replayed yet
+    spilled: usize,
+}
+
+impl<T, S> SpillingSubscriber<T, S>
+where
+    T: Serialize + DeserializeOwned,
+    S: SwapSlot<T, Shared = Arc<T>>,
+{
+    /// Wraps the subscriber, spilling once three quarters of the queue are pending.
+    pub fn new(subscriber: Subscriber<T, S>) -> io::Result<Self> {
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "bus_queue-spill-{}-{}",
+            std::process::id(),
+            FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let writer = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(&path)?;
+        let reader = File::open(&path)?;
+        let len = subscriber.len();
+        Ok(Self {
+            subscriber,
+            high_water: std::cmp::max(len - len / 4, 1),
+            path,
+            writer: BufWriter::new(writer),
+            reader: BufReader::new(reader),
+            spilled: 0,
+        })
+    }
+
+    /// Sets the number of pending items from which they are spilled, at most the queue size.
+    pub fn set_high_water(&mut self, high_water: usize) {
+        self.high_water = std::cmp::min(std::cmp::max(high_water, 1), self.subscriber.len());
+    }
+
+    /// Receives the next item, spilling the pending ones first if the subscriber lags behind.
+    /// Spilled items are received before the ones still in the queue.
+    pub fn try_recv(&mut self) -> Result<Arc<T>, SpillRecvError> {
+        self.spill_if_lagging()?;
+        if self.spilled > 0 {
+            return self.replay();
+        }
+        Ok(self.subscriber.try_recv()?)
+    }
+
+    /// Spills the pending items if there are at least high water of them,
+    /// returning how many were spilled.
+    pub fn spill_if_lagging(&mut self) -> Result<usize, SpillRecvError> {
+        let pending = self.subscriber.buffer.pending(self.subscriber.ri.get());
+        if pending < self.high_water {
+            return Ok(0);
+        }
+        let items = self.subscriber.drain();
+        for item in &items {
+            bincode::serialize_into(&mut self.writer, &**item)?;
+        }
+        self.writer.flush()?;
+        self.spilled += items.len();
+        Ok(items.len())
+    }
+
+    /// Returns the number of spilled items which have not been received yet.
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    fn replay(&mut self) -> Result<Arc<T>, SpillRecvError> {
+        let item = bincode::deserialize_from(&mut self.reader)?;
+        self.spilled -= 1;
+        if self.spilled == 0 {
+            // Everything has been replayed, start over with an empty file.
+            self.writer.get_ref().set_len(0)?;
+            self.reader.seek(SeekFrom::Start(0))?;
+        }
+        Ok(Arc::new(item))
+    }
+}
+
+impl<T, S: SwapSlot<T, Shared = Arc<T>>> Drop for SpillingSubscriber<T, S> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl<T, S: SwapSlot<T, Shared = Arc<T>>> fmt::Debug for SpillingSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpillingSubscriber")
+            .field("subscriber", &self.subscriber)
+            .field("high_water", &self.high_water)
+            .field("path", &self.path)
+            .field("spilled", &self.spilled)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SpillRecvError, SpillingSubscriber};
+    use crate::flavors::arc_swap::bounded;
+
+    #[test]
+    fn spills_instead_of_lapping() {
+        let (sender, receiver) = bounded::<String>(4);
+        let mut receiver = SpillingSubscriber::new(receiver).unwrap();
+
+        let mut received = Vec::new();
+        for i in 0..20 {
+            sender.broadcast(i.to_string()).unwrap();
+            // Receives one item for every two published.
+            if i % 2 == 0 {
+                received.push((*receiver.try_recv().unwrap()).clone());
+            }
+        }
+        drop(sender);
+        loop {
+            match receiver.try_recv() {
+                Ok(item) => received.push((*item).clone()),
+                Err(SpillRecvError::Disconnected) => break,
+                Err(error) => panic!("{}", error),
+            }
+        }
+
+        let expected = (0..20).map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(received, expected);
+        assert_eq!(receiver.spilled(), 0);
+    }
+
+    #[test]
+    fn removes_file_on_drop() {
+        let (_sender, receiver) = bounded::<u32>(4);
+        let receiver = SpillingSubscriber::new(receiver).unwrap();
+        let path = receiver.path.clone();
+        assert!(path.exists());
+        drop(receiver);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::async_subscriber::AsyncSubscriber;
+use crate::swap_slot::SwapSlot;
+use futures_core::{
+    task::{self, Poll},
+    Stream,
+};
+use serde::Serialize;
+use std::fmt;
+use std::pin::Pin;
+
+impl<T: Serialize, S: SwapSlot<T>> AsyncSubscriber<T, S> {
+    /// Turns the stream into one yielding the items as Server-Sent Events, each one
+    /// serialized to JSON on a `data` line, ready to be used as a chunked HTTP body.
+    ///
+    /// ```rust
+    /// use bus_queue::flavors::arc_swap::async_bounded;
+    /// use futures::executor::block_on;
+    /// use futures::{SinkExt, StreamExt};
+    ///
+    /// let (mut publisher, subscriber) = async_bounded(10);
+    /// let mut events = subscriber.into_sse().with_event("price");
+    /// block_on(publisher.send(42)).unwrap();
+    ///
+    /// let event = block_on(events.next()).unwrap().unwrap();
+    /// assert_eq!(event, b"event: price\ndata: 42\n\n");
+    /// ```
+    pub fn into_sse(self) -> SseStream<T, S> {
+        SseStream {
+            subscriber: self,
+            event: None,
+        }
+    }
+}
+
+/// Stream of Server-Sent Events frames, returned by `AsyncSubscriber::into_sse`.
+///
+/// Frames are plain bytes so that the stream can be wrapped into the body type of
+/// any HTTP framework. Items failing to serialize are yielded as errors.
+pub struct SseStream<T, S: SwapSlot<T>> {
+    subscriber: AsyncSubscriber<T, S>,
+    /// Event type of the frames, the default `message` if None
+    event: Option<String>,
+}
+
+impl<T, S: SwapSlot<T>> SseStream<T, S> {
+    /// Sets the event type of the frames, for clients listening to it by name.
+    pub fn with_event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_owned());
+        self
+    }
+
+    /// Returns the underlying subscriber.
+    pub fn into_inner(self) -> AsyncSubscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T: Serialize, S: SwapSlot<T>> Stream for SseStream<T, S> {
+    type Item = Result<Vec<u8>, serde_json::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = match futures_core::ready!(Pin::new(&mut self.subscriber).poll_next(cx)) {
+            Some(item) => item,
+            None => return Poll::Ready(None),
+        };
+        let mut frame = Vec::new();
+        if let Some(event) = &self.event {
+            frame.extend_from_slice(b"event: ");
+            frame.extend_from_slice(event.as_bytes());
+            frame.push(b'\n');
+        }
+        frame.extend_from_slice(b"data: ");
+        // Compact JSON holds no newline, the item fits a single data line.
+        if let Err(error) = serde_json::to_writer(&mut frame, &*item) {
+            return Poll::Ready(Some(Err(error)));
+        }
+        frame.extend_from_slice(b"\n\n");
+        Poll::Ready(Some(Ok(frame)))
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for SseStream<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SseStream")
+            .field("subscriber", &self.subscriber)
+            .field("event", &self.event)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::async_bounded;
+    use futures::executor::block_on;
+    use futures::{SinkExt, StreamExt};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Quote {
+        symbol: &'static str,
+        price: u32,
+    }
+
+    #[test]
+    fn items_are_framed_as_events() {
+        let (mut publisher, subscriber) = async_bounded(10);
+        let events = subscriber.into_sse();
+        block_on(publisher.send(Quote {
+            symbol: "ACME",
+            price: 42,
+        }))
+        .unwrap();
+        drop(publisher);
+
+        let frames: Vec<_> = block_on(events.map(Result::unwrap).collect());
+        assert_eq!(
+            frames,
+            vec![b"data: {\"symbol\":\"ACME\",\"price\":42}\n\n".to_vec()]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::async_publisher::AsyncPublisher;
+use crate::async_subscriber::AsyncSubscriber;
+use crate::ring_buffer::SendError;
+use crate::swap_slot::SwapSlot;
+use futures_core::{
+    future::Future,
+    task::{self, Poll},
+    Stream,
+};
+use futures_sink::Sink;
+use std::pin::Pin;
+
+/// Extension trait used to fan out any Stream to many subscribers through a new bus.
+pub trait StreamExt2: Stream + Sized {
+    /// Creates a bus of the given size fed by this stream.
+    ///
+    /// Nothing is spawned, the returned driver future has to be polled for the items
+    /// to be published. It resolves once the stream ends, closing the bus.
+    fn into_bus<S: SwapSlot<Self::Item>>(
+        self,
+        size: usize,
+    ) -> (
+        AsyncPublisherDriver<Self, S>,
+        AsyncSubscriber<Self::Item, S>,
+    ) {
+        let (publisher, subscriber) = crate::async_bounded(size);
+        (
+            AsyncPublisherDriver {
+                stream: Box::pin(self),
+                publisher,
+            },
+            subscriber,
+        )
+    }
+}
+
+impl<St: Stream> StreamExt2 for St {}
+
+/// Future forwarding a stream into a bus, returned by `StreamExt2::into_bus`.
+pub struct AsyncPublisherDriver<St: Stream, S: SwapSlot<St::Item>> {
+    stream: Pin<Box<St>>,
+    publisher: AsyncPublisher<St::Item, S>,
+}
+
+impl<St: Stream, S: SwapSlot<St::Item>> Future for AsyncPublisherDriver<St, S> {
+    type Output = Result<(), SendError<St::Item>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    Pin::new(&mut this.publisher).start_send(item)?;
+                    futures_core::ready!(Pin::new(&mut this.publisher).poll_flush(cx))?;
+                }
+                Poll::Ready(None) => return Pin::new(&mut this.publisher).poll_close(cx),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<St: Stream, S: SwapSlot<St::Item>> std::fmt::Debug for AsyncPublisherDriver<St, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncPublisherDriver").finish()
+    }
+}
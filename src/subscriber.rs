@@ -1,9 +1,24 @@
 use crate::atomic_counter::AtomicCounter;
-use crate::ring_buffer::{RingBuffer, TryRecvError};
+use crate::fence::{FenceToken, Fences};
+use crate::histogram::{LatencyHistogram, LatencyRecorder};
+#[cfg(feature = "ordering-checks")]
+use crate::ordering::OrderingCheck;
+#[cfg(feature = "blocking")]
+use crate::parker::{Parker, ThreadParker, Wakeup};
+use crate::position::{Position, RestoreError};
+use crate::publisher::Fallible;
+use crate::quota::Quota;
+use crate::reaper::Activity;
+use crate::ring_buffer::{ChannelState, Receive, RingBuffer, Shared, TryRecvError, With};
+#[cfg(feature = "blocking")]
+use crate::ring_buffer::{RecvError, RecvTimeoutError};
 use crate::swap_slot::SwapSlot;
-use std::sync::Arc;
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-#[derive(Debug)]
 pub struct Subscriber<T, S: SwapSlot<T>> {
     /// Shared reference to the channel
     pub(super) buffer: Arc<RingBuffer<T, S>>,
@@ -11,14 +26,58 @@ pub struct Subscriber<T, S: SwapSlot<T>> {
     pub(super) ri: AtomicCounter,
     /// how many items should the receiver skip when the writer overflows
     pub(super) skip_items: usize,
+    /// only items whose sequence is a multiple of this are received
+    pub(super) sample_every: usize,
+    /// remainder of the sequence of the sampled items
+    pub(super) sample_offset: usize,
+    /// true if this receiver is asking the publisher to slow down
+    pub(super) slowing_down: bool,
+    /// how many times the reader was moved forward because the writer overflowed
+    pub(super) laps: AtomicCounter,
+    /// activity tracked when inactive subscribers are reaped
+    pub(super) activity: Option<Arc<Activity>>,
+    /// publish to receive latencies, if recorded
+    pub(super) latency: Option<Box<LatencyRecorder>>,
+    /// items per second this receiver may receive, if limited
+    pub(super) quota: Option<Quota>,
+    /// origins whose items are skipped, see `exclude_origin`
+    pub(super) excluded_origins: Vec<usize>,
+    /// threads waiting for this receiver to get past a fence token
+    pub(super) fences: Fences,
+    /// read index shared with the bus, if it has an overflow policy
+    pub(super) cursor: Option<Arc<AtomicCounter>>,
+    /// true once the receiver polled the bus, see `Publisher::start_barrier`
+    pub(super) polled: AtomicBool,
+    /// item held back by `try_recv_lagged` after reporting a lag
+    pub(super) lagged_item: Mutex<Option<S::Shared>>,
+    /// check of the order items are received in
+    #[cfg(feature = "ordering-checks")]
+    pub(super) ordering: OrderingCheck,
 }
 
+// The lagged item is never pinned.
+impl<T, S: SwapSlot<T>> Unpin for Subscriber<T, S> {}
+
 impl<T, S: SwapSlot<T>> From<Arc<RingBuffer<T, S>>> for Subscriber<T, S> {
     fn from(arc_channel: Arc<RingBuffer<T, S>>) -> Self {
         Self {
+            activity: arc_channel.register_activity(),
+            quota: arc_channel.read_quota(),
+            cursor: arc_channel.register_cursor(0),
             buffer: arc_channel,
             skip_items: 0,
+            sample_every: 1,
+            sample_offset: 0,
+            slowing_down: false,
             ri: AtomicCounter::new(0),
+            laps: AtomicCounter::new(0),
+            latency: None,
+            excluded_origins: Vec::new(),
+            fences: Fences::new(),
+            polled: AtomicBool::new(false),
+            lagged_item: Mutex::new(None),
+            #[cfg(feature = "ordering-checks")]
+            ordering: OrderingCheck::default(),
         }
     }
 }
@@ -30,16 +89,667 @@ impl<T, S: SwapSlot<T>> Subscriber<T, S> {
         self.buffer.is_available()
     }
 
-    /// Sets the skip_items attribute of the reader to a max value being the queue size.
-    #[allow(dead_code)]
-    pub fn set_skip_items(&mut self, skip_items: usize) {
-        self.skip_items = std::cmp::min(skip_items, self.buffer.len() - 1);
+    /// Sets the number of items the reader skips when lapped, lowered to the most the bus
+    /// allows, its size minus one. Returns the number actually set.
+    pub fn set_skip_items(&mut self, skip_items: usize) -> usize {
+        match self.set_skip_items_with(skip_items, SkipMode::Saturating) {
+            Ok(skip_items) => skip_items,
+            Err(error) => error.max,
+        }
+    }
+
+    /// Sets the number of items the reader skips when lapped, handling a number larger
+    /// than the bus allows according to the mode. Returns the number actually set.
+    pub fn set_skip_items_with(
+        &mut self,
+        skip_items: usize,
+        mode: SkipMode,
+    ) -> Result<usize, SkipItemsError> {
+        self.skip_items = mode.apply(skip_items, self.buffer.len() - 1)?;
+        Ok(self.skip_items)
+    }
+
+    /// Returns the number of items the reader skips when lapped.
+    pub fn skip_items(&self) -> usize {
+        self.skip_items
+    }
+
+    /// Makes the receiver only receive every nth published item.
+    ///
+    /// Items are picked by their sequence, so that all receivers sampling
+    /// with the same n receive the same items.
+    pub fn set_sample_every(&mut self, n: usize) {
+        self.sample_every = std::cmp::max(n, 1);
+        self.sample_offset = 0;
+    }
+
+    /// Splits the receiver into n receivers dividing the items between them,
+    /// item k (by sequence) being received only by the receiver k % n.
+    ///
+    /// Replaces any sampling set with `set_sample_every`.
+    pub fn split_round_robin(mut self, n: usize) -> Vec<Self> {
+        let n = std::cmp::max(n, 1);
+        let mut handles: Vec<Self> = (1..n)
+            .map(|offset| {
+                let mut handle = self.clone();
+                handle.sample_every = n;
+                handle.sample_offset = offset;
+                handle
+            })
+            .collect();
+        self.sample_every = n;
+        self.sample_offset = 0;
+        handles.insert(0, self);
+        handles
+    }
+
+    /// Makes the receiver skip the items broadcast by publishers with the origin `id`,
+    /// see `Publisher::set_origin`, e.g. a component's own broadcasts.
+    ///
+    /// Nothing is skipped unless the bus was built with `Builder::origin_tags`.
+    pub fn exclude_origin(&mut self, id: usize) {
+        if !self.excluded_origins.contains(&id) {
+            self.excluded_origins.push(id);
+        }
+    }
+
+    /// Checks if the item at `seq` is one of the receiver's samples and was not broadcast
+    /// by an excluded origin.
+    fn is_accepted(&self, seq: usize) -> bool {
+        seq % self.sample_every == self.sample_offset && self.is_origin_accepted(seq)
+    }
+
+    /// Checks if the item at `seq` was not broadcast by an excluded origin. Items whose
+    /// origin is no longer known are accepted, receiving them fails anyway.
+    fn is_origin_accepted(&self, seq: usize) -> bool {
+        self.excluded_origins.is_empty()
+            || !matches!(self.buffer.origin_of(seq), Some(origin) if self.excluded_origins.contains(&origin))
+    }
+
+    /// Asks the publisher to slow down until `release_slowdown` is called or the receiver is
+    /// dropped. Only async publishers configured with `set_max_slowdown` take it into account.
+    pub fn request_slowdown(&mut self) {
+        if !self.slowing_down {
+            self.slowing_down = true;
+            self.buffer.request_slowdown();
+        }
+    }
+
+    /// Withdraws the request made by `request_slowdown`.
+    pub fn release_slowdown(&mut self) {
+        if self.slowing_down {
+            self.slowing_down = false;
+            self.buffer.release_slowdown();
+        }
     }
 
     /// Receives some atomic reference to an object if queue is not empty, or None if it is. Never
     /// Blocks
-    pub fn try_recv(&self) -> Result<Arc<T>, TryRecvError> {
-        self.buffer.try_recv(&self.ri, self.skip_items)
+    pub fn try_recv(&self) -> Result<S::Shared, TryRecvError> {
+        self.try_recv_sequenced().map(|(_, item)| item)
+    }
+
+    /// Receives an item like `try_recv`, along with its sequence: the one stamped from the
+    /// bus's `SequenceSource` if it has one, its position in the bus otherwise.
+    pub fn try_recv_sequenced(&self) -> Result<(u64, S::Shared), TryRecvError> {
+        let mut unbounded = usize::MAX;
+        self.try_recv_within(&mut unbounded)
+            .map(|(sequence, item, _)| (sequence, item))
+    }
+
+    /// Receives an item like `try_recv`, telling whether items were lost to the publisher
+    /// lapping the receiver just before it.
+    ///
+    /// The count is taken while receiving, unlike the one of `lap_count`, which a publish
+    /// could change before it is read.
+    pub fn try_recv_status(&self) -> Result<RecvStatus<S::Shared>, TryRecvError> {
+        let mut unbounded = usize::MAX;
+        let (_, item, skipped) = self.try_recv_within(&mut unbounded)?;
+        Ok(RecvStatus::new(item, skipped))
+    }
+
+    /// Receives an item like `try_recv`, but returns `Lagged` with the number of items
+    /// lost when the publisher lapped the receiver, so that it can resync, e.g. by
+    /// requesting a snapshot. The next call returns the oldest item retained then.
+    ///
+    /// ```rust
+    /// use bus_queue::flavors::arc_swap::bounded;
+    /// use bus_queue::TryRecvLaggedError;
+    ///
+    /// let (tx, rx) = bounded(2);
+    /// for i in 0..5 {
+    ///     tx.broadcast(i).unwrap();
+    /// }
+    /// assert_eq!(rx.try_recv_lagged(), Err(TryRecvLaggedError::Lagged(3)));
+    /// assert_eq!(*rx.try_recv_lagged().unwrap(), 3);
+    /// ```
+    pub fn try_recv_lagged(&self) -> Result<S::Shared, TryRecvLaggedError> {
+        let mut lagged_item = self.lagged_item.lock().unwrap();
+        if let Some(item) = lagged_item.take() {
+            return Ok(item);
+        }
+        match self.try_recv_status()? {
+            RecvStatus::Fresh(item) => Ok(item),
+            RecvStatus::AfterSkip { item, skipped } => {
+                *lagged_item = Some(item);
+                Err(TryRecvLaggedError::Lagged(skipped as u64))
+            }
+        }
+    }
+
+    /// Receives like `try_recv_sequenced`, loading at most `budget` slots, see
+    /// `RingBuffer::try_recv_within`. Also returns the number of items lost to a lap.
+    pub(crate) fn try_recv_within(
+        &self,
+        budget: &mut usize,
+    ) -> Result<(u64, S::Shared, usize), TryRecvError> {
+        self.receive_within(budget, &mut Shared)
+    }
+
+    /// Receives like `try_recv_within`, the item being read by `receive`.
+    fn receive_within<V: Receive<T, S>>(
+        &self,
+        budget: &mut usize,
+        receive: &mut V,
+    ) -> Result<(u64, V::Output, usize), TryRecvError> {
+        self.touch();
+        if self.is_over_quota() {
+            return Err(TryRecvError::Empty);
+        }
+        let received = self.recv_sampled(budget, receive);
+        // Moved even when nothing is received, e.g. past the items of excluded origins.
+        self.fences.notify();
+        let received = received?;
+        self.check_ordering(self.ri.get());
+        self.charge_quota(1);
+        self.sync_cursor();
+        Ok(received)
+    }
+
+    /// Receives the next sampled item, if sampling.
+    fn recv_sampled<V: Receive<T, S>>(
+        &self,
+        budget: &mut usize,
+        receive: &mut V,
+    ) -> Result<(u64, V::Output, usize), TryRecvError> {
+        if self.sample_every > 1 {
+            let ri = self.ri.get();
+            let sampled = ri.wrapping_add(
+                (self.sample_offset + self.sample_every - ri % self.sample_every)
+                    % self.sample_every,
+            );
+            // Jump straight to the next sampled item without loading the ones in between.
+            if sampled.wrapping_sub(ri) >= self.buffer.pending(ri) {
+                if self.buffer.is_available() {
+                    return Err(TryRecvError::Empty);
+                } else {
+                    return Err(TryRecvError::Disconnected);
+                }
+            }
+            self.ri.set(sampled);
+        }
+        self.recv_lapping(budget, receive)
+    }
+
+    /// Receives an item like `try_recv`, along with the metadata it was published with by
+    /// `Publisher::broadcast_with_meta`, if it is of type M.
+    ///
+    /// The metadata is None as well if the item was overwritten before it could be read,
+    /// e.g. for pinned items received after being lapped.
+    pub fn try_recv_with_meta<M: Copy + Send + 'static>(
+        &self,
+    ) -> Result<(S::Shared, Option<M>), TryRecvError> {
+        let item = self.try_recv()?;
+        let meta = self.buffer.meta_of(self.ri.get().wrapping_sub(1));
+        Ok((item, meta))
+    }
+
+    /// Runs `f` on the next item instead of returning it, borrowing the item only while
+    /// `f` runs. Saves the ref count traffic of `try_recv` for consumers that only read
+    /// a field or two of each item.
+    pub fn try_recv_with<R, F: FnOnce(&T) -> R>(&self, f: F) -> Result<R, TryRecvError> {
+        let mut unbounded = usize::MAX;
+        self.receive_within(&mut unbounded, &mut With(Some(f)))
+            .map(|(_, received, _)| received)
+    }
+
+    /// Receives every retained item that has not been received yet at once.
+    ///
+    /// The pending range is captured once, so the returned batch is consistent
+    /// even while the publisher keeps broadcasting.
+    ///
+    /// Nothing is received while over the quota set with `Builder::read_quota`,
+    /// a batch exceeding it delays the next receive accordingly.
+    pub fn drain(&self) -> Vec<S::Shared> {
+        self.touch();
+        if self.is_over_quota() {
+            return Vec::new();
+        }
+        let ri = self.ri.get();
+        let mut items = Vec::new();
+        let mut lapped = ri;
+        while let Some((seq, _, item)) = self.buffer.take_lapped_pin(lapped) {
+            lapped = seq.wrapping_add(1);
+            items.push((seq, item));
+        }
+        let drained = self.buffer.drain(&self.ri, self.skip_items);
+        drained
+            .iter()
+            .for_each(|(seq, _)| self.buffer.mark_pin_received(*seq));
+        items.extend(drained);
+        items
+            .iter()
+            .for_each(|(seq, _)| self.check_ordering(seq.wrapping_add(1)));
+        if matches!(items.first(), Some((seq, _)) if *seq != ri) {
+            self.laps.inc();
+        }
+        let items: Vec<_> = items
+            .into_iter()
+            .filter(|(seq, _)| {
+                seq % self.sample_every == self.sample_offset && self.is_origin_accepted(*seq)
+            })
+            .map(|(seq, item)| {
+                self.record_latency_of(seq);
+                item
+            })
+            .collect();
+        self.charge_quota(items.len());
+        self.fences.notify();
+        self.sync_cursor();
+        items
+    }
+
+    /// Checks if the receiver received every item published before the token,
+    /// or was lapped past them.
+    pub fn is_past(&self, token: FenceToken) -> bool {
+        self.ri.get().wrapping_sub(token.sequence) as isize >= 0
+    }
+
+    /// Blocks the current thread until the receiver gets past the token, i.e. until
+    /// it received every item published before `Publisher::fence_token` was called,
+    /// while an other thread receives through it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token was handed out by the publisher of an other bus.
+    #[cfg(feature = "blocking")]
+    pub fn wait_for_token(&self, token: FenceToken) {
+        assert_eq!(token.bus_id, self.bus_id(), "fence token of an other bus");
+        while !self.is_past(token) {
+            let listener = self.fences.register();
+            if !self.is_past(token) {
+                Wakeup::new(listener).wait();
+            }
+            self.fences.unregister();
+        }
+    }
+
+    /// Returns the retained items published at or after `since` without receiving them.
+    ///
+    /// Always empty unless the bus was built with `Builder::timestamps`.
+    pub fn retained_since(&self, since: Instant) -> Vec<S::Shared> {
+        self.buffer.retained_since(since)
+    }
+
+    /// Receives the next item, blocking the current thread until one is published.
+    /// Returns an error once the publisher is gone and every item has been received.
+    ///
+    /// Only the threads blocked since the previous broadcast are woken by the next one.
+    #[cfg(feature = "blocking")]
+    pub fn recv(&self) -> Result<S::Shared, RecvError> {
+        self.recv_with_parker(&ThreadParker)
+    }
+
+    /// Receives the next item like `recv`, blocking through the given parker.
+    #[cfg(feature = "blocking")]
+    pub fn recv_with_parker<P: Parker + ?Sized>(&self, parker: &P) -> Result<S::Shared, RecvError> {
+        self.recv_sequenced_with_parker(parker)
+            .map(|(_, item)| item)
+    }
+
+    /// Receives the next item like `recv`, along with its sequence.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn recv_sequenced_with_parker<P: Parker + ?Sized>(
+        &self,
+        parker: &P,
+    ) -> Result<(u64, S::Shared), RecvError> {
+        loop {
+            match self.try_recv_sequenced() {
+                Ok(item) => return Ok(item),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+            match self.park_for_item(parker, None) {
+                Ok(Some(item)) => return Ok(item),
+                Ok(None) => {}
+                Err(_) => return Err(RecvError),
+            }
+        }
+    }
+
+    /// Waits through the parker for an item to receive after `try_recv` found none, until
+    /// the deadline if any. Returns the item if one was published before the waiter was
+    /// registered, None once woken or once the read quota allows receiving again.
+    #[cfg(feature = "blocking")]
+    fn park_for_item<P: Parker + ?Sized>(
+        &self,
+        parker: &P,
+        deadline: Option<Instant>,
+    ) -> Result<Option<(u64, S::Shared)>, RecvTimeoutError> {
+        let wakeup = Wakeup::new(self.buffer.register_waiter());
+        // Received again in case an item was published before the waiter was registered.
+        // Empty does not mean nothing is pending, e.g. when sampling or excluding origins.
+        let parked = match self.try_recv_sequenced() {
+            Ok(item) => Ok(Some(item)),
+            Err(TryRecvError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => {
+                let refilled = self
+                    .over_quota_backoff()
+                    .map(|backoff| Instant::now() + backoff);
+                match (refilled, deadline) {
+                    // Closed, the items left are received without waiting.
+                    (None, _) if !self.buffer.is_available() => Ok(None),
+                    (Some(refilled), deadline) if !matches!(deadline, Some(deadline) if deadline <= refilled) =>
+                    {
+                        // Woken or not, the quota allows receiving by then.
+                        parker.park_deadline(wakeup, refilled);
+                        Ok(None)
+                    }
+                    (_, Some(deadline)) => {
+                        if parker.park_deadline(wakeup, deadline) {
+                            Ok(None)
+                        } else {
+                            Err(RecvTimeoutError::Timeout)
+                        }
+                    }
+                    (_, None) => {
+                        parker.park(wakeup);
+                        Ok(None)
+                    }
+                }
+            }
+        };
+        self.buffer.unregister_waiter();
+        parked
+    }
+
+    /// Receives the next item like `recv`, giving up with `Timeout` once nothing
+    /// was published for the timeout.
+    #[cfg(feature = "blocking")]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<S::Shared, RecvTimeoutError> {
+        self.recv_timeout_with_parker(timeout, &ThreadParker)
+    }
+
+    /// Receives the next item like `recv_timeout`, blocking through the given parker.
+    #[cfg(feature = "blocking")]
+    pub fn recv_timeout_with_parker<P: Parker + ?Sized>(
+        &self,
+        timeout: Duration,
+        parker: &P,
+    ) -> Result<S::Shared, RecvTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.recv_deadline_with_parker(deadline, parker),
+            None => self
+                .recv_with_parker(parker)
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        }
+    }
+
+    /// Receives the next item like `recv`, giving up with `Timeout` once the deadline
+    /// is reached, e.g. the start of the next frame of a loop running at a fixed rate.
+    #[cfg(feature = "blocking")]
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<S::Shared, RecvTimeoutError> {
+        self.recv_deadline_with_parker(deadline, &ThreadParker)
+    }
+
+    /// Receives the next item like `recv_deadline`, blocking through the given parker.
+    /// The deadline is taken as reached once the parker says so.
+    #[cfg(feature = "blocking")]
+    pub fn recv_deadline_with_parker<P: Parker + ?Sized>(
+        &self,
+        deadline: Instant,
+        parker: &P,
+    ) -> Result<S::Shared, RecvTimeoutError> {
+        loop {
+            match self.try_recv() {
+                Ok(item) => return Ok(item),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+            if Instant::now() >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            match self.park_for_item(parker, Some(deadline)) {
+                Ok(Some((_, item))) => return Ok(item),
+                Ok(None) => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Returns how long to wait before receiving fits the quota set with
+    /// `Builder::read_quota`, None if it does.
+    ///
+    /// Over the quota, `try_recv` returns `Empty` with items pending, while blocking
+    /// and async receives wait for the quota to allow receiving them.
+    pub fn quota_backoff(&self) -> Option<Duration> {
+        self.quota.as_ref().and_then(Quota::backoff)
+    }
+
+    /// Returns how long to wait before the quota allows receiving the pending items,
+    /// None if there are none or it does.
+    pub(crate) fn over_quota_backoff(&self) -> Option<Duration> {
+        if self.quota.is_some() && !self.is_empty() {
+            self.quota_backoff()
+        } else {
+            None
+        }
+    }
+
+    /// Checks if there are items to receive but the quota does not allow receiving them.
+    fn is_over_quota(&self) -> bool {
+        self.over_quota_backoff().is_some()
+    }
+
+    fn charge_quota(&self, n: usize) {
+        if let Some(quota) = &self.quota {
+            quota.charge(n);
+        }
+    }
+
+    /// Shares the read index with the bus, if it has an overflow policy.
+    pub(crate) fn sync_cursor(&self) {
+        if let Some(cursor) = &self.cursor {
+            cursor.set(self.ri.get());
+        }
+    }
+
+    /// Records that the receiver is active, if inactive receivers are reaped.
+    fn touch(&self) {
+        if !self.polled.load(Ordering::Relaxed) && !self.polled.swap(true, Ordering::Relaxed) {
+            self.buffer.mark_ready();
+        }
+        if let Some(activity) = &self.activity {
+            self.buffer.touch(activity);
+        }
+    }
+
+    /// Checks if the receiver was marked as stale for not receiving, see
+    /// `Publisher::reap_inactive`. It is no longer stale once it receives again.
+    pub fn is_stale(&self) -> bool {
+        matches!(&self.activity, Some(activity) if activity.is_stale())
+    }
+
+    /// Returns the number of items `try_recv` would return if nothing else was published.
+    fn remaining(&self) -> usize {
+        let ri = self.ri.get();
+        let wi = self.buffer.write_index();
+        if ri == wi {
+            return 0;
+        }
+        let oldest = self.buffer.clamp_sequence(ri);
+        // A lapped or evicted reader skips items on top of the missed ones.
+        let start = if oldest == ri {
+            ri
+        } else {
+            std::cmp::min(oldest.wrapping_add(self.skip_items), wi.wrapping_sub(1))
+        };
+        // Sampled sequences before n.
+        let sampled = |n: usize| {
+            n / self.sample_every + usize::from(n % self.sample_every > self.sample_offset)
+        };
+        sampled(wi) - sampled(start)
+    }
+
+    /// Turns the receiver into an ExactSizeIterator over the remaining items,
+    /// or gives it back if the publisher has not been closed yet.
+    ///
+    /// The items are received at once, see `drain`, so that lapped pinned items and
+    /// items of excluded origins are accounted for.
+    #[allow(clippy::result_large_err)]
+    pub fn into_remaining(self) -> Result<Remaining<T, S>, Self> {
+        if self.buffer.is_available() {
+            Err(self)
+        } else {
+            Ok(Remaining {
+                items: self.drain().into_iter(),
+                subscriber: self,
+            })
+        }
+    }
+
+    /// Checks the order of the items received, see `ordering_anomalies`.
+    #[cfg(feature = "ordering-checks")]
+    pub(crate) fn check_ordering(&self, position: usize) {
+        self.ordering.received(position);
+    }
+
+    #[cfg(not(feature = "ordering-checks"))]
+    #[inline]
+    pub(crate) fn check_ordering(&self, _: usize) {}
+
+    /// Returns the number of items this receiver received out of order, e.g. because it
+    /// is shared between threads or because of a faulty custom flavor. Debug builds
+    /// panic on the first one instead.
+    #[cfg(feature = "ordering-checks")]
+    pub fn ordering_anomalies(&self) -> usize {
+        self.ordering.anomalies()
+    }
+
+    /// Receives from the buffer, counting the times the reader gets lapped.
+    fn recv_lapping<V: Receive<T, S>>(
+        &self,
+        budget: &mut usize,
+        receive: &mut V,
+    ) -> Result<(u64, V::Output, usize), TryRecvError> {
+        let ri = self.ri.get();
+        // Items of excluded origins or of other samples are passed over, not lost.
+        let excluded = Cell::new(0);
+        while let Some((seq, sequence, item)) = self.buffer.take_lapped_pin(self.ri.get()) {
+            self.ri.set(seq.wrapping_add(1));
+            if self.is_accepted(seq) {
+                self.laps.inc();
+                let skipped = seq.wrapping_sub(ri).saturating_sub(excluded.get());
+                return Ok((sequence, receive.take(item), skipped));
+            }
+            excluded.set(excluded.get() + 1);
+        }
+        let (sequence, item) = self.buffer.receive_within(
+            &self.ri,
+            self.skip_items,
+            |seq| {
+                let accepted = self.is_accepted(seq);
+                if !accepted {
+                    excluded.set(excluded.get() + 1);
+                }
+                accepted
+            },
+            budget,
+            receive,
+        )?;
+        let skipped = self
+            .ri
+            .get()
+            .wrapping_sub(ri)
+            .wrapping_sub(1)
+            .saturating_sub(excluded.get());
+        if skipped > 0 {
+            self.laps.inc();
+        }
+        self.buffer.mark_pin_received(self.ri.get().wrapping_sub(1));
+        self.record_latency_of(self.ri.get().wrapping_sub(1));
+        Ok((sequence, item, skipped))
+    }
+
+    /// Makes the receiver record the time between the publishing and the receiving of
+    /// every item it receives, see `latency_histogram`. Clones record their own.
+    ///
+    /// Nothing is recorded unless the bus was built with `Builder::timestamps`.
+    pub fn record_latency(&mut self, enabled: bool) {
+        self.latency = if enabled {
+            Some(Box::new(LatencyRecorder::new()))
+        } else {
+            None
+        };
+    }
+
+    /// Returns the latencies recorded so far, None unless enabled with `record_latency`.
+    pub fn latency_histogram(&self) -> Option<LatencyHistogram> {
+        self.latency.as_ref().map(|latency| latency.snapshot())
+    }
+
+    fn record_latency_of(&self, seq: usize) {
+        if let Some(latency) = &self.latency {
+            if let Some(nanos) = self.buffer.latency_of(seq) {
+                latency.record(nanos);
+            }
+        }
+    }
+
+    /// Returns how many times the receiver fell so far behind that it had to skip items.
+    pub fn lap_count(&self) -> usize {
+        self.laps.get()
+    }
+
+    /// Checks if the receiver is behind by more than half of the queue.
+    pub fn is_lagging(&self) -> bool {
+        self.buffer.pending(self.ri.get()) > self.buffer.len() / 2
+    }
+
+    /// Returns the id of the bus, shared by all of its handles: random unless set with
+    /// `Builder::bus_id`.
+    pub fn bus_id(&self) -> u64 {
+        self.buffer.id()
+    }
+
+    /// Saves the position of the receiver, to be restored later with `restore`.
+    pub fn save_position(&self) -> Position {
+        Position {
+            bus_id: self.bus_id(),
+            sequence: self.ri.get() as u64,
+        }
+    }
+
+    /// Moves the receiver to a position saved with `save_position`.
+    ///
+    /// Fails if the position belongs to another bus, or if some of the items after
+    /// the position are no longer retained, moving to the oldest retained item instead.
+    pub fn restore(&mut self, position: Position) -> Result<(), RestoreError> {
+        if position.bus_id != self.bus_id() {
+            return Err(RestoreError::OtherBus);
+        }
+        let sequence = position.sequence as usize;
+        let clamped = self.buffer.clamp_retained(sequence);
+        self.ri.set(clamped);
+        self.fences.notify();
+        self.sync_cursor();
+        #[cfg(feature = "ordering-checks")]
+        self.ordering.reset();
+        if clamped > sequence {
+            Err(RestoreError::NotRetained)
+        } else {
+            Ok(())
+        }
     }
 
     /// Returns the length of the queue.
@@ -47,10 +757,53 @@ impl<T, S: SwapSlot<T>> Subscriber<T, S> {
         self.buffer.len()
     }
 
+    /// Returns the state of the bus as seen by this receiver: `Closing` once the publisher
+    /// is closed while items are left to receive, `Closed` once they are all received.
+    pub fn state(&self) -> ChannelState {
+        if self.buffer.is_available() {
+            ChannelState::Open
+        } else if self.is_empty() {
+            ChannelState::Closed
+        } else {
+            ChannelState::Closing
+        }
+    }
+
     /// Checks if nothings has been published yet.
     pub fn is_empty(&self) -> bool {
         self.buffer.is_sub_empty(self.ri.get())
     }
+
+    /// Checks if `try_recv` may return anything but `Empty`, i.e. if there is something
+    /// left to receive or the publisher is gone, without loading any slot.
+    pub fn has_pending(&self) -> bool {
+        !self.is_empty() || !self.buffer.is_available()
+    }
+}
+
+impl<T, E, S: SwapSlot<Fallible<T, E>>> Subscriber<Fallible<T, E>, S> {
+    /// Receives the next data item or upstream error from a bus with an error lane,
+    /// see `Publisher::broadcast_err`. Async subscribers get the same out of `cloned`.
+    pub fn try_recv_fallible(&self) -> Result<Fallible<T, E>, TryRecvError> {
+        self.try_recv().map(|item| (*item).clone())
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for Subscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("bus_id", &self.bus_id())
+            .field("ri", &self.ri.get())
+            .field("wi", &self.buffer.write_index())
+            .field("size", &self.buffer.len())
+            .field("sub_count", &self.buffer.sub_count())
+            .field("closed", &!self.buffer.is_available())
+            .field("skip_items", &self.skip_items)
+            .field("sample_every", &self.sample_every)
+            .field("sample_offset", &self.sample_offset)
+            .field("laps", &self.laps.get())
+            .finish()
+    }
 }
 
 /// Clone trait is used to create a Receiver which receives messages from the same Sender
@@ -61,13 +814,37 @@ impl<T, S: SwapSlot<T>> Clone for Subscriber<T, S> {
             buffer: self.buffer.clone(),
             ri: AtomicCounter::new(self.ri.get()),
             skip_items: self.skip_items,
+            sample_every: self.sample_every,
+            sample_offset: self.sample_offset,
+            slowing_down: false,
+            laps: AtomicCounter::new(0),
+            activity: self.buffer.register_activity(),
+            latency: self
+                .latency
+                .as_ref()
+                .map(|_| Box::new(LatencyRecorder::new())),
+            quota: self.buffer.read_quota(),
+            excluded_origins: self.excluded_origins.clone(),
+            fences: Fences::new(),
+            cursor: self.buffer.register_cursor(self.ri.get()),
+            polled: AtomicBool::new(false),
+            lagged_item: Mutex::new(None),
+            #[cfg(feature = "ordering-checks")]
+            ordering: OrderingCheck::default(),
         }
     }
 }
 
 impl<T, S: SwapSlot<T>> Drop for Subscriber<T, S> {
     fn drop(&mut self) {
-        self.buffer.dec_sub_count();
+        self.release_slowdown();
+        if self.polled.load(Ordering::Relaxed) {
+            self.buffer.unmark_ready();
+        }
+        match &self.activity {
+            Some(activity) => self.buffer.unregister_activity(activity),
+            None => self.buffer.dec_sub_count(),
+        }
     }
 }
 
@@ -80,9 +857,182 @@ impl<T, S: SwapSlot<T>> PartialEq for Subscriber<T, S> {
 impl<T, S: SwapSlot<T>> Eq for Subscriber<T, S> {}
 
 impl<T, S: SwapSlot<T>> Iterator for Subscriber<T, S> {
-    type Item = Arc<T>;
+    type Item = S::Shared;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.try_recv().ok()
     }
+
+    /// The remaining items are exact once the publisher is closed, unless the receiver
+    /// was lapped, pinned items being received on top of them. Until then more can be
+    /// published, and only receivers that neither skip, sample nor have items evicted by
+    /// a byte budget are sure not to lose some of the remaining ones to overwrites.
+    ///
+    /// Receivers excluding origins or limited by a read quota give no lower bound,
+    /// they can receive none of the remaining items.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if !self.excluded_origins.is_empty() || self.quota.is_some() {
+            return (0, None);
+        }
+        let remaining = self.remaining();
+        if !self.buffer.is_available() {
+            let ri = self.ri.get();
+            if self.buffer.clamp_sequence(ri) == ri {
+                (remaining, Some(remaining))
+            } else {
+                (remaining, None)
+            }
+        } else if self.skip_items == 0 && self.sample_every == 1 && !self.buffer.is_byte_bounded() {
+            (remaining, None)
+        } else {
+            (0, None)
+        }
+    }
+}
+
+/// How a number of items to skip larger than the bus allows is handled,
+/// see `Subscriber::set_skip_items_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipMode {
+    /// The number is lowered to the most the bus allows.
+    Saturating,
+    /// The number is rejected, the previous one staying.
+    Strict,
+}
+
+impl SkipMode {
+    /// Returns the number of items to skip out of the requested one.
+    pub(crate) fn apply(self, requested: usize, max: usize) -> Result<usize, SkipItemsError> {
+        match self {
+            _ if requested <= max => Ok(requested),
+            SkipMode::Saturating => Ok(max),
+            SkipMode::Strict => Err(SkipItemsError { requested, max }),
+        }
+    }
+}
+
+/// Error returned in `SkipMode::Strict` for a number of items to skip larger than
+/// the bus allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkipItemsError {
+    pub requested: usize,
+    /// Most items the bus allows to skip
+    pub max: usize,
+}
+
+impl fmt::Display for SkipItemsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot skip {} items, the bus allows at most {}",
+            self.requested, self.max
+        )
+    }
+}
+
+impl std::error::Error for SkipItemsError {}
+
+/// Item received by `Subscriber::try_recv_status` or `AsyncSubscriber::with_status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecvStatus<I> {
+    /// The item following the previous one received
+    Fresh(I),
+    /// The item received after the receiver was lapped, `skipped` items being lost
+    AfterSkip { item: I, skipped: usize },
+}
+
+impl<I> RecvStatus<I> {
+    pub(crate) fn new(item: I, skipped: usize) -> Self {
+        if skipped == 0 {
+            RecvStatus::Fresh(item)
+        } else {
+            RecvStatus::AfterSkip { item, skipped }
+        }
+    }
+
+    /// Returns the number of items lost just before this one.
+    pub fn skipped(&self) -> usize {
+        match self {
+            RecvStatus::Fresh(_) => 0,
+            RecvStatus::AfterSkip { skipped, .. } => *skipped,
+        }
+    }
+
+    /// Returns the received item.
+    pub fn into_item(self) -> I {
+        match self {
+            RecvStatus::Fresh(item) | RecvStatus::AfterSkip { item, .. } => item,
+        }
+    }
+}
+
+/// Error returned by `Subscriber::try_recv_lagged`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvLaggedError {
+    /// No item is available at the moment.
+    Empty,
+    /// The publisher was dropped and every item has been received.
+    Disconnected,
+    /// The receiver was lapped and the given number of items was overwritten.
+    /// The next call returns the oldest retained item.
+    Lagged(u64),
+}
+
+impl From<TryRecvError> for TryRecvLaggedError {
+    fn from(error: TryRecvError) -> Self {
+        match error {
+            TryRecvError::Empty => TryRecvLaggedError::Empty,
+            TryRecvError::Disconnected => TryRecvLaggedError::Disconnected,
+        }
+    }
+}
+
+impl fmt::Display for TryRecvLaggedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvLaggedError::Empty => write!(f, "channel empty"),
+            TryRecvLaggedError::Disconnected => write!(f, "channel disconnected"),
+            TryRecvLaggedError::Lagged(n) => write!(f, "channel lagged by {}", n),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvLaggedError {}
+
+/// Iterator over the items left once the publisher is closed,
+/// returned by `Subscriber::into_remaining`.
+pub struct Remaining<T, S: SwapSlot<T>> {
+    items: std::vec::IntoIter<S::Shared>,
+    subscriber: Subscriber<T, S>,
+}
+
+impl<T, S: SwapSlot<T>> Remaining<T, S> {
+    /// Returns the underlying receiver, past the remaining items whether they were
+    /// iterated over or not.
+    pub fn into_inner(self) -> Subscriber<T, S> {
+        self.subscriber
+    }
+}
+
+impl<T, S: SwapSlot<T>> Iterator for Remaining<T, S> {
+    type Item = S::Shared;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl<T, S: SwapSlot<T>> ExactSizeIterator for Remaining<T, S> {}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for Remaining<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Remaining")
+            .field("items", &self.items.len())
+            .field("subscriber", &self.subscriber)
+            .finish()
+    }
 }
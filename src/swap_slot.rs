@@ -1,14 +1,37 @@
-use std::sync::Arc;
+use std::ops::Deref;
 
 /// Trait required by implementers of syncing primitives.
 pub trait SwapSlot<T> {
-    /// Creates a new Arc around item and stores it,
-    /// dropping the previously held item's Arc.
+    /// Reference counted pointer handed out to subscribers,
+    /// usually `std::sync::Arc<T>`.
+    type Shared: Clone + Deref<Target = T>;
+
+    /// True if loading and storing never wait on a lock held by an other thread.
+    /// Can be checked at compile time, e.g. to rule out the `rw_lock` flavor on a
+    /// realtime path. Flavors not overriding it do not claim it.
+    const IS_LOCK_FREE: bool = false;
+
+    /// True if `load` may allocate, beyond a one time registration of each thread.
+    /// Flavors not overriding it are assumed to.
+    const LOAD_ALLOCATES: bool = true;
+
+    /// Creates a new Shared pointer around item and stores it,
+    /// dropping the previously held item's pointer.
     fn store(&self, item: T);
 
-    /// Returns a clone of the held Arc,
+    /// Returns a clone of the held Shared pointer,
     /// incrementing the ref count atomically
-    fn load(&self) -> Option<Arc<T>>;
+    fn load(&self) -> Option<Self::Shared>;
+
+    /// Runs `f` on the held item, if any, without handing out a Shared pointer.
+    /// Flavors that can borrow the item in place skip the ref count traffic of `load`.
+    fn load_with<R, F: FnOnce(&T) -> R>(&self, f: F) -> Option<R> {
+        self.load().map(|item| f(&item))
+    }
+
+    /// Drops the held pointer, leaving the slot empty.
+    /// Used to release evicted items before they are overwritten.
+    fn clear(&self);
 
     /// Creates a placeholder without an item.
     /// Due to the queue's internal implementation
@@ -0,0 +1,60 @@
+use crate::async_publisher::AsyncPublisher;
+use crate::event::Event;
+use crate::ring_buffer::RingBuffer;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::sync::Arc;
+
+/// White-box controls over a bus, for deterministic tests of code built around it.
+///
+/// Notifications can be stepped manually and the write index moved around,
+/// as the crate's own tests do through private fields.
+pub struct TestBusControls<T, S: SwapSlot<T>> {
+    buffer: Arc<RingBuffer<T, S>>,
+    event: Arc<Event>,
+}
+
+impl<T, S: SwapSlot<T>> TestBusControls<T, S> {
+    /// Creates controls over the bus the publisher broadcasts to.
+    pub fn new(publisher: &AsyncPublisher<T, S>) -> Self {
+        Self {
+            buffer: publisher.publisher.buffer.clone(),
+            event: publisher.event.clone(),
+        }
+    }
+
+    /// Returns the number of async subscribers waiting to be notified.
+    pub fn pending_listeners(&self) -> usize {
+        self.buffer.listeners()
+    }
+
+    /// Wakes up to n waiting async subscribers.
+    pub fn manual_notify(&self, n: usize) {
+        self.event.notify(n);
+    }
+
+    /// Returns the write index, the sequence of the next published item.
+    pub fn write_index(&self) -> usize {
+        self.buffer.write_index()
+    }
+
+    /// Moves the write index, e.g. close to usize::MAX to exercise wrapping.
+    pub fn set_write_index(&self, wi: usize) {
+        self.buffer.set_write_index(wi);
+    }
+
+    /// Stores an item in the slot at the write index without publishing it,
+    /// as if the publisher was interrupted between the store and the index increment.
+    pub fn store_unpublished(&self, item: T) {
+        self.buffer.store_unpublished(item);
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for TestBusControls<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestBusControls")
+            .field("write_index", &self.write_index())
+            .field("pending_listeners", &self.pending_listeners())
+            .finish()
+    }
+}
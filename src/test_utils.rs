@@ -0,0 +1,181 @@
+//! Recording of a feed and its replay, for deterministic integration tests of code
+//! consuming a bus under realistic feed dynamics.
+//!
+//! ```rust
+//! use bus_queue::flavors::arc_swap::bounded;
+//! use bus_queue::test_utils::{Player, Recorder};
+//!
+//! let (tx, rx) = bounded(10);
+//! (0..3).for_each(|x| tx.broadcast(x).unwrap());
+//! drop(tx);
+//! let mut recorder = Recorder::new();
+//! recorder.record_until_closed(&rx);
+//!
+//! let (tx, rx) = bounded(10);
+//! Player::new(recorder.into_records()).play(&tx).unwrap();
+//! drop(tx);
+//! let replayed: Vec<i32> = rx.map(|x| *x).collect();
+//! assert_eq!(replayed, vec![0, 1, 2]);
+//! ```
+use crate::parker::ThreadParker;
+use crate::publisher::Publisher;
+use crate::ring_buffer::SendError;
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Item captured by a `Recorder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<T> {
+    /// Sequence of the item, see `Subscriber::try_recv_sequenced`
+    pub sequence: u64,
+    /// Time the item was received, since the recorder was created
+    pub at: Duration,
+    pub item: T,
+}
+
+/// Captures the items a subscriber receives, along with when it received them.
+#[derive(Debug)]
+pub struct Recorder<T> {
+    started: Instant,
+    records: Vec<Record<T>>,
+}
+
+impl<T: Clone> Recorder<T> {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            records: Vec::new(),
+        }
+    }
+
+    /// Records the items the subscriber has pending, without blocking.
+    /// Returns the number of items recorded.
+    pub fn record<S: SwapSlot<T>>(&mut self, subscriber: &Subscriber<T, S>) -> usize {
+        let recorded = self.records.len();
+        while let Ok((sequence, item)) = subscriber.try_recv_sequenced() {
+            self.push(sequence, &item);
+        }
+        self.records.len() - recorded
+    }
+
+    /// Records the items the subscriber receives until the bus is closed, blocking.
+    /// Returns the number of items recorded.
+    pub fn record_until_closed<S: SwapSlot<T>>(&mut self, subscriber: &Subscriber<T, S>) -> usize {
+        let recorded = self.records.len();
+        while let Ok((sequence, item)) = subscriber.recv_sequenced_with_parker(&ThreadParker) {
+            self.push(sequence, &item);
+        }
+        self.records.len() - recorded
+    }
+
+    /// Returns the items recorded so far.
+    pub fn records(&self) -> &[Record<T>] {
+        &self.records
+    }
+
+    pub fn into_records(self) -> Vec<Record<T>> {
+        self.records
+    }
+
+    fn push(&mut self, sequence: u64, item: &T) {
+        self.records.push(Record {
+            sequence,
+            at: self.started.elapsed(),
+            item: item.clone(),
+        });
+    }
+}
+
+impl<T: Clone> Default for Recorder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Publishes recorded items again, following the recorded timing by default.
+#[derive(Debug, Clone)]
+pub struct Player<T> {
+    records: Vec<Record<T>>,
+    /// true if items are published without waiting between them
+    fast: bool,
+}
+
+impl<T: Clone> Player<T> {
+    pub fn new(records: Vec<Record<T>>) -> Self {
+        Self {
+            records,
+            fast: false,
+        }
+    }
+
+    /// Makes the player publish the items as fast as possible, ignoring their timing.
+    pub fn as_fast_as_possible(mut self) -> Self {
+        self.fast = true;
+        self
+    }
+
+    /// Publishes the items, blocking until the last one is published. The first
+    /// one is published right away, the others as long after it as when recorded.
+    /// Returns the number of items published.
+    pub fn play<S: SwapSlot<T>>(&self, publisher: &Publisher<T, S>) -> Result<usize, SendError<T>> {
+        let started = Instant::now();
+        let first = self
+            .records
+            .first()
+            .map_or(Duration::ZERO, |record| record.at);
+        for record in &self.records {
+            if !self.fast {
+                let due = started + (record.at - first);
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep(due - now);
+                }
+            }
+            publisher.broadcast(record.item.clone())?;
+        }
+        Ok(self.records.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Player, Record, Recorder};
+    use crate::flavors::arc_swap::bounded;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn replay_follows_the_recorded_timing() {
+        let records = vec![
+            Record {
+                sequence: 0,
+                at: Duration::from_millis(100),
+                item: 'a',
+            },
+            Record {
+                sequence: 1,
+                at: Duration::from_millis(130),
+                item: 'b',
+            },
+        ];
+        let (tx, rx) = bounded(10);
+        let started = Instant::now();
+        assert_eq!(Player::new(records.clone()).play(&tx), Ok(2));
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        let mut recorder = Recorder::new();
+        assert_eq!(recorder.record(&rx), 2);
+        let items: Vec<_> = recorder
+            .records()
+            .iter()
+            .map(|record| record.item)
+            .collect();
+        assert_eq!(items, vec!['a', 'b']);
+
+        let started = Instant::now();
+        let played = Player::new(records).as_fast_as_possible().play(&tx);
+        assert_eq!(played, Ok(2));
+        assert!(started.elapsed() < Duration::from_millis(30));
+    }
+}
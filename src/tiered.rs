@@ -0,0 +1,44 @@
+use std::sync::Mutex;
+
+/// Item retained by the cold tier, along with its position and sequence.
+type Entry<I> = Mutex<Option<(usize, u64, I)>>;
+
+/// Secondary ring of a buffer created with `bounded_tiered`, retaining a longer history
+/// than the hot ring for the subscribers lapped on it or joining late.
+///
+/// It holds the shared pointers of the items stored in the hot ring, so retaining an
+/// item in both tiers does not copy it.
+#[derive(Debug)]
+pub(crate) struct ColdRing<I> {
+    slots: Vec<Entry<I>>,
+}
+
+impl<I: Clone> ColdRing<I> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity).map(|_| Mutex::new(None)).collect(),
+        }
+    }
+
+    /// Returns the number of items retained.
+    pub(crate) fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Retains the item at `seq`, `wide` being its full 64 bit sequence.
+    pub(crate) fn store(&self, seq: usize, wide: u64, sequence: u64, item: I) {
+        *self.slots[self.index(wide)].lock().unwrap() = Some((seq, sequence, item));
+    }
+
+    /// Returns the sequence and the item at `seq` if it is still retained.
+    pub(crate) fn load(&self, seq: usize, wide: u64) -> Option<(u64, I)> {
+        match &*self.slots[self.index(wide)].lock().unwrap() {
+            Some((stored, sequence, item)) if *stored == seq => Some((*sequence, item.clone())),
+            _ => None,
+        }
+    }
+
+    fn index(&self, wide: u64) -> usize {
+        (wide % self.slots.len() as u64) as usize
+    }
+}
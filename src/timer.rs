@@ -0,0 +1,95 @@
+//! Timer thread shared by the whole process, waking tasks at their deadlines so that
+//! delayed wakeups do not take a thread each.
+use std::collections::BTreeMap;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::Waker;
+use std::thread;
+use std::time::Instant;
+
+struct Timers {
+    /// Wakers by deadline, the id telling apart those with the same deadline
+    due: BTreeMap<(Instant, u64), Waker>,
+    next_id: u64,
+}
+
+/// None until the timer thread is started
+static TIMERS: Mutex<Option<Timers>> = Mutex::new(None);
+/// Notified when a waker is added
+static ADDED: Condvar = Condvar::new();
+
+fn timers() -> MutexGuard<'static, Option<Timers>> {
+    TIMERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Wakes the waker from the timer thread once the deadline is reached.
+pub(crate) fn wake_at(deadline: Instant, waker: Waker) {
+    let mut guard = timers();
+    let timers = guard.get_or_insert_with(|| {
+        thread::Builder::new()
+            .name("bus_queue-timer".to_owned())
+            .spawn(run)
+            .expect("failed to spawn the timer thread");
+        Timers {
+            due: BTreeMap::new(),
+            next_id: 0,
+        }
+    });
+    let id = timers.next_id;
+    timers.next_id += 1;
+    timers.due.insert((deadline, id), waker);
+    ADDED.notify_one();
+}
+
+fn run() {
+    let mut guard = timers();
+    loop {
+        let now = Instant::now();
+        let timers = guard.as_mut().expect("started before the thread");
+        guard = match timers.due.keys().next().copied() {
+            Some(key) if key.0 <= now => {
+                let waker = timers.due.remove(&key).expect("just found");
+                // Woken unlocked, the task may well schedule its next wakeup.
+                drop(guard);
+                waker.wake();
+                self::timers()
+            }
+            Some((deadline, _)) => {
+                ADDED
+                    .wait_timeout(guard, deadline - now)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => ADDED.wait(guard).unwrap_or_else(PoisonError::into_inner),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::wake_at;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::task::{Wake, Waker};
+    use std::time::{Duration, Instant};
+
+    struct Report(mpsc::Sender<usize>, usize);
+
+    impl Wake for Report {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.send(self.1);
+        }
+    }
+
+    #[test]
+    fn wakes_in_deadline_order() {
+        let (tx, rx) = mpsc::channel();
+        let now = Instant::now();
+        for (id, delay) in [(2, 20), (0, 0), (1, 10)] {
+            let waker = Waker::from(Arc::new(Report(tx.clone(), id)));
+            wake_at(now + Duration::from_millis(delay), waker);
+        }
+        let woken: Vec<_> = rx.iter().take(3).collect();
+        assert_eq!(woken, vec![0, 1, 2]);
+        assert!(now.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::publisher::Publisher;
+#[cfg(feature = "blocking")]
+use crate::ring_buffer::RecvError;
+use crate::ring_buffer::{SendError, TryRecvError};
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+type Topics<K, T, S> = Arc<Mutex<HashMap<K, Publisher<T, S>>>>;
+
+/// Bus keyed by topic, each topic having a bus of its own created by its first subscriber.
+///
+/// Topics are reference counted by their subscribers: once the last subscriber of a
+/// topic is dropped, the topic's bus and the items it retains are freed, and broadcasting
+/// to the topic fails again without storing the item.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::TopicBus;
+///
+/// let bus = TopicBus::new(10);
+/// let prices = bus.subscribe("prices");
+/// bus.broadcast(&"prices", 101).unwrap();
+/// // Nobody listens to trades, nothing is stored.
+/// assert!(bus.broadcast(&"trades", 7).is_err());
+/// assert_eq!(*prices.try_recv().unwrap(), 101);
+///
+/// assert_eq!(bus.active_topics(), vec!["prices"]);
+/// drop(prices);
+/// assert!(bus.active_topics().is_empty());
+/// ```
+pub struct TopicBus<K, T, S: SwapSlot<T>> {
+    topics: Topics<K, T, S>,
+    size: usize,
+}
+
+impl<K: Eq + Hash + Clone, T, S: SwapSlot<T>> TopicBus<K, T, S> {
+    /// Creates a bus whose topics retain up to `size` items each.
+    pub fn new(size: usize) -> Self {
+        Self {
+            topics: Arc::new(Mutex::new(HashMap::new())),
+            size,
+        }
+    }
+
+    /// Publishes the object to the topic, failing if the topic has no subscribers.
+    pub fn broadcast(&self, topic: &K, object: T) -> Result<(), SendError<T>> {
+        let mut topics = self.topics.lock().unwrap();
+        let publisher = match topics.get(topic) {
+            Some(publisher) => publisher,
+            None => return Err(SendError(object)),
+        };
+        let result = publisher.broadcast(object);
+        // Subscribers taken out of their topic subscriber do not clean up on drop.
+        if publisher.sub_count() == 0 {
+            topics.remove(topic);
+        }
+        result
+    }
+
+    /// Creates a receiver of the items published to the topic from now on,
+    /// creating the topic's bus if it has none.
+    pub fn subscribe(&self, topic: K) -> TopicSubscriber<K, T, S> {
+        let mut topics = self.topics.lock().unwrap();
+        let subscriber = match topics.get(&topic) {
+            Some(publisher) => publisher.subscribe(),
+            None => {
+                let (publisher, subscriber) = crate::bounded::<T, S>(self.size);
+                topics.insert(topic.clone(), publisher);
+                subscriber
+            }
+        };
+        TopicSubscriber {
+            subscriber: Some(subscriber),
+            topic,
+            topics: self.topics.clone(),
+        }
+    }
+
+    /// Returns the topics which have subscribers, in no particular order.
+    pub fn active_topics(&self) -> Vec<K> {
+        self.topics.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+impl<K, T, S: SwapSlot<T>> fmt::Debug for TopicBus<K, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TopicBus")
+            .field("size", &self.size)
+            .field("topics", &self.topics.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Receiver of a topic of a `TopicBus`, returned by `TopicBus::subscribe`.
+pub struct TopicSubscriber<K: Eq + Hash, T, S: SwapSlot<T>> {
+    /// Only None while dropping
+    subscriber: Option<Subscriber<T, S>>,
+    topic: K,
+    topics: Topics<K, T, S>,
+}
+
+impl<K: Eq + Hash, T, S: SwapSlot<T>> TopicSubscriber<K, T, S> {
+    /// Returns the topic received from.
+    pub fn topic(&self) -> &K {
+        &self.topic
+    }
+
+    /// Receives the next item of the topic, see `Subscriber::try_recv`.
+    pub fn try_recv(&self) -> Result<S::Shared, TryRecvError> {
+        self.subscriber().try_recv()
+    }
+
+    /// Receives the next item of the topic, blocking until there is one,
+    /// see `Subscriber::recv`.
+    #[cfg(feature = "blocking")]
+    pub fn recv(&self) -> Result<S::Shared, RecvError> {
+        self.subscriber().recv()
+    }
+
+    fn subscriber(&self) -> &Subscriber<T, S> {
+        self.subscriber.as_ref().expect("only taken on drop")
+    }
+}
+
+impl<K: Eq + Hash + Clone, T, S: SwapSlot<T>> Clone for TopicSubscriber<K, T, S> {
+    fn clone(&self) -> Self {
+        // Counted under the lock, so that a subscriber dropped meanwhile keeps the topic.
+        let _topics = self.topics.lock().unwrap();
+        Self {
+            subscriber: self.subscriber.clone(),
+            topic: self.topic.clone(),
+            topics: self.topics.clone(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, T, S: SwapSlot<T>> Iterator for TopicSubscriber<K, T, S> {
+    type Item = S::Shared;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_recv().ok()
+    }
+}
+
+impl<K: Eq + Hash, T, S: SwapSlot<T>> Drop for TopicSubscriber<K, T, S> {
+    fn drop(&mut self) {
+        let mut topics = match self.topics.lock() {
+            Ok(topics) => topics,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        drop(self.subscriber.take());
+        if matches!(topics.get(&self.topic), Some(publisher) if publisher.sub_count() == 0) {
+            topics.remove(&self.topic);
+        }
+    }
+}
+
+impl<K: Eq + Hash + fmt::Debug, T, S: SwapSlot<T>> fmt::Debug for TopicSubscriber<K, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TopicSubscriber")
+            .field("topic", &self.topic)
+            .field("subscriber", &self.subscriber)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::TopicBus;
+    use std::sync::Arc;
+
+    #[test]
+    fn last_subscriber_frees_the_topic() {
+        let bus = TopicBus::new(10);
+        let a = bus.subscribe(1);
+        let b = a.clone();
+        let item = Arc::new("retained");
+        bus.broadcast(&1, item.clone()).unwrap();
+        assert_eq!(Arc::strong_count(&item), 2);
+
+        drop(a);
+        assert_eq!(bus.active_topics(), vec![1]);
+        drop(b);
+        assert!(bus.active_topics().is_empty());
+        // The retained item went with the topic's bus.
+        assert_eq!(Arc::strong_count(&item), 1);
+        assert!(bus.broadcast(&1, item).is_err());
+
+        let c = bus.subscribe(1);
+        assert_eq!(c.try_recv(), Err(std::sync::mpsc::TryRecvError::Empty));
+    }
+}
@@ -0,0 +1,320 @@
+//! Single-threaded bus for pipelines running on one thread, e.g. inside a tokio `LocalSet`.
+//!
+//! Mirrors the sync bus with `Cell` counters and `Rc` pointers instead of atomics,
+//! so neither half can be sent to another thread.
+//!
+//! ```rust
+//! use bus_queue::unsync::bounded;
+//!
+//! let (tx, rx) = bounded(2);
+//! (0..4).for_each(|x| tx.broadcast(x).unwrap());
+//!
+//! let received: Vec<i32> = rx.map(|x| *x).collect();
+//! assert_eq!(received, vec![2, 3]);
+//! ```
+use crate::ring_buffer::{SendError, TryRecvError};
+use crate::subscriber::{SkipItemsError, SkipMode};
+#[cfg(feature = "async")]
+use futures_core::Stream;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Waker;
+#[cfg(feature = "async")]
+use std::task::{self, Poll};
+
+/// Function used to create and initialise a (LocalPublisher, LocalSubscriber) tuple.
+pub fn bounded<T>(size: usize) -> (LocalPublisher<T>, LocalSubscriber<T>) {
+    let buffer = Rc::new(LocalRingBuffer::new(size));
+    (
+        LocalPublisher {
+            buffer: buffer.clone(),
+        },
+        LocalSubscriber {
+            buffer,
+            ri: Cell::new(0),
+            skip_items: 0,
+        },
+    )
+}
+
+struct LocalRingBuffer<T> {
+    buffer: Vec<RefCell<Option<Rc<T>>>>,
+    size: usize,
+    wi: Cell<usize>,
+    sub_count: Cell<usize>,
+    is_available: Cell<bool>,
+    /// Tasks of the subscribers waiting for the next item
+    wakers: RefCell<Vec<Waker>>,
+}
+
+impl<T> LocalRingBuffer<T> {
+    fn new(size: usize) -> Self {
+        let size = size + 1;
+        Self {
+            buffer: (0..size).map(|_| RefCell::new(None)).collect(),
+            size,
+            wi: Cell::new(0),
+            sub_count: Cell::new(1),
+            is_available: Cell::new(true),
+            wakers: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        if self.sub_count.get() == 0 {
+            return Err(SendError(object));
+        }
+        let wi = self.wi.get();
+        *self.buffer[wi % self.size].borrow_mut() = Some(Rc::new(object));
+        self.wi.set(wi.wrapping_add(1));
+        self.wake();
+        Ok(())
+    }
+
+    fn try_recv(&self, ri: &Cell<usize>, skip_items: usize) -> Result<Rc<T>, TryRecvError> {
+        let wi = self.wi.get();
+        if ri.get() == wi {
+            if self.is_available.get() {
+                return Err(TryRecvError::Empty);
+            } else {
+                return Err(TryRecvError::Disconnected);
+            }
+        }
+        // Without concurrent writers the reader only has to catch up once.
+        if wi.wrapping_sub(ri.get()) >= self.size {
+            ri.set(wi.wrapping_sub(self.size).wrapping_add(1 + skip_items));
+        }
+        let item = self.buffer[ri.get() % self.size].borrow().clone();
+        ri.set(ri.get().wrapping_add(1));
+        Ok(item.expect("published slot is never empty"))
+    }
+
+    fn close(&self) {
+        self.is_available.set(false);
+        self.wake();
+    }
+
+    fn wake(&self) {
+        for waker in self.wakers.borrow_mut().drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// Publishing half of the single-threaded bus.
+pub struct LocalPublisher<T> {
+    buffer: Rc<LocalRingBuffer<T>>,
+}
+
+impl<T> LocalPublisher<T> {
+    /// Publishes values to the circular buffer at wi % size
+    pub fn broadcast(&self, object: T) -> Result<(), SendError<T>> {
+        self.buffer.broadcast(object)
+    }
+
+    /// Creates a new Receiver which only receives the items published from now on.
+    pub fn subscribe(&self) -> LocalSubscriber<T> {
+        self.buffer.sub_count.set(self.buffer.sub_count.get() + 1);
+        LocalSubscriber {
+            buffer: self.buffer.clone(),
+            ri: Cell::new(self.buffer.wi.get()),
+            skip_items: 0,
+        }
+    }
+
+    /// Returns the length of the queue
+    pub fn len(&self) -> usize {
+        self.buffer.size - 1
+    }
+
+    /// Checks if nothings has been published yet
+    pub fn is_empty(&self) -> bool {
+        self.buffer.wi.get() == 0
+    }
+
+    /// Returns the number of Receivers
+    pub fn sub_count(&self) -> usize {
+        self.buffer.sub_count.get()
+    }
+}
+
+impl<T> Drop for LocalPublisher<T> {
+    fn drop(&mut self) {
+        self.buffer.close();
+    }
+}
+
+impl<T> fmt::Debug for LocalPublisher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalPublisher")
+            .field("wi", &self.buffer.wi.get())
+            .field("size", &self.len())
+            .field("sub_count", &self.sub_count())
+            .finish()
+    }
+}
+
+/// Subscribing half of the single-threaded bus, both an Iterator and a Stream.
+pub struct LocalSubscriber<T> {
+    buffer: Rc<LocalRingBuffer<T>>,
+    ri: Cell<usize>,
+    skip_items: usize,
+}
+
+impl<T> LocalSubscriber<T> {
+    /// Sets the number of items the reader skips when lapped, see
+    /// `Subscriber::set_skip_items`.
+    pub fn set_skip_items(&mut self, skip_items: usize) -> usize {
+        match self.set_skip_items_with(skip_items, SkipMode::Saturating) {
+            Ok(skip_items) => skip_items,
+            Err(error) => error.max,
+        }
+    }
+
+    /// Sets the number of items the reader skips when lapped, see
+    /// `Subscriber::set_skip_items_with`.
+    pub fn set_skip_items_with(
+        &mut self,
+        skip_items: usize,
+        mode: SkipMode,
+    ) -> Result<usize, SkipItemsError> {
+        self.skip_items = mode.apply(skip_items, self.buffer.size - 2)?;
+        Ok(self.skip_items)
+    }
+
+    /// Returns the number of items the reader skips when lapped.
+    pub fn skip_items(&self) -> usize {
+        self.skip_items
+    }
+
+    /// Receives the next item if there is one. Never blocks.
+    pub fn try_recv(&self) -> Result<Rc<T>, TryRecvError> {
+        self.buffer.try_recv(&self.ri, self.skip_items)
+    }
+
+    /// Returns the length of the queue.
+    pub fn len(&self) -> usize {
+        self.buffer.size - 1
+    }
+
+    /// Checks if every published item has been received.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.wi.get() == self.ri.get()
+    }
+}
+
+impl<T> Clone for LocalSubscriber<T> {
+    fn clone(&self) -> Self {
+        self.buffer.sub_count.set(self.buffer.sub_count.get() + 1);
+        Self {
+            buffer: self.buffer.clone(),
+            ri: self.ri.clone(),
+            skip_items: self.skip_items,
+        }
+    }
+}
+
+impl<T> Drop for LocalSubscriber<T> {
+    fn drop(&mut self) {
+        self.buffer.sub_count.set(self.buffer.sub_count.get() - 1);
+    }
+}
+
+impl<T> fmt::Debug for LocalSubscriber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSubscriber")
+            .field("ri", &self.ri.get())
+            .field("wi", &self.buffer.wi.get())
+            .field("skip_items", &self.skip_items)
+            .finish()
+    }
+}
+
+impl<T> Iterator for LocalSubscriber<T> {
+    type Item = Rc<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_recv().ok()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Stream for LocalSubscriber<T> {
+    type Item = Rc<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.try_recv() {
+            Ok(item) => Poll::Ready(Some(item)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => {
+                // Nothing can be published in between, the publisher lives on this thread.
+                self.buffer.wakers.borrow_mut().push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::bounded;
+    use crate::ring_buffer::TryRecvError;
+
+    #[test]
+    fn broadcast_recv() {
+        let (tx, rx) = bounded(3);
+        let rx2 = tx.subscribe();
+        assert_eq!(tx.sub_count(), 2);
+
+        for i in 0..5 {
+            tx.broadcast(i).unwrap();
+        }
+        assert_eq!(*rx.try_recv().unwrap(), 2);
+        assert_eq!(rx2.map(|x| *x).collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        drop(tx);
+        assert_eq!(*rx.try_recv().unwrap(), 3);
+        assert_eq!(*rx.try_recv().unwrap(), 4);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn no_subscribers() {
+        let (tx, rx) = bounded(3);
+        drop(rx);
+        assert_eq!(tx.broadcast(1).unwrap_err().0, 1);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn stream_on_local_pool() {
+        use futures::executor::LocalPool;
+        use futures::task::LocalSpawnExt;
+        use futures::StreamExt;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let (tx, rx) = bounded(10);
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut pool = LocalPool::new();
+
+        let sink = received.clone();
+        pool.spawner()
+            .spawn_local(async move {
+                let values = StreamExt::map(rx, |x| *x).collect::<Vec<i32>>().await;
+                sink.borrow_mut().extend(values);
+            })
+            .unwrap();
+        pool.run_until_stalled();
+
+        for i in 0..3 {
+            tx.broadcast(i).unwrap();
+        }
+        drop(tx);
+        pool.run();
+        assert_eq!(*received.borrow(), vec![0, 1, 2]);
+    }
+}
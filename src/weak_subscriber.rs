@@ -0,0 +1,135 @@
+use crate::atomic_counter::AtomicCounter;
+use crate::ring_buffer::{RingBuffer, TryRecvError};
+use crate::subscriber::Subscriber;
+use crate::swap_slot::SwapSlot;
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+/// Receiver holding a weak reference to the bus, returned by `Subscriber::downgrade`.
+///
+/// It counts as a subscriber while the bus is alive, but does not keep it alive: once
+/// the publisher and every other subscriber are gone, the bus and the items it retains
+/// are dropped, and receiving returns `Disconnected`. A handle forgotten somewhere then
+/// does not pin the memory of the retained items.
+///
+/// ```rust
+/// use bus_queue::flavors::arc_swap::bounded;
+/// use std::sync::mpsc::TryRecvError;
+///
+/// let (tx, rx) = bounded(10);
+/// let weak = rx.downgrade();
+/// tx.broadcast(1).unwrap();
+/// assert_eq!(*weak.try_recv().unwrap(), 1);
+///
+/// drop(tx);
+/// assert_eq!(weak.try_recv(), Err(TryRecvError::Disconnected));
+/// ```
+pub struct WeakSubscriber<T, S: SwapSlot<T>> {
+    buffer: Weak<RingBuffer<T, S>>,
+    /// Read index pointer
+    ri: AtomicCounter,
+    /// how many items should the receiver skip when the writer overflows
+    skip_items: usize,
+}
+
+impl<T, S: SwapSlot<T>> Subscriber<T, S> {
+    /// Turns the receiver into one not keeping the bus alive, see `WeakSubscriber`.
+    pub fn downgrade(self) -> WeakSubscriber<T, S> {
+        // Handed over to the weak receiver, this receiver's count is released on drop.
+        self.buffer.inc_sub_count();
+        WeakSubscriber {
+            buffer: Arc::downgrade(&self.buffer),
+            ri: AtomicCounter::new(self.ri.get()),
+            skip_items: self.skip_items,
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> WeakSubscriber<T, S> {
+    /// Receives the next item like `Subscriber::try_recv`, or `Disconnected`
+    /// once the bus is gone.
+    pub fn try_recv(&self) -> Result<S::Shared, TryRecvError> {
+        self.try_recv_sequenced().map(|(_, item)| item)
+    }
+
+    /// Receives the next item along with its sequence, see `Subscriber::try_recv_sequenced`.
+    pub fn try_recv_sequenced(&self) -> Result<(u64, S::Shared), TryRecvError> {
+        match self.buffer.upgrade() {
+            Some(buffer) => buffer.try_recv_sequenced(&self.ri, self.skip_items),
+            None => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Checks if the bus is still alive.
+    pub fn is_alive(&self) -> bool {
+        self.buffer.strong_count() > 0
+    }
+
+    /// Returns a receiver keeping the bus alive, positioned where this one is,
+    /// or None if the bus is gone.
+    pub fn upgrade(&self) -> Option<Subscriber<T, S>> {
+        let buffer = self.buffer.upgrade()?;
+        buffer.inc_sub_count();
+        let mut subscriber = Subscriber::from(buffer);
+        subscriber.ri.set(self.ri.get());
+        subscriber.sync_cursor();
+        subscriber.set_skip_items(self.skip_items);
+        Some(subscriber)
+    }
+}
+
+impl<T, S: SwapSlot<T>> Clone for WeakSubscriber<T, S> {
+    fn clone(&self) -> Self {
+        if let Some(buffer) = self.buffer.upgrade() {
+            buffer.inc_sub_count();
+        }
+        Self {
+            buffer: self.buffer.clone(),
+            ri: AtomicCounter::new(self.ri.get()),
+            skip_items: self.skip_items,
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> Drop for WeakSubscriber<T, S> {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.upgrade() {
+            buffer.dec_sub_count();
+        }
+    }
+}
+
+impl<T, S: SwapSlot<T>> fmt::Debug for WeakSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakSubscriber")
+            .field("ri", &self.ri.get())
+            .field("is_alive", &self.is_alive())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flavors::arc_swap::bounded;
+    use crate::ring_buffer::TryRecvError;
+    use std::sync::Arc;
+
+    #[test]
+    fn retained_items_are_released_with_the_bus() {
+        let (tx, rx) = bounded(4);
+        let weak = rx.downgrade();
+        let item = Arc::new(());
+        tx.broadcast(item.clone()).unwrap();
+        // The weak receiver counts as a subscriber.
+        assert_eq!(tx.sub_count(), 1);
+
+        let strong = weak.upgrade().unwrap();
+        assert_eq!(tx.sub_count(), 2);
+        drop(strong);
+        drop(tx);
+        assert_eq!(Arc::strong_count(&item), 1);
+        assert!(!weak.is_alive());
+        assert_eq!(weak.try_recv(), Err(TryRecvError::Disconnected));
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -1,10 +1,13 @@
-use bus_queue::flavors::arc_swap::async_bounded;
+#![cfg(feature = "async")]
+use bus_queue::flavors::arc_swap::{async_bounded, Slot};
+use bus_queue::Builder;
 // use futures::{executor, pin_mut, task::Poll, task::SpawnExt, FutureExt, SinkExt, StreamExt};
-use futures::{pin_mut, task::Poll, FutureExt, SinkExt};
+use futures::task::{waker, ArcWake, Context};
+use futures::{pin_mut, task::Poll, Future, FutureExt, SinkExt, StreamExt};
 use futures_test::task::noop_context;
 use futures_test::{assert_stream_done, assert_stream_next, assert_stream_pending};
 // use rand::Rng;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 // use std::time::Duration;
 
 // pool.spawn alternative
@@ -172,6 +175,27 @@ fn test_set_skip_items() {
     assert_stream_next!(subscriber4, Arc::new(5));
 }
 
+#[test]
+fn test_set_sample_every() {
+    let (publisher, subscriber1) = async_bounded(10);
+    let mut subscriber2 = subscriber1.clone();
+    subscriber2.set_sample_every(2);
+
+    pin_mut!(publisher);
+    pin_mut!(subscriber1);
+    pin_mut!(subscriber2);
+
+    let mut cx = noop_context();
+    for i in 0..4 {
+        assert_eq!(publisher.send(i).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    }
+    assert_stream_next!(subscriber1, Arc::new(0));
+    assert_stream_next!(subscriber1, Arc::new(1));
+    assert_stream_next!(subscriber2, Arc::new(0));
+    assert_stream_next!(subscriber2, Arc::new(2));
+    assert_stream_pending!(subscriber2);
+}
+
 #[test]
 fn test_publisher_eq() {
     let (publisher1, _) = async_bounded::<i32>(1);
@@ -190,3 +214,507 @@ fn test_subscriber_eq() {
     assert_ne!(subscriber2, subscriber3);
     assert_ne!(subscriber1, subscriber3);
 }
+
+#[test]
+fn stream_into_bus() {
+    use bus_queue::flavors::arc_swap::Slot;
+    use bus_queue::StreamExt2;
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+
+    let (driver, subscriber1) = stream::iter(1..15).into_bus::<Slot<_>>(10);
+    let subscriber2 = subscriber1.clone();
+
+    block_on(driver).unwrap();
+
+    let received1: Vec<u32> = block_on(subscriber1.map(|x| *x).collect());
+    let received2: Vec<u32> = block_on(subscriber2.map(|x| *x).collect());
+    // Only the last 10 items are retained and the bus is closed once the stream ends.
+    let expected = (5..15).collect::<Vec<u32>>();
+    assert_eq!(received1, expected);
+    assert_eq!(received2, expected);
+}
+
+#[test]
+fn publisher_slows_down_on_request() {
+    use std::time::Duration;
+    let mut cx = noop_context();
+    let (mut publisher, mut subscriber) = async_bounded::<usize>(1);
+    publisher.set_max_slowdown(Some(Duration::from_secs(60)));
+
+    subscriber.request_slowdown();
+    assert_eq!(publisher.slowdown_requests(), 1);
+    {
+        let mut send = publisher.send(1);
+        // The publisher is held back while the subscriber asks it to slow down.
+        assert_eq!(send.poll_unpin(&mut cx), Poll::Pending);
+        subscriber.release_slowdown();
+        assert_eq!(send.poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    }
+    assert_eq!(publisher.slowdown_requests(), 0);
+
+    // Dropping a subscriber withdraws its request.
+    let mut subscriber2 = subscriber.clone();
+    subscriber2.request_slowdown();
+    drop(subscriber2);
+    assert_eq!(publisher.slowdown_requests(), 0);
+}
+
+#[test]
+fn publisher_slowdown_expires() {
+    use std::time::Duration;
+    let mut cx = noop_context();
+    let (mut publisher, mut subscriber) = async_bounded::<usize>(1);
+    publisher.set_max_slowdown(Some(Duration::from_millis(10)));
+    subscriber.request_slowdown();
+
+    let mut send = publisher.send(1);
+    assert_eq!(send.poll_unpin(&mut cx), Poll::Pending);
+    std::thread::sleep(Duration::from_millis(20));
+    // The item is published once the maximum slowdown has elapsed.
+    assert_eq!(send.poll_unpin(&mut cx), Poll::Ready(Ok(())));
+}
+
+#[cfg(feature = "test-controls")]
+#[test]
+fn test_bus_controls() {
+    use bus_queue::TestBusControls;
+    let mut cx = noop_context();
+    let (publisher, subscriber) = async_bounded::<usize>(2);
+    let controls = TestBusControls::new(&publisher);
+    pin_mut!(subscriber);
+    pin_mut!(publisher);
+
+    assert_eq!(controls.pending_listeners(), 0);
+    assert_stream_pending!(subscriber);
+    assert_eq!(controls.pending_listeners(), 1);
+
+    // An item stored without publishing it is not received.
+    controls.store_unpublished(1);
+    controls.manual_notify(1);
+    assert_stream_pending!(subscriber);
+
+    // Until the write index is moved past it.
+    controls.set_write_index(controls.write_index() + 1);
+    controls.manual_notify(1);
+    assert_stream_next!(subscriber, Arc::new(1));
+    assert_eq!(controls.pending_listeners(), 0);
+
+    assert_eq!(publisher.send(2).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(controls.write_index(), 2);
+}
+
+#[test]
+fn subscriber_cloned() {
+    let mut cx = noop_context();
+    let (publisher, subscriber) = async_bounded::<String>(2);
+    let subscriber = subscriber.cloned();
+    pin_mut!(subscriber);
+    pin_mut!(publisher);
+
+    assert_eq!(
+        publisher.send("item".to_string()).poll_unpin(&mut cx),
+        Poll::Ready(Ok(()))
+    );
+    // Items are yielded as owned values.
+    assert_stream_next!(subscriber, "item".to_string());
+    assert_stream_pending!(subscriber);
+}
+
+#[test]
+fn waiting_subscribers() {
+    let mut cx = noop_context();
+    let (publisher, subscriber1) = async_bounded::<usize>(2);
+    let subscriber2 = subscriber1.clone();
+    pin_mut!(subscriber1);
+    pin_mut!(subscriber2);
+    pin_mut!(publisher);
+    assert_eq!(publisher.waiting_subscribers(), 0);
+
+    assert_stream_pending!(subscriber1);
+    assert_stream_pending!(subscriber2);
+    assert_eq!(publisher.waiting_subscribers(), 2);
+
+    assert_eq!(publisher.send(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_stream_next!(subscriber1, Arc::new(1));
+    assert_eq!(publisher.waiting_subscribers(), 1);
+}
+
+#[test]
+fn drain_remaining() {
+    let mut cx = noop_context();
+    let (publisher, mut subscriber) = async_bounded::<usize>(4);
+    pin_mut!(publisher);
+
+    assert_eq!(publisher.send(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    let drain = subscriber.drain_remaining();
+    pin_mut!(drain);
+    assert_eq!(drain.as_mut().poll(&mut cx), Poll::Pending);
+
+    assert_eq!(publisher.send(2).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(publisher.close().poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(
+        drain.poll(&mut cx),
+        Poll::Ready(vec![Arc::new(1), Arc::new(2)])
+    );
+}
+
+#[test]
+fn critical_subscriber() {
+    let mut cx = noop_context();
+    let (publisher, mut critical) = async_bounded::<usize>(2);
+    let mut other = critical.clone();
+    assert!(critical.set_critical(true));
+    // There is only one critical subscriber per bus.
+    assert!(!other.set_critical(true));
+    pin_mut!(publisher);
+    {
+        pin_mut!(critical);
+        assert_stream_pending!(critical);
+        assert_eq!(publisher.send(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+        assert_stream_next!(critical, Arc::new(1));
+        assert_eq!(publisher.close().poll_unpin(&mut cx), Poll::Ready(Ok(())));
+        assert_stream_done!(critical);
+    }
+    // Dropping the critical subscriber lets another one take its place.
+    assert!(other.set_critical(true));
+}
+
+#[test]
+fn strict_sink() {
+    let mut cx = noop_context();
+    let (mut publisher, subscriber) = async_bounded::<usize>(4);
+    publisher.set_strict(true);
+    pin_mut!(subscriber);
+    pin_mut!(publisher);
+
+    assert_eq!(publisher.feed(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    // The item is only published on flush.
+    assert_stream_pending!(subscriber);
+    assert_eq!(publisher.feed(2).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_stream_next!(subscriber, Arc::new(1));
+    assert_stream_pending!(subscriber);
+
+    assert_eq!(publisher.flush().poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_stream_next!(subscriber, Arc::new(2));
+    assert_eq!(publisher.send(3).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_stream_next!(subscriber, Arc::new(3));
+}
+
+#[test]
+fn subscriber_closed() {
+    let mut cx = noop_context();
+    let (publisher, subscriber) = async_bounded::<usize>(2);
+    let closed = subscriber.closed();
+    pin_mut!(closed);
+    pin_mut!(publisher);
+
+    assert_eq!(publisher.send(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+
+    // Resolves without the remaining item being received.
+    assert_eq!(publisher.close().poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(closed.poll(&mut cx), Poll::Ready(()));
+    assert!(!subscriber.is_empty());
+}
+
+/// Waker recording its id in the order the wakers are woken.
+struct WakeLog {
+    id: usize,
+    log: Arc<Mutex<Vec<usize>>>,
+}
+
+impl ArcWake for WakeLog {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.log.lock().unwrap().push(arc_self.id);
+    }
+}
+
+/// Returns how many times each of two subscribers was woken first over four items.
+fn first_wakeups(builder: Builder) -> [usize; 2] {
+    let (publisher, subscriber) = builder.build_async::<usize, Slot<usize>>();
+    let mut subscribers = [subscriber.clone(), subscriber];
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let wakers: Vec<_> = (0..2)
+        .map(|id| {
+            waker(Arc::new(WakeLog {
+                id,
+                log: log.clone(),
+            }))
+        })
+        .collect();
+    pin_mut!(publisher);
+    let mut first = [0; 2];
+    for i in 0..4 {
+        for (subscriber, waker) in subscribers.iter_mut().zip(&wakers) {
+            let mut cx = Context::from_waker(waker);
+            assert_eq!(subscriber.poll_next_unpin(&mut cx), Poll::Pending);
+        }
+        log.lock().unwrap().clear();
+        assert_eq!(
+            publisher.send(i).poll_unpin(&mut noop_context()),
+            Poll::Ready(Ok(()))
+        );
+        first[log.lock().unwrap()[0]] += 1;
+        for subscriber in subscribers.iter_mut() {
+            assert_eq!(
+                subscriber.poll_next_unpin(&mut noop_context()),
+                Poll::Ready(Some(Arc::new(i)))
+            );
+        }
+    }
+    first
+}
+
+#[test]
+fn fair_wakeups() {
+    // Subscribers listening first are woken first.
+    assert_eq!(first_wakeups(Builder::new(4)), [4, 0]);
+    assert_eq!(first_wakeups(Builder::new(4).fair_wakeups(2)), [2, 2]);
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn subscriber_woken_on_close() {
+    use bus_queue::fault_injection::Faults;
+    let faults = Arc::new(Faults::new());
+    faults.disconnect_at(0);
+    let (publisher, mut subscriber) = Builder::new(2)
+        .fault_injection(faults)
+        .build_async::<usize, Slot<usize>>();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let waker = waker(Arc::new(WakeLog {
+        id: 0,
+        log: log.clone(),
+    }));
+    let mut cx = Context::from_waker(&waker);
+    let closed = subscriber.closed();
+    pin_mut!(closed);
+    pin_mut!(publisher);
+    assert_eq!(subscriber.poll_next_unpin(&mut cx), Poll::Pending);
+    assert_eq!(closed.as_mut().poll(&mut cx), Poll::Pending);
+
+    // The bus closes without the publisher notifying its subscribers.
+    assert!(publisher.start_send_unpin(1).is_err());
+    assert_eq!(*log.lock().unwrap(), vec![0, 0]);
+    assert_eq!(closed.poll(&mut cx), Poll::Ready(()));
+    assert_eq!(subscriber.poll_next_unpin(&mut cx), Poll::Ready(None));
+}
+
+#[test]
+fn subscriber_with_policy() {
+    use bus_queue::SkipPolicy;
+    use futures::channel::mpsc;
+
+    let mut cx = noop_context();
+    let (publisher, subscriber) = async_bounded::<usize>(8);
+    let (policies, policy_stream) = mpsc::unbounded();
+    let subscriber = subscriber.with_policy(policy_stream);
+    pin_mut!(subscriber);
+    pin_mut!(publisher);
+
+    for i in 0..2 {
+        assert_eq!(publisher.send(i).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    }
+    assert_stream_next!(subscriber, Arc::new(0));
+    assert_stream_next!(subscriber, Arc::new(1));
+
+    // The controller only wants every other item from now on.
+    policies
+        .unbounded_send(SkipPolicy {
+            skip_items: 0,
+            sample_every: 2,
+        })
+        .unwrap();
+    for i in 2..6 {
+        assert_eq!(publisher.send(i).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    }
+    assert_stream_next!(subscriber, Arc::new(2));
+    assert_stream_next!(subscriber, Arc::new(4));
+    assert_stream_pending!(subscriber);
+
+    // The last policy stays once the controller is gone.
+    drop(policies);
+    assert_eq!(publisher.send(6).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_stream_next!(subscriber, Arc::new(6));
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn subscriber_poll_is_bounded() {
+    use bus_queue::fault_injection::Faults;
+    let faults = Arc::new(Faults::new());
+    (0..100).for_each(|seq| faults.lap_at(seq));
+    let (publisher, mut subscriber) = Builder::new(128)
+        .fault_injection(faults)
+        .build_async::<usize, Slot<usize>>();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let waker = waker(Arc::new(WakeLog {
+        id: 0,
+        log: log.clone(),
+    }));
+    let mut cx = Context::from_waker(&waker);
+    pin_mut!(publisher);
+    for i in 0..101 {
+        assert_eq!(publisher.send(i).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    }
+
+    // Skipping the missed items takes more than one poll, the stream wakes itself in between.
+    log.lock().unwrap().clear();
+    assert_eq!(subscriber.poll_next_unpin(&mut cx), Poll::Pending);
+    assert_eq!(*log.lock().unwrap(), vec![0]);
+    assert_eq!(
+        subscriber.poll_next_unpin(&mut cx),
+        Poll::Ready(Some(Arc::new(100)))
+    );
+}
+
+#[test]
+fn subscriber_into_blocking_iter() {
+    let (publisher, subscriber) = async_bounded::<usize>(8);
+    let received = std::thread::spawn(move || {
+        subscriber
+            .into_blocking_iter()
+            .map(|x| *x)
+            .collect::<Vec<_>>()
+    });
+    futures::executor::block_on(async move {
+        pin_mut!(publisher);
+        for i in 0..4 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            publisher.send(i).await.unwrap();
+        }
+    });
+    assert_eq!(received.join().unwrap(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn notify_policy() {
+    use bus_queue::NotifyPolicy;
+    use std::time::Duration;
+    let mut cx = noop_context();
+    let (mut publisher, mut subscriber) = async_bounded::<usize>(8);
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let waker = waker(Arc::new(WakeLog {
+        id: 0,
+        log: log.clone(),
+    }));
+    let mut sub_cx = Context::from_waker(&waker);
+    publisher.set_notify_policy(NotifyPolicy::EveryN(2));
+    assert_eq!(subscriber.poll_next_unpin(&mut sub_cx), Poll::Pending);
+
+    // Flushing does not wake the subscriber before the batch is full.
+    assert_eq!(publisher.send(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert!(log.lock().unwrap().is_empty());
+    assert_eq!(publisher.send(2).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(*log.lock().unwrap(), vec![0]);
+    assert_eq!(
+        subscriber.poll_next_unpin(&mut sub_cx),
+        Poll::Ready(Some(Arc::new(1)))
+    );
+    assert_eq!(
+        subscriber.poll_next_unpin(&mut sub_cx),
+        Poll::Ready(Some(Arc::new(2)))
+    );
+    assert_eq!(subscriber.poll_next_unpin(&mut sub_cx), Poll::Pending);
+
+    // Items published within the interval are announced once it ends.
+    publisher.set_notify_policy(NotifyPolicy::Coalesced(Duration::from_millis(20)));
+    assert_eq!(subscriber.poll_next_unpin(&mut sub_cx), Poll::Pending);
+    log.lock().unwrap().clear();
+    assert_eq!(publisher.send(3).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert!(log.lock().unwrap().is_empty());
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(*log.lock().unwrap(), vec![0]);
+    assert_eq!(
+        subscriber.poll_next_unpin(&mut sub_cx),
+        Poll::Ready(Some(Arc::new(3)))
+    );
+}
+
+#[test]
+fn wakeup_limit() {
+    use std::time::Duration;
+
+    let mut cx = noop_context();
+    let (mut publisher, subscriber) = async_bounded::<usize>(8);
+    let mut subscribers = [subscriber.clone(), subscriber];
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let wakers: Vec<_> = (0..2)
+        .map(|id| {
+            waker(Arc::new(WakeLog {
+                id,
+                log: log.clone(),
+            }))
+        })
+        .collect();
+    for (subscriber, waker) in subscribers.iter_mut().zip(&wakers) {
+        let mut sub_cx = Context::from_waker(waker);
+        assert_eq!(subscriber.poll_next_unpin(&mut sub_cx), Poll::Pending);
+    }
+    publisher.set_wakeup_limit(Some(1));
+
+    // Each notification wakes one more subscriber.
+    assert_eq!(publisher.send(1).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(log.lock().unwrap().len(), 1);
+    assert_eq!(publisher.send(2).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    let mut woken = log.lock().unwrap().clone();
+    woken.sort_unstable();
+    assert_eq!(woken, vec![0, 1]);
+
+    // The subscribers left waiting once publishing stops are woken shortly after.
+    log.lock().unwrap().clear();
+    for (subscriber, waker) in subscribers.iter_mut().zip(&wakers) {
+        let mut sub_cx = Context::from_waker(waker);
+        while subscriber.poll_next_unpin(&mut sub_cx).is_ready() {}
+    }
+    assert_eq!(publisher.send(3).poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(log.lock().unwrap().len(), 1);
+    std::thread::sleep(Duration::from_millis(100));
+    let mut woken = log.lock().unwrap().clone();
+    woken.sort_unstable();
+    assert_eq!(woken, vec![0, 1]);
+}
+
+#[test]
+fn publisher_guard() {
+    let mut cx = noop_context();
+    let (mut publisher, mut subscriber) = async_bounded::<usize>(8);
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let waker = waker(Arc::new(WakeLog {
+        id: 0,
+        log: log.clone(),
+    }));
+    let mut sub_cx = Context::from_waker(&waker);
+    assert_eq!(subscriber.poll_next_unpin(&mut sub_cx), Poll::Pending);
+
+    // A send cancelled after the item was started but before it was flushed.
+    {
+        let mut guard = publisher.guard();
+        assert_eq!(guard.poll_ready_unpin(&mut cx), Poll::Ready(Ok(())));
+        guard.start_send_unpin(1).unwrap();
+        assert!(log.lock().unwrap().is_empty());
+    }
+    assert_eq!(*log.lock().unwrap(), vec![0]);
+    assert_eq!(
+        subscriber.poll_next_unpin(&mut sub_cx),
+        Poll::Ready(Some(Arc::new(1)))
+    );
+}
+
+#[test]
+fn read_quota_refill_wakes_the_stream() {
+    use futures::executor::block_on;
+
+    let (mut publisher, subscriber) = Builder::new(128)
+        .read_quota(100)
+        .build_async::<usize, Slot<usize>>();
+    block_on(async {
+        for i in 0..101 {
+            publisher.send(i).await.unwrap();
+        }
+    });
+    // The publisher stays open and quiet, the last item is let through by the quota.
+    assert_eq!(block_on(subscriber.take(101).count()), 101);
+    drop(publisher);
+}
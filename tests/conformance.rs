@@ -0,0 +1,37 @@
+use bus_queue::conformance::check;
+
+#[cfg(feature = "arcswap")]
+#[test]
+fn arc_swap_conformance() {
+    check::<bus_queue::flavors::arc_swap::Slot<u64>>();
+}
+
+#[cfg(feature = "rwlock")]
+#[test]
+fn rw_lock_conformance() {
+    check::<bus_queue::flavors::rw_lock::Slot<u64>>();
+}
+
+#[cfg(feature = "atomic-arc")]
+#[test]
+fn atomic_arc_conformance() {
+    check::<bus_queue::flavors::atomic_arc::Slot<u64>>();
+}
+
+#[cfg(feature = "arcswap")]
+#[test]
+fn cache_padded_conformance() {
+    check::<bus_queue::CachePadded<bus_queue::flavors::arc_swap::Slot<u64>>>();
+}
+
+#[cfg(feature = "arcswap")]
+#[test]
+fn arena_conformance() {
+    check::<bus_queue::flavors::arena::Slot<u64>>();
+}
+
+#[cfg(feature = "small-copy")]
+#[test]
+fn small_copy_conformance() {
+    check::<bus_queue::flavors::small_copy::Slot<u64>>();
+}